@@ -0,0 +1,377 @@
+//! Generic aggregation engine for document statistics.
+//!
+//! [`get_document_stats`](crate::qdrant::QdrantService::get_document_stats)
+//! hard-codes one fixed set of counts. This module generalizes that into a
+//! declarative tree of bucket and metric aggregations (terms buckets with
+//! nested sub-aggregations, count/sum/avg/min/max, and fixed-width
+//! histograms), evaluated in a single pass over scrolled payloads via
+//! [`run_aggregation`]. Each aggregation accumulates into a [`Partial`] that
+//! merges associatively (`merge` is commutative/associative over disjoint
+//! input), so the same partials produced from independent scroll pages (or,
+//! eventually, independent Qdrant shards) can be combined without
+//! re-scanning already-processed documents.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// One requested aggregation: either a `terms` bucket (group by the string
+/// value of `field`, with nested sub-aggregations per group) or a metric
+/// computed over a numeric payload field.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AggregationSpec {
+    Terms {
+        field: String,
+        #[serde(default)]
+        aggs: HashMap<String, AggregationSpec>,
+    },
+    Count,
+    Sum { field: String },
+    Avg { field: String },
+    Min { field: String },
+    Max { field: String },
+    /// Fixed-width histogram of a numeric field.
+    Histogram { field: String, bucket_width: f64 },
+}
+
+/// Top-level aggregation request: named aggregations evaluated over the
+/// same document scope.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AggregationRequest {
+    pub aggs: HashMap<String, AggregationSpec>,
+}
+
+/// Finalized result for one named aggregation, mirroring the shape of its
+/// [`AggregationSpec`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum AggregationResult {
+    Terms(HashMap<String, HashMap<String, AggregationResult>>),
+    Count(u64),
+    Number(f64),
+    Histogram(Vec<HistogramBucket>),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HistogramBucket {
+    pub range_start: f64,
+    pub count: u64,
+}
+
+/// Mergeable partial state for one aggregation. `Avg` tracks a running
+/// sum/count rather than a running average so it stays associative:
+/// `merge(avg(a), avg(b))` must equal the true average over `a ++ b`.
+#[derive(Debug, Clone)]
+enum Partial {
+    Terms(HashMap<String, PartialTree>),
+    Count(u64),
+    Sum(f64),
+    Avg { sum: f64, count: u64 },
+    Min(Option<f64>),
+    Max(Option<f64>),
+    Histogram {
+        bucket_width: f64,
+        buckets: HashMap<i64, u64>,
+    },
+}
+
+type PartialTree = HashMap<String, Partial>;
+
+impl Partial {
+    fn init(spec: &AggregationSpec) -> Partial {
+        match spec {
+            AggregationSpec::Terms { .. } => Partial::Terms(HashMap::new()),
+            AggregationSpec::Count => Partial::Count(0),
+            AggregationSpec::Sum { .. } => Partial::Sum(0.0),
+            AggregationSpec::Avg { .. } => Partial::Avg { sum: 0.0, count: 0 },
+            AggregationSpec::Min { .. } => Partial::Min(None),
+            AggregationSpec::Max { .. } => Partial::Max(None),
+            AggregationSpec::Histogram { bucket_width, .. } => Partial::Histogram {
+                bucket_width: *bucket_width,
+                buckets: HashMap::new(),
+            },
+        }
+    }
+
+    /// Feeds one document's payload into this partial, per `spec`.
+    fn accumulate(&mut self, spec: &AggregationSpec, payload: &serde_json::Value) {
+        match (self, spec) {
+            (Partial::Terms(groups), AggregationSpec::Terms { field, aggs }) => {
+                let Some(key) = payload.get(field).and_then(bucket_key) else {
+                    return;
+                };
+                let group = groups.entry(key).or_insert_with(|| init_tree(aggs));
+                for (name, sub_spec) in aggs {
+                    group.get_mut(name).expect("tree built from the same spec").accumulate(sub_spec, payload);
+                }
+            }
+            (Partial::Count(n), AggregationSpec::Count) => *n += 1,
+            (Partial::Sum(total), AggregationSpec::Sum { field }) => {
+                if let Some(v) = payload.get(field).and_then(|v| v.as_f64()) {
+                    *total += v;
+                }
+            }
+            (Partial::Avg { sum, count }, AggregationSpec::Avg { field }) => {
+                if let Some(v) = payload.get(field).and_then(|v| v.as_f64()) {
+                    *sum += v;
+                    *count += 1;
+                }
+            }
+            (Partial::Min(min), AggregationSpec::Min { field }) => {
+                if let Some(v) = payload.get(field).and_then(|v| v.as_f64()) {
+                    *min = Some(min.map_or(v, |m| m.min(v)));
+                }
+            }
+            (Partial::Max(max), AggregationSpec::Max { field }) => {
+                if let Some(v) = payload.get(field).and_then(|v| v.as_f64()) {
+                    *max = Some(max.map_or(v, |m| m.max(v)));
+                }
+            }
+            (Partial::Histogram { bucket_width, buckets }, AggregationSpec::Histogram { field, .. }) => {
+                if let Some(v) = payload.get(field).and_then(|v| v.as_f64()) {
+                    let bucket = (v / *bucket_width).floor() as i64;
+                    *buckets.entry(bucket).or_insert(0) += 1;
+                }
+            }
+            _ => unreachable!("partial/spec shape mismatch: partials are only built by Partial::init from this same spec"),
+        }
+    }
+
+    /// Associatively merges `other` into `self`.
+    fn merge(&mut self, other: Partial) {
+        match (self, other) {
+            (Partial::Terms(a), Partial::Terms(b)) => {
+                for (key, other_group) in b {
+                    match a.get_mut(&key) {
+                        Some(group) => merge_tree(group, other_group),
+                        None => {
+                            a.insert(key, other_group);
+                        }
+                    }
+                }
+            }
+            (Partial::Count(a), Partial::Count(b)) => *a += b,
+            (Partial::Sum(a), Partial::Sum(b)) => *a += b,
+            (Partial::Avg { sum: sa, count: ca }, Partial::Avg { sum: sb, count: cb }) => {
+                *sa += sb;
+                *ca += cb;
+            }
+            (Partial::Min(a), Partial::Min(b)) => {
+                *a = match (*a, b) {
+                    (Some(x), Some(y)) => Some(x.min(y)),
+                    (Some(x), None) => Some(x),
+                    (None, y) => y,
+                };
+            }
+            (Partial::Max(a), Partial::Max(b)) => {
+                *a = match (*a, b) {
+                    (Some(x), Some(y)) => Some(x.max(y)),
+                    (Some(x), None) => Some(x),
+                    (None, y) => y,
+                };
+            }
+            (Partial::Histogram { buckets: a, .. }, Partial::Histogram { buckets: b, .. }) => {
+                for (bucket, count) in b {
+                    *a.entry(bucket).or_insert(0) += count;
+                }
+            }
+            _ => unreachable!("partial shape mismatch: both sides are built from the same spec tree"),
+        }
+    }
+
+    fn finalize(self) -> AggregationResult {
+        match self {
+            Partial::Terms(groups) => AggregationResult::Terms(
+                groups.into_iter().map(|(key, tree)| (key, finalize_tree(tree))).collect(),
+            ),
+            Partial::Count(n) => AggregationResult::Count(n),
+            Partial::Sum(total) => AggregationResult::Number(total),
+            Partial::Avg { sum, count } => {
+                AggregationResult::Number(if count == 0 { 0.0 } else { sum / count as f64 })
+            }
+            Partial::Min(min) => AggregationResult::Number(min.unwrap_or(0.0)),
+            Partial::Max(max) => AggregationResult::Number(max.unwrap_or(0.0)),
+            Partial::Histogram { bucket_width, buckets } => {
+                let mut sorted: Vec<_> = buckets.into_iter().collect();
+                sorted.sort_by_key(|(bucket, _)| *bucket);
+                AggregationResult::Histogram(
+                    sorted
+                        .into_iter()
+                        .map(|(bucket, count)| HistogramBucket {
+                            range_start: bucket as f64 * bucket_width,
+                            count,
+                        })
+                        .collect(),
+                )
+            }
+        }
+    }
+}
+
+fn init_tree(specs: &HashMap<String, AggregationSpec>) -> PartialTree {
+    specs.iter().map(|(name, spec)| (name.clone(), Partial::init(spec))).collect()
+}
+
+fn merge_tree(tree: &mut PartialTree, other: PartialTree) {
+    for (name, other_partial) in other {
+        tree.get_mut(&name).expect("tree built from the same spec").merge(other_partial);
+    }
+}
+
+fn finalize_tree(tree: PartialTree) -> HashMap<String, AggregationResult> {
+    tree.into_iter().map(|(name, partial)| (name, partial.finalize())).collect()
+}
+
+fn bucket_key(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        serde_json::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Runs `request` against `payloads` in a single pass, building the partial
+/// tree and finalizing it at the end.
+pub fn run_aggregation(
+    request: &AggregationRequest,
+    payloads: impl Iterator<Item = serde_json::Value>,
+) -> HashMap<String, AggregationResult> {
+    let mut tree = init_tree(&request.aggs);
+    for payload in payloads {
+        for (name, spec) in &request.aggs {
+            tree.get_mut(name).expect("tree built from the same spec").accumulate(spec, &payload);
+        }
+    }
+    finalize_tree(tree)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn spec(aggs: HashMap<String, AggregationSpec>) -> AggregationRequest {
+        AggregationRequest { aggs }
+    }
+
+    #[test]
+    fn test_count_and_avg_over_flat_documents() {
+        let mut aggs = HashMap::new();
+        aggs.insert("total".to_string(), AggregationSpec::Count);
+        aggs.insert(
+            "avg_len".to_string(),
+            AggregationSpec::Avg { field: "len".to_string() },
+        );
+        let request = spec(aggs);
+
+        let docs = vec![json!({"len": 10.0}), json!({"len": 20.0}), json!({"len": 30.0})];
+        let result = run_aggregation(&request, docs.into_iter());
+
+        match &result["total"] {
+            AggregationResult::Count(n) => assert_eq!(*n, 3),
+            other => panic!("expected Count, got {:?}", other),
+        }
+        match &result["avg_len"] {
+            AggregationResult::Number(avg) => assert!((*avg - 20.0).abs() < 1e-9),
+            other => panic!("expected Number, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_nested_terms_bucket_aggregates_per_group() {
+        let mut inner = HashMap::new();
+        inner.insert("count".to_string(), AggregationSpec::Count);
+        let mut aggs = HashMap::new();
+        aggs.insert(
+            "by_group".to_string(),
+            AggregationSpec::Terms {
+                field: "group_key".to_string(),
+                aggs: inner,
+            },
+        );
+        let request = spec(aggs);
+
+        let docs = vec![
+            json!({"group_key": "a"}),
+            json!({"group_key": "a"}),
+            json!({"group_key": "b"}),
+        ];
+        let result = run_aggregation(&request, docs.into_iter());
+
+        let AggregationResult::Terms(groups) = &result["by_group"] else {
+            panic!("expected Terms");
+        };
+        match &groups["a"]["count"] {
+            AggregationResult::Count(n) => assert_eq!(*n, 2),
+            other => panic!("expected Count, got {:?}", other),
+        }
+        match &groups["b"]["count"] {
+            AggregationResult::Count(n) => assert_eq!(*n, 1),
+            other => panic!("expected Count, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_histogram_buckets_by_fixed_width() {
+        let mut aggs = HashMap::new();
+        aggs.insert(
+            "sizes".to_string(),
+            AggregationSpec::Histogram {
+                field: "size".to_string(),
+                bucket_width: 10.0,
+            },
+        );
+        let request = spec(aggs);
+
+        let docs = vec![json!({"size": 3.0}), json!({"size": 7.0}), json!({"size": 15.0})];
+        let result = run_aggregation(&request, docs.into_iter());
+
+        let AggregationResult::Histogram(buckets) = &result["sizes"] else {
+            panic!("expected Histogram");
+        };
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].range_start, 0.0);
+        assert_eq!(buckets[0].count, 2);
+        assert_eq!(buckets[1].range_start, 10.0);
+        assert_eq!(buckets[1].count, 1);
+    }
+
+    #[test]
+    fn test_partial_merge_matches_single_pass_over_combined_input() {
+        let mut aggs = HashMap::new();
+        aggs.insert(
+            "avg_len".to_string(),
+            AggregationSpec::Avg { field: "len".to_string() },
+        );
+        let request = spec(aggs);
+
+        let batch_a = vec![json!({"len": 10.0}), json!({"len": 20.0})];
+        let batch_b = vec![json!({"len": 30.0})];
+        let combined: Vec<_> = batch_a.iter().cloned().chain(batch_b.iter().cloned()).collect();
+
+        let mut tree_a = init_tree(&request.aggs);
+        for doc in &batch_a {
+            for (name, s) in &request.aggs {
+                tree_a.get_mut(name).unwrap().accumulate(s, doc);
+            }
+        }
+        let mut tree_b = init_tree(&request.aggs);
+        for doc in &batch_b {
+            for (name, s) in &request.aggs {
+                tree_b.get_mut(name).unwrap().accumulate(s, doc);
+            }
+        }
+        merge_tree(&mut tree_a, tree_b);
+        let merged = finalize_tree(tree_a);
+
+        let single_pass = run_aggregation(&request, combined.into_iter());
+
+        match (&merged["avg_len"], &single_pass["avg_len"]) {
+            (AggregationResult::Number(a), AggregationResult::Number(b)) => {
+                assert!((a - b).abs() < 1e-9);
+            }
+            other => panic!("expected matching Numbers, got {:?}", other),
+        }
+    }
+}
@@ -1,16 +1,10 @@
+use async_trait::async_trait;
 use serde_json::json;
+use std::sync::Arc;
 use tracing::{error, info};
 
 use crate::stats::StatsSnapshot;
 
-/// Generic webhook alerts system
-/// Supports Discord, Slack, Telegram, or any webhook-compatible service
-#[derive(Clone)]
-pub struct WebhookAlerts {
-    webhook_url: Option<String>,
-    client: reqwest::Client,
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AlertLevel {
     Info,
@@ -19,71 +13,405 @@ pub enum AlertLevel {
     Critical,
 }
 
-impl WebhookAlerts {
-    pub fn new(webhook_url: Option<String>) -> Self {
+fn level_color(level: AlertLevel) -> u32 {
+    match level {
+        AlertLevel::Info => 0x3498db,     // Blue
+        AlertLevel::Warning => 0xf39c12,  // Orange
+        AlertLevel::Error => 0xe74c3c,    // Red
+        AlertLevel::Critical => 0x992d22, // Dark Red
+    }
+}
+
+fn level_emoji(level: AlertLevel) -> &'static str {
+    match level {
+        AlertLevel::Info => "ℹ️",
+        AlertLevel::Warning => "⚠️",
+        AlertLevel::Error => "❌",
+        AlertLevel::Critical => "🚨",
+    }
+}
+
+/// Which downstream chat platform a [`WebhookSink`] is posting to, since
+/// each expects a different JSON shape for the same `(level, title,
+/// message)` alert. Chosen either explicitly (`WebhookSink::with_provider`)
+/// or auto-detected from the webhook URL's host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookProvider {
+    Discord,
+    Slack,
+    Telegram,
+    /// Unrecognized host; falls back to the Discord embed shape, which a
+    /// number of self-hosted chat tools (Mattermost, Rocket.Chat) also accept.
+    Generic,
+}
+
+impl WebhookProvider {
+    /// Auto-detects the provider from the webhook URL's host.
+    pub fn detect(url: &str) -> Self {
+        if url.contains("hooks.slack.com") {
+            WebhookProvider::Slack
+        } else if url.contains("api.telegram.org") {
+            WebhookProvider::Telegram
+        } else if url.contains("discord.com/api/webhooks") || url.contains("discordapp.com/api/webhooks") {
+            WebhookProvider::Discord
+        } else {
+            WebhookProvider::Generic
+        }
+    }
+
+    /// Parses an explicit `WEBHOOK_PROVIDER` config override.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "discord" => Some(WebhookProvider::Discord),
+            "slack" => Some(WebhookProvider::Slack),
+            "telegram" => Some(WebhookProvider::Telegram),
+            "generic" => Some(WebhookProvider::Generic),
+            _ => None,
+        }
+    }
+}
+
+/// Escapes the characters Telegram's MarkdownV2 treats as formatting
+/// (<https://core.telegram.org/bots/api#markdownv2-style>) so arbitrary
+/// alert text - which may contain `.`, `-`, `_`, parens, etc. - renders as
+/// plain text instead of breaking the parser or getting silently dropped.
+fn escape_markdown_v2(text: &str) -> String {
+    const SPECIAL: &[char] = &[
+        '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.', '!',
+    ];
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if SPECIAL.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn telegram_alert_text(level: AlertLevel, title: &str, message: &str) -> String {
+    format!(
+        "{} *{}*\n{}",
+        level_emoji(level),
+        escape_markdown_v2(title),
+        escape_markdown_v2(message)
+    )
+}
+
+fn telegram_daily_stats_text(stats: &StatsSnapshot, collection_name: &str) -> String {
+    format!(
+        "📊 *Daily Statistics Report*\nCollection: `{}`\n\n⬆️ Upserted: *{}*\n🔍 Searches: *{}*\n🗑️ Deleted: *{}*\n⏱️ Uptime: `{}`",
+        escape_markdown_v2(collection_name),
+        format_number(stats.upserts),
+        format_number(stats.searches),
+        format_number(stats.deletes),
+        stats.format_uptime(),
+    )
+}
+
+fn discord_alert_payload(level: AlertLevel, title: &str, message: &str) -> serde_json::Value {
+    json!({
+        "embeds": [{
+            "title": format!("{} {}", level_emoji(level), title),
+            "description": message,
+            "color": level_color(level),
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "footer": {
+                "text": "Synaplan Qdrant Service"
+            }
+        }]
+    })
+}
+
+fn discord_daily_stats_payload(stats: &StatsSnapshot, collection_name: &str) -> serde_json::Value {
+    json!({
+        "embeds": [{
+            "title": "📊 Daily Statistics Report",
+            "description": format!("Statistics for collection `{}`", collection_name),
+            "color": 0x2ecc71, // Green
+            "fields": [
+                {
+                    "name": "⬆️ Vectors Upserted",
+                    "value": format!("**{}**", format_number(stats.upserts)),
+                    "inline": true
+                },
+                {
+                    "name": "🔍 Searches Performed",
+                    "value": format!("**{}**", format_number(stats.searches)),
+                    "inline": true
+                },
+                {
+                    "name": "🗑️ Vectors Deleted",
+                    "value": format!("**{}**", format_number(stats.deletes)),
+                    "inline": true
+                },
+                {
+                    "name": "⏱️ Uptime",
+                    "value": format!("`{}`", stats.format_uptime()),
+                    "inline": false
+                }
+            ],
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "footer": {
+                "text": "Synaplan Qdrant Service · Daily Report"
+            }
+        }]
+    })
+}
+
+/// Slack's incoming-webhook API ignores Discord-style `embeds`; it wants
+/// `attachments` (for the color bar) wrapping Block Kit `blocks`.
+fn slack_alert_payload(level: AlertLevel, title: &str, message: &str) -> serde_json::Value {
+    json!({
+        "attachments": [{
+            "color": format!("#{:06x}", level_color(level)),
+            "blocks": [{
+                "type": "section",
+                "text": {
+                    "type": "mrkdwn",
+                    "text": format!("*{} {}*\n{}", level_emoji(level), title, message)
+                }
+            }]
+        }]
+    })
+}
+
+fn slack_daily_stats_payload(stats: &StatsSnapshot, collection_name: &str) -> serde_json::Value {
+    json!({
+        "attachments": [{
+            "color": "#2ecc71",
+            "blocks": [
+                {
+                    "type": "section",
+                    "text": {
+                        "type": "mrkdwn",
+                        "text": format!("*📊 Daily Statistics Report*\nCollection: `{}`", collection_name)
+                    }
+                },
+                {
+                    "type": "section",
+                    "fields": [
+                        { "type": "mrkdwn", "text": format!("*⬆️ Upserted:*\n{}", format_number(stats.upserts)) },
+                        { "type": "mrkdwn", "text": format!("*🔍 Searches:*\n{}", format_number(stats.searches)) },
+                        { "type": "mrkdwn", "text": format!("*🗑️ Deleted:*\n{}", format_number(stats.deletes)) },
+                        { "type": "mrkdwn", "text": format!("*⏱️ Uptime:*\n{}", stats.format_uptime()) }
+                    ]
+                }
+            ]
+        }]
+    })
+}
+
+fn telegram_alert_payload(
+    level: AlertLevel,
+    title: &str,
+    message: &str,
+    chat_id: Option<&str>,
+) -> serde_json::Value {
+    json!({
+        "chat_id": chat_id.unwrap_or_default(),
+        "text": telegram_alert_text(level, title, message),
+        "parse_mode": "MarkdownV2",
+        "disable_web_page_preview": true,
+    })
+}
+
+fn telegram_daily_stats_payload(
+    stats: &StatsSnapshot,
+    collection_name: &str,
+    chat_id: Option<&str>,
+) -> serde_json::Value {
+    json!({
+        "chat_id": chat_id.unwrap_or_default(),
+        "text": telegram_daily_stats_text(stats, collection_name),
+        "parse_mode": "MarkdownV2",
+        "disable_web_page_preview": true,
+    })
+}
+
+/// A destination that can receive operational alerts.
+///
+/// Implementations format the same `(level, title, message)` triple (or
+/// daily-stats snapshot) however their downstream service expects it -
+/// Discord-style embeds, Telegram Markdown, etc. `WebhookAlerts` fans out to
+/// every configured sink, so adding a new channel (e.g. Slack) only requires
+/// a new impl, not changes to call sites.
+#[async_trait]
+pub trait AlertSink: Send + Sync {
+    async fn send_alert(&self, level: AlertLevel, title: &str, message: &str);
+    async fn send_daily_stats(&self, stats: &StatsSnapshot, collection_name: &str);
+}
+
+/// Generic webhook sink. Formats payloads for whichever chat platform
+/// `provider` resolves to (Discord embeds, Slack attachments/blocks,
+/// Telegram MarkdownV2) so the same `WEBHOOK_URL` config works against any
+/// of them without per-provider wiring elsewhere.
+#[derive(Clone)]
+pub struct WebhookSink {
+    webhook_url: String,
+    client: reqwest::Client,
+    provider: WebhookProvider,
+    /// Chat ID for a Telegram `sendMessage` endpoint reached through this
+    /// generic sink (rather than the dedicated [`TelegramSink`]). Ignored
+    /// by every other provider.
+    telegram_chat_id: Option<String>,
+}
+
+impl WebhookSink {
+    /// Auto-detects the provider from `webhook_url`'s host.
+    pub fn new(webhook_url: String) -> Self {
+        Self::with_provider(webhook_url, None, None)
+    }
+
+    /// `provider_override` takes precedence over host-based auto-detection;
+    /// `telegram_chat_id` is required for a `Telegram`-resolved provider.
+    pub fn with_provider(
+        webhook_url: String,
+        provider_override: Option<WebhookProvider>,
+        telegram_chat_id: Option<String>,
+    ) -> Self {
+        let provider = provider_override.unwrap_or_else(|| WebhookProvider::detect(&webhook_url));
         Self {
             webhook_url,
             client: reqwest::Client::new(),
+            provider,
+            telegram_chat_id,
         }
     }
 
-    pub fn is_enabled(&self) -> bool {
-        self.webhook_url.is_some()
+    async fn post_json(&self, payload: serde_json::Value, context: &str) {
+        match self.client.post(&self.webhook_url).json(&payload).send().await {
+            Ok(response) => {
+                if response.status().is_success() {
+                    info!("{} sent", context);
+                } else {
+                    error!("Failed to send {}: HTTP {}", context, response.status());
+                }
+            }
+            Err(e) => {
+                error!("Failed to send {}: {}", context, e);
+            }
+        }
     }
+}
 
-    /// Send an alert via webhook (Discord/Slack/Telegram compatible format)
-    pub async fn send_alert(&self, level: AlertLevel, title: &str, message: &str) {
-        if !self.is_enabled() {
-            return;
-        }
+#[async_trait]
+impl AlertSink for WebhookSink {
+    async fn send_alert(&self, level: AlertLevel, title: &str, message: &str) {
+        let payload = match self.provider {
+            WebhookProvider::Slack => slack_alert_payload(level, title, message),
+            WebhookProvider::Telegram => {
+                telegram_alert_payload(level, title, message, self.telegram_chat_id.as_deref())
+            }
+            WebhookProvider::Discord | WebhookProvider::Generic => {
+                discord_alert_payload(level, title, message)
+            }
+        };
 
-        let webhook_url = self.webhook_url.as_ref().unwrap();
+        self.post_json(payload, &format!("webhook alert: {title} - {message}"))
+            .await;
+    }
 
-        // Choose color based on level
-        let color = match level {
-            AlertLevel::Info => 0x3498db,      // Blue
-            AlertLevel::Warning => 0xf39c12,   // Orange
-            AlertLevel::Error => 0xe74c3c,     // Red
-            AlertLevel::Critical => 0x992d22,  // Dark Red
+    async fn send_daily_stats(&self, stats: &StatsSnapshot, collection_name: &str) {
+        let payload = match self.provider {
+            WebhookProvider::Slack => slack_daily_stats_payload(stats, collection_name),
+            WebhookProvider::Telegram => {
+                telegram_daily_stats_payload(stats, collection_name, self.telegram_chat_id.as_deref())
+            }
+            WebhookProvider::Discord | WebhookProvider::Generic => {
+                discord_daily_stats_payload(stats, collection_name)
+            }
         };
 
-        // Choose emoji based on level
-        let emoji = match level {
-            AlertLevel::Info => "ℹ️",
-            AlertLevel::Warning => "⚠️",
-            AlertLevel::Error => "❌",
-            AlertLevel::Critical => "🚨",
-        };
+        self.post_json(payload, "daily stats webhook").await;
+    }
+}
 
-        // Discord/Slack-compatible webhook payload
+/// Telegram Bot API sink. Posts Markdown-formatted messages to a chat via
+/// `https://api.telegram.org/bot<token>/sendMessage`, so operators can get
+/// human-readable alerts without standing up a webhook relay.
+#[derive(Clone)]
+pub struct TelegramSink {
+    bot_token: String,
+    chat_id: String,
+    client: reqwest::Client,
+}
+
+impl TelegramSink {
+    pub fn new(bot_token: String, chat_id: String) -> Self {
+        Self {
+            bot_token,
+            chat_id,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn send_message_url(&self) -> String {
+        format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token)
+    }
+
+    async fn post_markdown(&self, text: String) {
         let payload = json!({
-            "embeds": [{
-                "title": format!("{} {}", emoji, title),
-                "description": message,
-                "color": color,
-                "timestamp": chrono::Utc::now().to_rfc3339(),
-                "footer": {
-                    "text": "Synaplan Qdrant Service"
-                }
-            }]
+            "chat_id": self.chat_id,
+            "text": text,
+            "parse_mode": "MarkdownV2",
+            "disable_web_page_preview": true,
         });
 
-        match self.client.post(webhook_url).json(&payload).send().await {
+        match self.client.post(self.send_message_url()).json(&payload).send().await {
             Ok(response) => {
                 if response.status().is_success() {
-                    info!("Webhook alert sent: {} - {}", title, message);
+                    info!("Telegram alert sent");
                 } else {
-                    error!(
-                        "Failed to send webhook alert: HTTP {}",
-                        response.status()
-                    );
+                    error!("Failed to send Telegram alert: HTTP {}", response.status());
                 }
             }
             Err(e) => {
-                error!("Failed to send webhook alert: {}", e);
+                error!("Failed to send Telegram alert: {}", e);
             }
         }
     }
+}
+
+#[async_trait]
+impl AlertSink for TelegramSink {
+    async fn send_alert(&self, level: AlertLevel, title: &str, message: &str) {
+        self.post_markdown(telegram_alert_text(level, title, message)).await;
+    }
+
+    async fn send_daily_stats(&self, stats: &StatsSnapshot, collection_name: &str) {
+        self.post_markdown(telegram_daily_stats_text(stats, collection_name))
+            .await;
+    }
+}
+
+/// Fan-out alert broadcaster.
+///
+/// Holds zero or more [`AlertSink`]s (generic webhook, Telegram, ...) and
+/// broadcasts every alert to all of them. Disabled (no-op) when no sinks are
+/// configured.
+#[derive(Clone)]
+pub struct WebhookAlerts {
+    sinks: Arc<Vec<Arc<dyn AlertSink>>>,
+}
+
+impl WebhookAlerts {
+    pub fn new(sinks: Vec<Arc<dyn AlertSink>>) -> Self {
+        Self {
+            sinks: Arc::new(sinks),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.sinks.is_empty()
+    }
+
+    /// Send an alert to every configured sink.
+    pub async fn send_alert(&self, level: AlertLevel, title: &str, message: &str) {
+        for sink in self.sinks.iter() {
+            sink.send_alert(level, title, message).await;
+        }
+    }
 
     /// Alert when service starts
     pub async fn alert_service_started(&self, version: &str) {
@@ -107,7 +435,6 @@ impl WebhookAlerts {
 
     /// Alert when error rate is high
     pub async fn alert_high_error_rate(&self, error_rate: f64, failed: u64, total: u64) {
-        // Ensure Warning is used in release builds (avoid dead_code warnings)
         let level = if error_rate >= 20.0 {
             AlertLevel::Error
         } else {
@@ -125,64 +452,33 @@ impl WebhookAlerts {
         .await;
     }
 
-    /// Send daily statistics report (Discord-optimized format)
+    /// Alert when the batch job queue is close to saturation
+    pub async fn alert_job_queue_saturated(&self, queue_depth: usize, capacity: usize) {
+        self.send_alert(
+            AlertLevel::Warning,
+            "Batch Job Queue Saturated",
+            &format!(
+                "Job queue depth is {} of {} ({:.0}% full) - batch upserts may start being rejected",
+                queue_depth,
+                capacity,
+                (queue_depth as f64 / capacity as f64) * 100.0
+            ),
+        )
+        .await;
+    }
+
+    /// Send daily statistics report to every configured sink.
     pub async fn send_daily_stats(&self, stats: &StatsSnapshot, collection_name: &str) {
-        if !self.is_enabled() {
-            return;
+        for sink in self.sinks.iter() {
+            sink.send_daily_stats(stats, collection_name).await;
         }
+    }
 
-        let webhook_url = self.webhook_url.as_ref().unwrap();
-
-        // Discord embed with rich formatting
-        let payload = json!({
-            "embeds": [{
-                "title": "📊 Daily Statistics Report",
-                "description": format!("Statistics for collection `{}`", collection_name),
-                "color": 0x2ecc71, // Green
-                "fields": [
-                    {
-                        "name": "⬆️ Vectors Upserted",
-                        "value": format!("**{}**", format_number(stats.upserts)),
-                        "inline": true
-                    },
-                    {
-                        "name": "🔍 Searches Performed",
-                        "value": format!("**{}**", format_number(stats.searches)),
-                        "inline": true
-                    },
-                    {
-                        "name": "🗑️ Vectors Deleted",
-                        "value": format!("**{}**", format_number(stats.deletes)),
-                        "inline": true
-                    },
-                    {
-                        "name": "⏱️ Uptime",
-                        "value": format!("`{}`", stats.format_uptime()),
-                        "inline": false
-                    }
-                ],
-                "timestamp": chrono::Utc::now().to_rfc3339(),
-                "footer": {
-                    "text": "Synaplan Qdrant Service · Daily Report"
-                }
-            }]
-        });
-
-        match self.client.post(webhook_url).json(&payload).send().await {
-            Ok(response) => {
-                if response.status().is_success() {
-                    info!("Daily stats webhook sent successfully");
-                } else {
-                    error!(
-                        "Failed to send daily stats webhook: HTTP {}",
-                        response.status()
-                    );
-                }
-            }
-            Err(e) => {
-                error!("Failed to send daily stats webhook: {}", e);
-            }
-        }
+    /// Alert on a process panic. `report` is the preformatted, already
+    /// length-capped panic message plus demangled backtrace produced by
+    /// [`crate::panic_hook`].
+    pub async fn alert_panic(&self, report: &str) {
+        self.send_alert(AlertLevel::Critical, "Panic", report).await;
     }
 }
 
@@ -203,13 +499,15 @@ mod tests {
 
     #[test]
     fn test_webhook_alerts_disabled() {
-        let alerts = WebhookAlerts::new(None);
+        let alerts = WebhookAlerts::new(vec![]);
         assert!(!alerts.is_enabled());
     }
 
     #[test]
     fn test_webhook_alerts_enabled() {
-        let alerts = WebhookAlerts::new(Some("https://example.com/webhook".to_string()));
+        let sinks: Vec<Arc<dyn AlertSink>> =
+            vec![Arc::new(WebhookSink::new("https://example.com/webhook".to_string()))];
+        let alerts = WebhookAlerts::new(sinks);
         assert!(alerts.is_enabled());
     }
 
@@ -235,7 +533,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_send_alert_disabled() {
-        let alerts = WebhookAlerts::new(None);
+        let alerts = WebhookAlerts::new(vec![]);
         // Should not panic or fail when disabled
         alerts.send_alert(AlertLevel::Info, "Test", "Message").await;
     }
@@ -248,5 +546,64 @@ mod tests {
         assert_eq!(format_number(1234567), "1,234,567");
         assert_eq!(format_number(1000000000), "1,000,000,000");
     }
-}
 
+    #[test]
+    fn test_telegram_sink_send_message_url() {
+        let sink = TelegramSink::new("abc123".to_string(), "-100999".to_string());
+        assert_eq!(
+            sink.send_message_url(),
+            "https://api.telegram.org/botabc123/sendMessage"
+        );
+    }
+
+    #[test]
+    fn test_webhook_provider_detection() {
+        assert_eq!(
+            WebhookProvider::detect("https://hooks.slack.com/services/T000/B000/XXX"),
+            WebhookProvider::Slack
+        );
+        assert_eq!(
+            WebhookProvider::detect("https://api.telegram.org/botabc123/sendMessage"),
+            WebhookProvider::Telegram
+        );
+        assert_eq!(
+            WebhookProvider::detect("https://discord.com/api/webhooks/1/abc"),
+            WebhookProvider::Discord
+        );
+        assert_eq!(
+            WebhookProvider::detect("https://example.com/my-webhook"),
+            WebhookProvider::Generic
+        );
+    }
+
+    #[test]
+    fn test_webhook_provider_parse() {
+        assert_eq!(WebhookProvider::parse("Slack"), Some(WebhookProvider::Slack));
+        assert_eq!(WebhookProvider::parse("TELEGRAM"), Some(WebhookProvider::Telegram));
+        assert_eq!(WebhookProvider::parse("bogus"), None);
+    }
+
+    #[test]
+    fn test_escape_markdown_v2() {
+        assert_eq!(escape_markdown_v2("user_memories"), "user\\_memories");
+        assert_eq!(escape_markdown_v2("v1.0!"), "v1\\.0\\!");
+        assert_eq!(escape_markdown_v2("plain text"), "plain text");
+    }
+
+    #[test]
+    fn test_slack_alert_payload_has_color_bar_and_blocks() {
+        let payload = slack_alert_payload(AlertLevel::Critical, "Panic", "it broke");
+        assert_eq!(payload["attachments"][0]["color"], "#992d22");
+        assert!(payload["attachments"][0]["blocks"][0]["text"]["text"]
+            .as_str()
+            .unwrap()
+            .contains("it broke"));
+    }
+
+    #[test]
+    fn test_telegram_alert_payload_uses_markdown_v2_and_chat_id() {
+        let payload = telegram_alert_payload(AlertLevel::Warning, "Disk", "90% full", Some("-100999"));
+        assert_eq!(payload["chat_id"], "-100999");
+        assert_eq!(payload["parse_mode"], "MarkdownV2");
+    }
+}
@@ -1,28 +1,257 @@
 use axum::{
-    extract::{Request, State},
+    extract::{Path, Request, State},
     http::{HeaderMap, StatusCode},
     middleware::Next,
     response::Response,
+    Extension, Json,
 };
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
 
-/// Authentication state holding optional API key
-#[derive(Clone)]
+use crate::error::AppError;
+
+/// Action an API key may be scoped to perform.
+///
+/// `Admin` implicitly grants every other action (see [`ApiKey::allows`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Search,
+    Upsert,
+    Delete,
+    Stats,
+    Admin,
+}
+
+/// A single API key record: the opaque key string, its allowed actions,
+/// an optional collection/user scope, and an optional expiry.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiKey {
+    pub key: String,
+    pub actions: HashSet<Action>,
+    /// Restricts the key to a single user or namespace, checked by
+    /// [`ApiKeyPermissions::check_user_scope`]/[`ApiKeyPermissions::check_namespace_scope`]
+    /// against the `user_id`/`namespace` a request targets. One of:
+    /// - `user:<id>` — only requests for that `user_id` are allowed
+    /// - `namespace:<name>` — only requests against that `namespace` are allowed
+    ///
+    /// `None` leaves the key unscoped (subject only to `actions`).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub scope: Option<String>,
+    /// Unix timestamp after which this key is rejected. `None` never expires.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub expires_at: Option<i64>,
+}
+
+impl ApiKey {
+    pub fn is_expired(&self) -> bool {
+        match self.expires_at {
+            Some(expires_at) => chrono::Utc::now().timestamp() >= expires_at,
+            None => false,
+        }
+    }
+
+    pub fn allows(&self, action: Action) -> bool {
+        self.actions.contains(&Action::Admin) || self.actions.contains(&action)
+    }
+}
+
+/// The resolved permission set for the key presented on a request, injected
+/// into request extensions by [`auth_middleware`] so handlers can check
+/// `permissions.require(Action::Upsert)` without re-parsing headers.
+#[derive(Debug, Clone)]
+pub struct ApiKeyPermissions {
+    pub actions: HashSet<Action>,
+    pub scope: Option<String>,
+}
+
+impl ApiKeyPermissions {
+    /// Permissions for a request when authentication is disabled entirely
+    /// (no keys configured): everything is allowed.
+    fn unrestricted() -> Self {
+        let mut actions = HashSet::new();
+        actions.insert(Action::Admin);
+        Self {
+            actions,
+            scope: None,
+        }
+    }
+
+    fn allows(&self, action: Action) -> bool {
+        self.actions.contains(&Action::Admin) || self.actions.contains(&action)
+    }
+
+    /// Require `action`, returning a 403 [`AppError::Forbidden`] if the
+    /// presented key isn't scoped for it.
+    pub fn require(&self, action: Action) -> Result<(), AppError> {
+        if self.allows(action) {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden(format!(
+                "API key is not scoped for '{:?}'",
+                action
+            )))
+        }
+    }
+
+    /// Require that this key is allowed to touch `user_id`, returning a 403
+    /// [`AppError::Forbidden`] if the key's `scope` is `user:<other_id>`.
+    /// A key with no scope, scoped to this exact `user_id`, or scoped to a
+    /// namespace (checked separately by [`Self::check_namespace_scope`]) passes.
+    pub fn check_user_scope(&self, user_id: i64) -> Result<(), AppError> {
+        match self.scope.as_deref().and_then(|s| s.strip_prefix("user:")) {
+            Some(scoped_user_id) if scoped_user_id != user_id.to_string() => {
+                Err(AppError::Forbidden(format!(
+                    "API key is scoped to a different user (expected user_id {})",
+                    user_id
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Require that this key is allowed to touch `namespace`, returning a
+    /// 403 [`AppError::Forbidden`] if the key's `scope` is
+    /// `namespace:<other_name>`. A key with no scope, or scoped to this
+    /// exact namespace, passes; a `namespace:...`-scoped key also rejects
+    /// requests that omit the namespace (the default collection).
+    pub fn check_namespace_scope(&self, namespace: Option<&str>) -> Result<(), AppError> {
+        match self.scope.as_deref().and_then(|s| s.strip_prefix("namespace:")) {
+            Some(scoped_namespace) if Some(scoped_namespace) != namespace => {
+                Err(AppError::Forbidden(format!(
+                    "API key is scoped to a different namespace (expected '{}')",
+                    scoped_namespace
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct KeyStoreFile {
+    keys: Vec<ApiKey>,
+}
+
+/// Authentication state holding the full API key store.
+///
+/// Keys are persisted as a JSON file (`api_keys_path`) so the store survives
+/// restarts. On first boot with no store on disk, a bootstrap admin key is
+/// seeded from `SERVICE_API_KEY` so the `/keys` endpoints can be used to
+/// create real keys.
 pub struct AuthState {
-    pub api_key: Option<String>,
+    keys: RwLock<HashMap<String, ApiKey>>,
+    store_path: PathBuf,
 }
 
 impl AuthState {
-    /// Create new auth state with optional API key
-    #[inline]
-    pub fn new(api_key: Option<String>) -> Self {
-        Self { api_key }
+    /// Load the key store from disk, falling back to a bootstrap admin key
+    /// derived from `service_api_key` when no store exists yet.
+    pub fn load(store_path: impl AsRef<std::path::Path>, service_api_key: Option<String>) -> Self {
+        let store_path = store_path.as_ref().to_path_buf();
+
+        let keys = match std::fs::read_to_string(&store_path) {
+            Ok(contents) => match serde_json::from_str::<KeyStoreFile>(&contents) {
+                Ok(file) => {
+                    info!(
+                        "Loaded {} API key(s) from {}",
+                        file.keys.len(),
+                        store_path.display()
+                    );
+                    file.keys.into_iter().map(|k| (k.key.clone(), k)).collect()
+                }
+                Err(e) => {
+                    error!(
+                        "Failed to parse API key store {}: {} (starting empty)",
+                        store_path.display(),
+                        e
+                    );
+                    HashMap::new()
+                }
+            },
+            Err(_) => HashMap::new(),
+        };
+
+        let state = Self {
+            keys: RwLock::new(keys),
+            store_path,
+        };
+
+        if state.keys.read().unwrap().is_empty() {
+            if let Some(key) = service_api_key {
+                let mut actions = HashSet::new();
+                actions.insert(Action::Admin);
+                state.keys.write().unwrap().insert(
+                    key.clone(),
+                    ApiKey {
+                        key,
+                        actions,
+                        scope: None,
+                        expires_at: None,
+                    },
+                );
+                info!("Bootstrapped admin API key from SERVICE_API_KEY");
+            }
+        }
+
+        state
     }
 
-    /// Check if authentication is enabled
-    #[inline]
+    /// Check if authentication is enabled (at least one key is configured).
     pub fn is_enabled(&self) -> bool {
-        self.api_key.is_some()
+        !self.keys.read().unwrap().is_empty()
+    }
+
+    /// Resolve the permission set for a presented key, if valid and unexpired.
+    fn authenticate(&self, key: &str) -> Option<ApiKeyPermissions> {
+        let keys = self.keys.read().unwrap();
+        let record = keys.get(key)?;
+
+        if record.is_expired() {
+            warn!("Rejected expired API key (scope: {:?})", record.scope);
+            return None;
+        }
+
+        Some(ApiKeyPermissions {
+            actions: record.actions.clone(),
+            scope: record.scope.clone(),
+        })
+    }
+
+    pub fn list_keys(&self) -> Vec<ApiKey> {
+        self.keys.read().unwrap().values().cloned().collect()
+    }
+
+    pub async fn create_key(&self, key: ApiKey) -> Result<(), AppError> {
+        {
+            let mut keys = self.keys.write().unwrap();
+            keys.insert(key.key.clone(), key);
+        }
+        self.persist().await
+    }
+
+    pub async fn delete_key(&self, key: &str) -> Result<bool, AppError> {
+        let removed = {
+            let mut keys = self.keys.write().unwrap();
+            keys.remove(key).is_some()
+        };
+        if removed {
+            self.persist().await?;
+        }
+        Ok(removed)
+    }
+
+    async fn persist(&self) -> Result<(), AppError> {
+        let keys: Vec<ApiKey> = self.keys.read().unwrap().values().cloned().collect();
+        let contents = serde_json::to_string_pretty(&KeyStoreFile { keys })
+            .map_err(|e| AppError::Internal(format!("Failed to serialize API key store: {}", e)))?;
+        tokio::fs::write(&self.store_path, contents)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to persist API key store: {}", e)))
     }
 }
 
@@ -31,28 +260,128 @@ impl AuthState {
 pub async fn auth_middleware(
     State(auth_state): State<Arc<AuthState>>,
     headers: HeaderMap,
-    request: Request,
+    mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    // If no API key is configured, skip auth
-    let Some(expected_key) = &auth_state.api_key else {
+    // If no API keys are configured, skip auth entirely.
+    if !auth_state.is_enabled() {
+        request
+            .extensions_mut()
+            .insert(ApiKeyPermissions::unrestricted());
         return Ok(next.run(request).await);
-    };
+    }
 
-    // Check Authorization header (Bearer token)
     let auth_header = headers
         .get("Authorization")
         .and_then(|h| h.to_str().ok())
         .and_then(|h| h.strip_prefix("Bearer "));
 
-    // Check X-API-Key header (alternative)
     let api_key_header = headers.get("X-API-Key").and_then(|h| h.to_str().ok());
 
     let provided_key = auth_header.or(api_key_header);
 
-    match provided_key {
-        Some(key) if key == expected_key => Ok(next.run(request).await),
-        _ => Err(StatusCode::UNAUTHORIZED),
+    let Some(key) = provided_key else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+
+    match auth_state.authenticate(key) {
+        Some(permissions) => {
+            request.extensions_mut().insert(permissions);
+            Ok(next.run(request).await)
+        }
+        None => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+// --- /keys admin endpoints ---
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateKeyRequest {
+    pub key: String,
+    pub actions: HashSet<Action>,
+    #[serde(default)]
+    pub scope: Option<String>,
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+}
+
+/// Create or replace an API key
+///
+/// Requires a key scoped with `admin`.
+#[utoipa::path(
+    post,
+    path = "/keys",
+    tag = "Admin",
+    request_body = CreateKeyRequest,
+    responses(
+        (status = 200, description = "Key created", body = ApiKey),
+        (status = 403, description = "Caller is not scoped for 'admin'"),
+    )
+)]
+pub async fn create_key(
+    State(state): State<crate::AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
+    Json(req): Json<CreateKeyRequest>,
+) -> Result<Json<ApiKey>, AppError> {
+    permissions.require(Action::Admin)?;
+
+    let key = ApiKey {
+        key: req.key,
+        actions: req.actions,
+        scope: req.scope,
+        expires_at: req.expires_at,
+    };
+    state.auth.create_key(key.clone()).await?;
+    Ok(Json(key))
+}
+
+/// List all configured API keys
+///
+/// Requires a key scoped with `admin`.
+#[utoipa::path(
+    get,
+    path = "/keys",
+    tag = "Admin",
+    responses(
+        (status = 200, description = "Configured keys", body = Vec<ApiKey>),
+        (status = 403, description = "Caller is not scoped for 'admin'"),
+    )
+)]
+pub async fn list_keys(
+    State(state): State<crate::AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
+) -> Result<Json<Vec<ApiKey>>, AppError> {
+    permissions.require(Action::Admin)?;
+    Ok(Json(state.auth.list_keys()))
+}
+
+/// Revoke an API key
+///
+/// Requires a key scoped with `admin`.
+#[utoipa::path(
+    delete,
+    path = "/keys/{key}",
+    tag = "Admin",
+    params(
+        ("key" = String, Path, description = "The API key to revoke")
+    ),
+    responses(
+        (status = 200, description = "Key revoked"),
+        (status = 403, description = "Caller is not scoped for 'admin'"),
+        (status = 404, description = "Key not found"),
+    )
+)]
+pub async fn delete_key(
+    State(state): State<crate::AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
+    Path(key): Path<String>,
+) -> Result<StatusCode, AppError> {
+    permissions.require(Action::Admin)?;
+
+    if state.auth.delete_key(&key).await? {
+        Ok(StatusCode::OK)
+    } else {
+        Err(AppError::NotFound(format!("API key not found: {}", key)))
     }
 }
 
@@ -60,33 +389,133 @@ pub async fn auth_middleware(
 mod tests {
     use super::*;
 
+    fn admin_key(key: &str) -> ApiKey {
+        let mut actions = HashSet::new();
+        actions.insert(Action::Admin);
+        ApiKey {
+            key: key.to_string(),
+            actions,
+            scope: None,
+            expires_at: None,
+        }
+    }
+
     #[test]
-    fn test_auth_state_creation() {
-        let state = AuthState::new(Some("test-key".to_string()));
-        assert!(state.api_key.is_some());
-        assert_eq!(state.api_key.unwrap(), "test-key");
+    fn test_admin_key_allows_everything() {
+        let key = admin_key("admin-key");
+        assert!(key.allows(Action::Search));
+        assert!(key.allows(Action::Upsert));
+        assert!(key.allows(Action::Delete));
     }
 
     #[test]
-    fn test_auth_state_disabled() {
-        let state = AuthState::new(None);
-        assert!(state.api_key.is_none());
+    fn test_scoped_key_denies_other_actions() {
+        let mut actions = HashSet::new();
+        actions.insert(Action::Search);
+        let key = ApiKey {
+            key: "search-only".to_string(),
+            actions,
+            scope: None,
+            expires_at: None,
+        };
+        assert!(key.allows(Action::Search));
+        assert!(!key.allows(Action::Upsert));
     }
 
     #[test]
-    fn test_auth_state_is_enabled() {
-        let enabled = AuthState::new(Some("key".to_string()));
-        assert!(enabled.is_enabled());
+    fn test_expired_key_is_expired() {
+        let key = ApiKey {
+            key: "stale".to_string(),
+            actions: HashSet::new(),
+            scope: None,
+            expires_at: Some(0),
+        };
+        assert!(key.is_expired());
+    }
 
-        let disabled = AuthState::new(None);
-        assert!(!disabled.is_enabled());
+    #[test]
+    fn test_unexpired_key_is_not_expired() {
+        let key = ApiKey {
+            key: "fresh".to_string(),
+            actions: HashSet::new(),
+            scope: None,
+            expires_at: Some(chrono::Utc::now().timestamp() + 3600),
+        };
+        assert!(!key.is_expired());
     }
 
     #[test]
-    fn test_auth_state_clone() {
-        let state1 = AuthState::new(Some("key".to_string()));
-        let state2 = state1.clone();
-        assert_eq!(state1.api_key, state2.api_key);
+    fn test_permissions_require() {
+        let mut actions = HashSet::new();
+        actions.insert(Action::Upsert);
+        let permissions = ApiKeyPermissions {
+            actions,
+            scope: None,
+        };
+        assert!(permissions.require(Action::Upsert).is_ok());
+        assert!(permissions.require(Action::Delete).is_err());
+    }
+
+    #[test]
+    fn test_unscoped_permissions_allow_any_user_or_namespace() {
+        let permissions = ApiKeyPermissions {
+            actions: HashSet::new(),
+            scope: None,
+        };
+        assert!(permissions.check_user_scope(123).is_ok());
+        assert!(permissions.check_namespace_scope(Some("other")).is_ok());
+        assert!(permissions.check_namespace_scope(None).is_ok());
+    }
+
+    #[test]
+    fn test_user_scoped_permissions_reject_other_users() {
+        let permissions = ApiKeyPermissions {
+            actions: HashSet::new(),
+            scope: Some("user:123".to_string()),
+        };
+        assert!(permissions.check_user_scope(123).is_ok());
+        assert!(permissions.check_user_scope(456).is_err());
+    }
+
+    #[test]
+    fn test_namespace_scoped_permissions_reject_other_namespaces() {
+        let permissions = ApiKeyPermissions {
+            actions: HashSet::new(),
+            scope: Some("namespace:feedback".to_string()),
+        };
+        assert!(permissions.check_namespace_scope(Some("feedback")).is_ok());
+        assert!(permissions.check_namespace_scope(Some("other")).is_err());
+        assert!(permissions.check_namespace_scope(None).is_err());
     }
-}
 
+    #[tokio::test]
+    async fn test_load_bootstraps_from_service_api_key() {
+        let dir = std::env::temp_dir().join(format!("synaplan_keys_test_{}", uuid::Uuid::new_v4()));
+        let path = dir.join("keys.json");
+
+        let state = AuthState::load(&path, Some("bootstrap-key".to_string()));
+        assert!(state.is_enabled());
+
+        let keys = state.list_keys();
+        assert_eq!(keys.len(), 1);
+        assert!(keys[0].allows(Action::Admin));
+    }
+
+    #[tokio::test]
+    async fn test_create_and_persist_key_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("synaplan_keys_test_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("keys.json");
+
+        let state = AuthState::load(&path, None);
+        assert!(!state.is_enabled());
+
+        state.create_key(admin_key("new-admin")).await.unwrap();
+        assert!(state.is_enabled());
+
+        let reloaded = AuthState::load(&path, None);
+        assert!(reloaded.is_enabled());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,228 @@
+//! Content-defined chunking (FastCDC) for document ingestion.
+//!
+//! Splitting a document on fixed byte offsets means a single inserted
+//! character shifts every chunk boundary after it, turning a one-line edit
+//! into a full re-chunk (and re-embed) of the file. FastCDC instead cuts on
+//! a rolling content fingerprint, so most chunk boundaries on either side of
+//! an edit stay put and only the touched chunk(s) change. Callers derive the
+//! point ID straight from a chunk's [`Chunk::content_hash`] (see
+//! `QdrantService::upsert_document_deduped`), so re-uploading a lightly
+//! edited file only upserts the chunks that actually changed, and two files
+//! sharing a chunk verbatim store it once.
+//!
+//! Uses *normalized chunking*: below [`FastCdcConfig::avg_size`] a stricter
+//! mask (more required zero bits) makes a cut less likely, and above it a
+//! looser mask makes one more likely, biasing chunk sizes toward the
+//! average instead of letting them drift toward `min_size` or `max_size`.
+
+use sha2::{Digest, Sha256};
+
+/// 256-entry table of pseudo-random 64-bit "gear" values used to roll the
+/// content fingerprint forward one byte at a time. Generated deterministically
+/// (splitmix64 from a fixed seed) rather than at runtime, so the same bytes
+/// always chunk the same way across processes and restarts.
+const GEAR: [u64; 256] = generate_gear_table();
+
+const fn generate_gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut seed: u64 = 0x9E3779B97F4A7C15;
+    let mut i = 0;
+    while i < 256 {
+        seed = seed.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = seed;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^= z >> 31;
+        table[i] = z;
+        i += 1;
+    }
+    table
+}
+
+/// Size bounds for [`fastcdc_chunks`].
+#[derive(Debug, Clone, Copy)]
+pub struct FastCdcConfig {
+    pub min_size: usize,
+    pub avg_size: usize,
+    pub max_size: usize,
+}
+
+impl FastCdcConfig {
+    /// Bounds tuned for the chunk sizes this service embeds (a few KB of
+    /// text): 2 KiB minimum, 8 KiB average, 32 KiB maximum.
+    pub fn for_documents() -> Self {
+        Self {
+            min_size: 2 * 1024,
+            avg_size: 8 * 1024,
+            max_size: 32 * 1024,
+        }
+    }
+
+    fn masks(&self) -> (u64, u64) {
+        let bits = (self.avg_size.max(2) as f64).log2().round() as u32;
+        let mask_s = (1u64 << (bits + 1).min(63)) - 1;
+        let mask_l = (1u64 << bits.saturating_sub(1)) - 1;
+        (mask_s, mask_l)
+    }
+}
+
+/// One content-defined chunk: its byte range within the original buffer and
+/// a SHA-256 hash of its content, used to detect unchanged chunks across
+/// re-uploads and as the basis of a stable point ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    pub start: usize,
+    pub end: usize,
+    pub content_hash: String,
+}
+
+impl Chunk {
+    fn new(data: &[u8], start: usize, end: usize) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(&data[start..end]);
+        Self {
+            start,
+            end,
+            content_hash: format!("{:x}", hasher.finalize()),
+        }
+    }
+}
+
+/// Splits `data` into content-defined chunks using FastCDC with normalized
+/// chunking. Returns an empty `Vec` for empty input.
+pub fn fastcdc_chunks(data: &[u8], config: &FastCdcConfig) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut start = 0usize;
+    let (mask_s, mask_l) = config.masks();
+
+    while start < data.len() {
+        let end = next_cut(data, start, config, mask_s, mask_l);
+        chunks.push(Chunk::new(data, start, end));
+        start = end;
+    }
+
+    chunks
+}
+
+/// Finds the end offset of the chunk starting at `start`, per the
+/// normalized-chunking rule: below `avg_size` require `fp & mask_s == 0`,
+/// above it require `fp & mask_l == 0`, and always cut by `max_size`.
+fn next_cut(
+    data: &[u8],
+    start: usize,
+    config: &FastCdcConfig,
+    mask_s: u64,
+    mask_l: u64,
+) -> usize {
+    let max_end = (start + config.max_size).min(data.len());
+
+    // Too little data left for a real cut decision; take the rest.
+    if max_end - start <= config.min_size {
+        return max_end;
+    }
+
+    let avg_end = (start + config.avg_size).min(max_end);
+    let skip_to = (start + config.min_size).min(max_end);
+
+    let mut fp: u64 = 0;
+    let mut i = start;
+
+    // Feed the mandatory minimum-size prefix into the fingerprint without
+    // testing for a cut; a chunk shorter than `min_size` is never emitted.
+    while i < skip_to {
+        fp = (fp << 1).wrapping_add(GEAR[data[i] as usize]);
+        i += 1;
+    }
+
+    while i < max_end {
+        fp = (fp << 1).wrapping_add(GEAR[data[i] as usize]);
+        let mask = if i < avg_end { mask_s } else { mask_l };
+        i += 1;
+        if fp & mask == 0 {
+            return i;
+        }
+    }
+
+    max_end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiny_config() -> FastCdcConfig {
+        FastCdcConfig {
+            min_size: 16,
+            avg_size: 64,
+            max_size: 256,
+        }
+    }
+
+    #[test]
+    fn test_empty_input_yields_no_chunks() {
+        assert!(fastcdc_chunks(&[], &tiny_config()).is_empty());
+    }
+
+    #[test]
+    fn test_chunks_cover_the_whole_input_contiguously() {
+        let data: Vec<u8> = (0..2000u32).map(|i| (i % 251) as u8).collect();
+        let chunks = fastcdc_chunks(&data, &tiny_config());
+
+        assert!(!chunks.is_empty());
+        assert_eq!(chunks[0].start, 0);
+        assert_eq!(chunks.last().unwrap().end, data.len());
+        for pair in chunks.windows(2) {
+            assert_eq!(pair[0].end, pair[1].start);
+        }
+    }
+
+    #[test]
+    fn test_chunks_respect_min_and_max_size() {
+        let config = tiny_config();
+        let data: Vec<u8> = (0..5000u32).map(|i| (i * 7 % 256) as u8).collect();
+        let chunks = fastcdc_chunks(&data, &config);
+
+        for (idx, chunk) in chunks.iter().enumerate() {
+            let len = chunk.end - chunk.start;
+            assert!(len <= config.max_size);
+            // The final chunk may be shorter than min_size if that's all
+            // that's left of the input.
+            if idx + 1 != chunks.len() {
+                assert!(len >= config.min_size);
+            }
+        }
+    }
+
+    #[test]
+    fn test_inserting_bytes_only_changes_nearby_chunks() {
+        let config = tiny_config();
+        let original: Vec<u8> = (0..4000u32).map(|i| (i * 31 % 256) as u8).collect();
+        let mut edited = original.clone();
+        edited.splice(1000..1000, std::iter::repeat(0xAAu8).take(5));
+
+        let original_chunks = fastcdc_chunks(&original, &config);
+        let edited_chunks = fastcdc_chunks(&edited, &config);
+
+        let original_hashes: std::collections::HashSet<_> =
+            original_chunks.iter().map(|c| c.content_hash.clone()).collect();
+        let unchanged = edited_chunks
+            .iter()
+            .filter(|c| original_hashes.contains(&c.content_hash))
+            .count();
+
+        // Most chunks should be untouched by a small localized insert; only
+        // the chunk(s) around the edit should differ.
+        assert!(unchanged >= original_chunks.len().saturating_sub(3));
+    }
+
+    #[test]
+    fn test_identical_content_hashes_identically() {
+        let config = tiny_config();
+        let data: Vec<u8> = (0..3000u32).map(|i| (i * 13 % 256) as u8).collect();
+
+        let first = fastcdc_chunks(&data, &config);
+        let second = fastcdc_chunks(&data, &config);
+
+        assert_eq!(first, second);
+    }
+}
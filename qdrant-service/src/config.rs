@@ -8,10 +8,33 @@ pub struct Config {
     pub vector_dimension: u64,
     pub port: u16,
     pub service_api_key: Option<String>,
+    /// Path to the JSON file used to persist the API key store across restarts.
+    pub api_keys_path: String,
+    /// OTLP collector endpoint (e.g. `http://otel-collector:4317`). When unset,
+    /// tracing falls back to the `fmt` layer only.
+    pub otel_exporter_otlp_endpoint: Option<String>,
+    /// Service name attached to exported spans' resource attributes.
+    pub otel_service_name: String,
     pub tls_enabled: bool,
     pub tls_cert_path: Option<String>,
     pub tls_key_path: Option<String>,
+    /// PEM file of CA certificates trusted to sign client certificates. When
+    /// set, the TLS server requires and verifies a client certificate
+    /// (mutual TLS) in addition to any API-key auth.
+    pub tls_client_ca_path: Option<String>,
     pub discord_webhook_url: Option<String>,
+    /// Explicit override for `discord_webhook_url`'s payload format
+    /// ("discord", "slack", "telegram", "generic"). When unset, the format
+    /// is auto-detected from the URL's host.
+    pub webhook_provider: Option<String>,
+    /// Telegram Bot API token for the Telegram alert sink (from @BotFather).
+    pub telegram_bot_token: Option<String>,
+    /// Telegram chat/channel ID the bot should post alerts to.
+    pub telegram_chat_id: Option<String>,
+    /// Whether to run the background daily-stats reporting task.
+    pub enable_daily_stats: bool,
+    /// Interval, in hours, between daily-stats reports.
+    pub stats_interval_hours: u64,
     /// Embedding backend used by this service (e.g. "none", "onnxruntime", "candle", "ollama").
     /// This is exposed via /capabilities for downstream routing decisions.
     pub embedding_backend: String,
@@ -29,6 +52,67 @@ pub struct Config {
     pub embedding_tokenizer_path: Option<String>,
     /// Max token length for embeddings (keep small for memories; e.g. 256/512)
     pub embedding_max_length: u32,
+    /// Pooling strategy the native ONNX embedder applies to the model's
+    /// `last_hidden_state` output ("cls" or "mean"). "cls" takes the first
+    /// token's hidden state; "mean" averages every non-padding token's
+    /// hidden state, weighted by the attention mask.
+    pub embedding_onnx_pooling: String,
+    /// Input tensor name for token IDs in the ONNX graph.
+    pub embedding_onnx_input_ids_name: String,
+    /// Input tensor name for the attention mask in the ONNX graph.
+    pub embedding_onnx_attention_mask_name: String,
+    /// Input tensor name for token type/segment IDs, if the exported graph
+    /// requires one (some BERT-family exports do; XLM-R/BGE typically
+    /// don't). Left unset, that input is omitted entirely.
+    pub embedding_onnx_token_type_ids_name: Option<String>,
+    /// Index into the model's output list that holds `last_hidden_state`
+    /// (`[batch, seq_len, hidden]`). Some exports put pooled/sentence
+    /// embeddings first and put the per-token hidden states elsewhere.
+    pub embedding_onnx_output_index: usize,
+    /// Retries for transient Ollama embedding failures (HTTP 429/5xx)
+    /// before giving up with `AppError::EmbeddingRateLimited`/`EmbeddingBackendError`.
+    /// The "rest"/"openai" backend does not retry.
+    pub embedding_max_retries: u32,
+
+    /// Request URL for embedding backends "rest"/"openai" (e.g.
+    /// https://api.openai.com/v1/embeddings or a local TEI/vLLM endpoint).
+    pub embedding_rest_url: Option<String>,
+    /// Bearer token sent as `Authorization: Bearer <key>` to the REST/OpenAI
+    /// embedding endpoint, if it requires auth.
+    pub embedding_rest_api_key: Option<String>,
+    /// Dot-separated JSON path to the float array in the REST/OpenAI
+    /// response body, e.g. `data.0.embedding` for OpenAI's
+    /// `{"data": [{"embedding": [...]}]}` shape.
+    pub embedding_rest_response_path: String,
+    /// Upper bound on in-flight `embed` calls used by `Embedder::embed_batch`'s
+    /// bounded-concurrency fan-out (and Ollama's fallback when its native
+    /// batch endpoint is unavailable), so a bulk upsert can't overwhelm the
+    /// embedding backend.
+    pub embedding_concurrency: usize,
+
+    /// Which `MakeRequestId` generator to use when an incoming request has no
+    /// `X-Request-ID` header: "uuid" (default), "counter", or "nanoid".
+    pub request_id_strategy: String,
+    /// Whether to reuse a client-supplied `X-Request-ID` header: "use_incoming"
+    /// (default) or "ignore_incoming" to always generate our own.
+    pub request_id_trust: String,
+    /// When reusing an incoming `X-Request-ID`, whether an invalid value is
+    /// rejected with `400` (`true`) or silently replaced (`false`, default).
+    pub request_id_reject_invalid: bool,
+
+    /// Whether memory/document content is encrypted at rest before being
+    /// written to Qdrant. Opt-in: off by default so existing deployments
+    /// keep working unchanged.
+    pub memory_encryption_enabled: bool,
+    /// Base64-encoded 32-byte master key memory/document content is
+    /// encrypted under (a per-user key is derived from it via HKDF).
+    /// Required when `memory_encryption_enabled` is `true`.
+    pub memory_encryption_master_key: Option<String>,
+
+    /// Number of concurrent scroll partitions used by
+    /// [`crate::qdrant::QdrantService::get_document_stats_parallel`] to scan
+    /// large collections. `1` behaves like a single sequential scroll.
+    pub document_stats_shards: usize,
 }
 
 impl Config {
@@ -48,13 +132,30 @@ impl Config {
                 .parse()
                 .map_err(|e| anyhow::anyhow!("Invalid PORT: {}", e))?,
             service_api_key: env::var("SERVICE_API_KEY").ok(),
+            api_keys_path: env::var("API_KEYS_PATH")
+                .unwrap_or_else(|_| "api_keys.json".to_string()),
+            otel_exporter_otlp_endpoint: env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok(),
+            otel_service_name: env::var("OTEL_SERVICE_NAME")
+                .unwrap_or_else(|_| "synaplan-qdrant-service".to_string()),
             tls_enabled: env::var("TLS_ENABLED")
                 .unwrap_or_else(|_| "false".to_string())
                 .parse()
                 .unwrap_or(false),
             tls_cert_path: env::var("TLS_CERT_PATH").ok(),
             tls_key_path: env::var("TLS_KEY_PATH").ok(),
+            tls_client_ca_path: env::var("TLS_CLIENT_CA_PATH").ok(),
             discord_webhook_url: env::var("DISCORD_WEBHOOK_URL").ok(),
+            webhook_provider: env::var("WEBHOOK_PROVIDER").ok(),
+            telegram_bot_token: env::var("TELEGRAM_BOT_TOKEN").ok(),
+            telegram_chat_id: env::var("TELEGRAM_CHAT_ID").ok(),
+            enable_daily_stats: env::var("ENABLE_DAILY_STATS")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            stats_interval_hours: env::var("STATS_INTERVAL_HOURS")
+                .unwrap_or_else(|_| "24".to_string())
+                .parse()
+                .unwrap_or(24),
             embedding_backend: env::var("EMBEDDING_BACKEND").unwrap_or_else(|_| "none".to_string()),
             embedding_model: env::var("EMBEDDING_MODEL").ok(),
             embedding_device: env::var("EMBEDDING_DEVICE").unwrap_or_else(|_| "auto".to_string()),
@@ -65,6 +166,46 @@ impl Config {
                 .unwrap_or_else(|_| "512".to_string())
                 .parse()
                 .unwrap_or(512),
+            embedding_onnx_pooling: env::var("EMBEDDING_ONNX_POOLING")
+                .unwrap_or_else(|_| "cls".to_string()),
+            embedding_onnx_input_ids_name: env::var("EMBEDDING_ONNX_INPUT_IDS_NAME")
+                .unwrap_or_else(|_| "input_ids".to_string()),
+            embedding_onnx_attention_mask_name: env::var("EMBEDDING_ONNX_ATTENTION_MASK_NAME")
+                .unwrap_or_else(|_| "attention_mask".to_string()),
+            embedding_onnx_token_type_ids_name: env::var("EMBEDDING_ONNX_TOKEN_TYPE_IDS_NAME").ok(),
+            embedding_onnx_output_index: env::var("EMBEDDING_ONNX_OUTPUT_INDEX")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+            embedding_max_retries: env::var("EMBEDDING_MAX_RETRIES")
+                .unwrap_or_else(|_| crate::embedding::DEFAULT_EMBEDDING_MAX_RETRIES.to_string())
+                .parse()
+                .unwrap_or(crate::embedding::DEFAULT_EMBEDDING_MAX_RETRIES),
+            embedding_rest_url: env::var("EMBEDDING_REST_URL").ok(),
+            embedding_rest_api_key: env::var("EMBEDDING_REST_API_KEY").ok(),
+            embedding_rest_response_path: env::var("EMBEDDING_REST_RESPONSE_PATH")
+                .unwrap_or_else(|_| "data.0.embedding".to_string()),
+            embedding_concurrency: env::var("EMBEDDING_CONCURRENCY")
+                .unwrap_or_else(|_| crate::embedding::DEFAULT_EMBEDDING_CONCURRENCY.to_string())
+                .parse()
+                .unwrap_or(crate::embedding::DEFAULT_EMBEDDING_CONCURRENCY),
+            request_id_strategy: env::var("REQUEST_ID_STRATEGY")
+                .unwrap_or_else(|_| "uuid".to_string()),
+            request_id_trust: env::var("REQUEST_ID_TRUST")
+                .unwrap_or_else(|_| "use_incoming".to_string()),
+            request_id_reject_invalid: env::var("REQUEST_ID_REJECT_INVALID")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            memory_encryption_enabled: env::var("MEMORY_ENCRYPTION_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            memory_encryption_master_key: env::var("MEMORY_ENCRYPTION_MASTER_KEY").ok(),
+            document_stats_shards: env::var("DOCUMENT_STATS_SHARDS")
+                .unwrap_or_else(|_| "4".to_string())
+                .parse()
+                .unwrap_or(4),
         })
     }
 
@@ -77,10 +218,19 @@ impl Config {
             vector_dimension: 128,
             port: 8090,
             service_api_key: None,
+            api_keys_path: "api_keys.json".to_string(),
+            otel_exporter_otlp_endpoint: None,
+            otel_service_name: "synaplan-qdrant-service".to_string(),
             tls_enabled: false,
             tls_cert_path: None,
             tls_key_path: None,
+            tls_client_ca_path: None,
             discord_webhook_url: None,
+            webhook_provider: None,
+            telegram_bot_token: None,
+            telegram_chat_id: None,
+            enable_daily_stats: false,
+            stats_interval_hours: 24,
             embedding_backend: "none".to_string(),
             embedding_model: None,
             embedding_device: "auto".to_string(),
@@ -88,6 +238,22 @@ impl Config {
             embedding_onnx_model_path: None,
             embedding_tokenizer_path: None,
             embedding_max_length: 512,
+            embedding_onnx_pooling: "cls".to_string(),
+            embedding_onnx_input_ids_name: "input_ids".to_string(),
+            embedding_onnx_attention_mask_name: "attention_mask".to_string(),
+            embedding_onnx_token_type_ids_name: None,
+            embedding_onnx_output_index: 0,
+            embedding_max_retries: crate::embedding::DEFAULT_EMBEDDING_MAX_RETRIES,
+            embedding_rest_url: None,
+            embedding_rest_api_key: None,
+            embedding_rest_response_path: "data.0.embedding".to_string(),
+            embedding_concurrency: crate::embedding::DEFAULT_EMBEDDING_CONCURRENCY,
+            request_id_strategy: "uuid".to_string(),
+            request_id_trust: "use_incoming".to_string(),
+            request_id_reject_invalid: false,
+            memory_encryption_enabled: false,
+            memory_encryption_master_key: None,
+            document_stats_shards: 4,
         }
     }
 }
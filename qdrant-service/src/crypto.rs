@@ -0,0 +1,143 @@
+//! Optional at-rest encryption for memory/document content.
+//!
+//! When enabled (see [`Config::memory_encryption_enabled`](crate::config::Config)),
+//! [`PayloadCipher`] encrypts the human-readable fields of a payload
+//! (`MemoryPayload::key`/`value`, `DocumentPayload::text`) before they're
+//! written to Qdrant, while filterable fields (`user_id`, `active`,
+//! `category`, `group_key`, `file_id`, `_point_id`) stay in clear so Qdrant
+//! filtering keeps working. Each user gets their own key, derived via HKDF
+//! from a single master key, so compromising one user's key doesn't expose
+//! another's content.
+//!
+//! Encrypted fields are stored as the string `enc:v1:<base64(nonce || ciphertext)>`
+//! in the same `String` field the plaintext would have occupied, so the
+//! payload schema and every consumer of it are unaffected either way.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chacha20poly1305::aead::{Aead, AeadCore, OsRng};
+use chacha20poly1305::{KeyInit, XChaCha20Poly1305, XNonce};
+use hkdf::Hkdf;
+use sha2::Sha256;
+
+use crate::error::AppError;
+
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 24; // XChaCha20Poly1305 uses a 192-bit nonce.
+
+/// Encrypts/decrypts payload fields with a per-user key derived from a
+/// single master key via HKDF-SHA256.
+#[derive(Clone)]
+pub struct PayloadCipher {
+    master_key: [u8; 32],
+}
+
+impl PayloadCipher {
+    /// Builds a cipher from a base64-encoded 32-byte master key, as stored
+    /// in `Config::memory_encryption_master_key`.
+    pub fn from_master_key_base64(master_key_b64: &str) -> Result<Self, AppError> {
+        let bytes = BASE64
+            .decode(master_key_b64)
+            .map_err(|e| AppError::Internal(format!("Invalid encryption master key: {}", e)))?;
+        let master_key: [u8; 32] = bytes.try_into().map_err(|bytes: Vec<u8>| {
+            AppError::Internal(format!(
+                "Encryption master key must be 32 bytes, got {}",
+                bytes.len()
+            ))
+        })?;
+
+        Ok(Self { master_key })
+    }
+
+    /// Derives a per-user encryption key so one user's key leaking doesn't
+    /// expose another user's content.
+    fn derive_user_key(&self, user_id: i64) -> chacha20poly1305::Key {
+        let hk = Hkdf::<Sha256>::new(None, &self.master_key);
+        let mut okm = [0u8; 32];
+        hk.expand(&user_id.to_be_bytes(), &mut okm)
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        okm.into()
+    }
+
+    /// Encrypts `plaintext` for `user_id`, returning the `enc:v1:...` string
+    /// to store in place of the plaintext.
+    pub fn encrypt_field(&self, user_id: i64, plaintext: &str) -> Result<String, AppError> {
+        let cipher = XChaCha20Poly1305::new(&self.derive_user_key(user_id));
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|e| AppError::Internal(format!("Failed to encrypt payload field: {}", e)))?;
+
+        let mut combined = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        combined.extend_from_slice(&nonce);
+        combined.extend_from_slice(&ciphertext);
+
+        Ok(format!("{}{}", ENCRYPTED_PREFIX, BASE64.encode(combined)))
+    }
+
+    /// Decrypts a field previously produced by [`Self::encrypt_field`]. A
+    /// value without the `enc:v1:` prefix is assumed to be plaintext written
+    /// before encryption was enabled (or while it's disabled) and is passed
+    /// through unchanged, so enabling encryption never breaks old data.
+    pub fn decrypt_field(&self, user_id: i64, stored: &str) -> Result<String, AppError> {
+        let Some(encoded) = stored.strip_prefix(ENCRYPTED_PREFIX) else {
+            return Ok(stored.to_string());
+        };
+
+        let combined = BASE64
+            .decode(encoded)
+            .map_err(|e| AppError::Internal(format!("Invalid encrypted field: {}", e)))?;
+        if combined.len() < NONCE_LEN {
+            return Err(AppError::Internal("Encrypted field is truncated".to_string()));
+        }
+        let (nonce_bytes, ciphertext) = combined.split_at(NONCE_LEN);
+
+        let cipher = XChaCha20Poly1305::new(&self.derive_user_key(user_id));
+        let nonce = XNonce::from_slice(nonce_bytes);
+        let plaintext = cipher
+            .decrypt(nonce, ciphertext)
+            .map_err(|e| AppError::Internal(format!("Failed to decrypt payload field: {}", e)))?;
+
+        String::from_utf8(plaintext)
+            .map_err(|e| AppError::Internal(format!("Decrypted field is not valid UTF-8: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_cipher() -> PayloadCipher {
+        PayloadCipher {
+            master_key: [7u8; 32],
+        }
+    }
+
+    #[test]
+    fn test_encrypt_then_decrypt_round_trips() {
+        let cipher = test_cipher();
+        let encrypted = cipher.encrypt_field(42, "hello world").unwrap();
+        assert!(encrypted.starts_with(ENCRYPTED_PREFIX));
+        let decrypted = cipher.decrypt_field(42, &encrypted).unwrap();
+        assert_eq!(decrypted, "hello world");
+    }
+
+    #[test]
+    fn test_decrypt_passes_through_plaintext_unchanged() {
+        let cipher = test_cipher();
+        let decrypted = cipher.decrypt_field(42, "not encrypted").unwrap();
+        assert_eq!(decrypted, "not encrypted");
+    }
+
+    #[test]
+    fn test_decrypt_fails_for_wrong_user() {
+        let cipher = test_cipher();
+        let encrypted = cipher.encrypt_field(1, "secret").unwrap();
+        assert!(cipher.decrypt_field(2, &encrypted).is_err());
+    }
+
+    #[test]
+    fn test_from_master_key_base64_rejects_wrong_length() {
+        let short_key = BASE64.encode([1u8; 16]);
+        assert!(PayloadCipher::from_master_key_base64(&short_key).is_err());
+    }
+}
@@ -1,8 +1,20 @@
+use crate::config::Config;
 use crate::error::AppError;
+use crate::metrics::MetricsState;
 use async_trait::async_trait;
-use reqwest::Client;
+use futures::stream::{self, StreamExt};
+use reqwest::{Client, StatusCode};
 use serde::Deserialize;
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Sentinel text embedded solely to measure the output vector length.
+const DIMENSION_PROBE_TEXT: &str = "test";
+
+/// Default upper bound on in-flight `embed` calls for the bounded-concurrency
+/// `embed_batch` fan-out, used when a caller doesn't override it (see
+/// `EMBEDDING_CONCURRENCY`).
+pub const DEFAULT_EMBEDDING_CONCURRENCY: usize = 8;
 
 #[async_trait]
 pub trait Embedder: Send + Sync {
@@ -10,13 +22,278 @@ pub trait Embedder: Send + Sync {
     fn backend(&self) -> String;
     fn model(&self) -> Option<String>;
     fn device(&self) -> String;
+
+    /// Infers this embedder's output dimension by embedding a fixed sentinel
+    /// string, so callers can validate (or fill in) `vector_dimension`
+    /// without hand-tracking it per model. Backends with a cheaper way to
+    /// report this (e.g. reading it off a loaded model) can override it.
+    async fn probe_dimension(&self) -> Result<usize, AppError> {
+        let vector = self.embed(DIMENSION_PROBE_TEXT).await?;
+        Ok(vector.len())
+    }
+
+    /// Upper bound on in-flight `embed` calls the default `embed_batch` fans
+    /// out to. Concrete embedders override this with their configured
+    /// `EMBEDDING_CONCURRENCY`.
+    fn concurrency(&self) -> usize {
+        DEFAULT_EMBEDDING_CONCURRENCY
+    }
+
+    /// Embeds many texts at once. The default implementation fans out over
+    /// `self.embed`, bounded to `self.concurrency()` in flight at a time, so
+    /// a bulk upsert doesn't serialize N round trips or overwhelm the
+    /// backend. Results are returned in the same order as `texts`. Backends
+    /// with a native batch endpoint (e.g. Ollama's `/api/embed`) should
+    /// override this and fall back to [`embed_batch_concurrent`] when it's
+    /// unavailable.
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, AppError> {
+        embed_batch_concurrent(self, texts).await
+    }
+}
+
+/// Shared bounded-concurrency fan-out backing the default `Embedder::embed_batch`,
+/// also used by backends that fall back to it when their native batch
+/// endpoint is unavailable.
+async fn embed_batch_concurrent<E: Embedder + ?Sized>(
+    embedder: &E,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>, AppError> {
+    let concurrency = embedder.concurrency().max(1);
+
+    stream::iter(texts.iter().map(|text| embedder.embed(text)))
+        .buffered(concurrency)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect()
+}
+
+/// Lets a boxed `Arc<dyn Embedder>` satisfy `Embedder` itself (e.g. to wrap
+/// one in `InstrumentedEmbedder`), delegating every method straight through
+/// to the concrete backend behind the trait object.
+#[async_trait]
+impl Embedder for Arc<dyn Embedder> {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, AppError> {
+        self.as_ref().embed(text).await
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, AppError> {
+        self.as_ref().embed_batch(texts).await
+    }
+
+    async fn probe_dimension(&self) -> Result<usize, AppError> {
+        self.as_ref().probe_dimension().await
+    }
+
+    fn backend(&self) -> String {
+        self.as_ref().backend()
+    }
+
+    fn model(&self) -> Option<String> {
+        self.as_ref().model()
+    }
+
+    fn device(&self) -> String {
+        self.as_ref().device()
+    }
+
+    fn concurrency(&self) -> usize {
+        self.as_ref().concurrency()
+    }
+}
+
+/// Builds the `Embedder` configured by `config.embedding_backend`, validating
+/// each backend's required fields up front (e.g. Ollama needs a base URL and
+/// model) the way Meilisearch's `validate_embedding_settings` rejects
+/// incomplete configs, so a typo surfaces at startup instead of on the first
+/// write. Returns `Ok(None)` for `"none"` and for backends not wired through
+/// this generic factory yet (`"onnxruntime"`/`"candle"`, which go through
+/// [`crate::embedding_onnx`] behind the `native_onnx` feature instead).
+pub fn build_embedder(config: &Config) -> Result<Option<Arc<dyn Embedder>>, AppError> {
+    match config.embedding_backend.as_str() {
+        "ollama" => {
+            let base_url = config.ollama_base_url.clone().ok_or_else(|| {
+                AppError::InvalidRequest(
+                    "OLLAMA_BASE_URL is required when EMBEDDING_BACKEND=ollama".to_string(),
+                )
+            })?;
+            let model = config.embedding_model.clone().ok_or_else(|| {
+                AppError::InvalidRequest(
+                    "EMBEDDING_MODEL is required when EMBEDDING_BACKEND=ollama".to_string(),
+                )
+            })?;
+
+            Ok(Some(Arc::new(OllamaEmbedder::new(
+                base_url,
+                model,
+                config.embedding_max_retries,
+                config.embedding_concurrency,
+            )) as Arc<dyn Embedder>))
+        }
+        "rest" | "openai" => {
+            let url = config.embedding_rest_url.clone().ok_or_else(|| {
+                AppError::InvalidRequest(format!(
+                    "EMBEDDING_REST_URL is required when EMBEDDING_BACKEND={}",
+                    config.embedding_backend
+                ))
+            })?;
+
+            Ok(Some(Arc::new(RestEmbedder::new(
+                url,
+                config.embedding_rest_api_key.clone(),
+                config.embedding_model.clone(),
+                config.embedding_rest_response_path.clone(),
+                config.embedding_backend.clone(),
+                config.embedding_concurrency,
+            )) as Arc<dyn Embedder>))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Resolves a request's dense vector for the optional server-side embedding
+/// path: returns the client-supplied vector (validated against
+/// `vector_dimension`) if present, otherwise embeds `text` with `embedder`.
+/// Fails if neither a vector nor `text` was given, or if a vector would need
+/// to be derived but no embedder is configured (see `build_embedder`).
+pub async fn resolve_vector(
+    embedder: Option<&Arc<dyn Embedder>>,
+    vector: Option<Vec<f32>>,
+    text: Option<&str>,
+    vector_dimension: u64,
+) -> Result<Vec<f32>, AppError> {
+    if let Some(vector) = vector {
+        if vector.len() != vector_dimension as usize {
+            return Err(AppError::InvalidRequest(format!(
+                "Vector dimension mismatch: expected {}, got {}",
+                vector_dimension,
+                vector.len()
+            )));
+        }
+        return Ok(vector);
+    }
+
+    let text = text.ok_or_else(|| {
+        AppError::InvalidRequest("either vector or source text must be provided".to_string())
+    })?;
+
+    let embedder = embedder.ok_or_else(|| {
+        AppError::InvalidRequest(
+            "vector is required: no embedder is configured for server-side embedding".to_string(),
+        )
+    })?;
+
+    embedder.embed(text).await
+}
+
+/// Rough characters-per-token ratio for estimating `tokens_estimated_total`
+/// without pulling in a real tokenizer; good enough for capacity dashboards,
+/// not for billing.
+const CHARS_PER_TOKEN_ESTIMATE: usize = 4;
+
+fn estimate_tokens(text: &str) -> u64 {
+    ((text.chars().count() / CHARS_PER_TOKEN_ESTIMATE).max(1)) as u64
+}
+
+/// Wraps any `Embedder` to record `embedding_duration_seconds`,
+/// `embeddings_total`/`embeddings_failed`, and `tokens_estimated_total` in
+/// `MetricsState` around every `embed`/`embed_batch` call, so operators can
+/// tell whether the embedding backend (not Qdrant) is the bottleneck.
+/// Everything else delegates straight to the wrapped embedder.
+pub struct InstrumentedEmbedder<E> {
+    inner: E,
+    metrics: MetricsState,
+}
+
+impl<E: Embedder> InstrumentedEmbedder<E> {
+    pub fn new(inner: E, metrics: MetricsState) -> Self {
+        Self { inner, metrics }
+    }
+
+    fn model_label(&self) -> String {
+        self.inner.model().unwrap_or_else(|| "unknown".to_string())
+    }
+}
+
+#[async_trait]
+impl<E: Embedder> Embedder for InstrumentedEmbedder<E> {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, AppError> {
+        let backend = self.inner.backend();
+        let model = self.model_label();
+
+        let start = Instant::now();
+        let result = self.inner.embed(text).await;
+        self.metrics
+            .record_embedding_duration(&backend, &model, start.elapsed().as_secs_f64());
+
+        match &result {
+            Ok(_) => {
+                self.metrics.increment_embeddings(&backend, &model);
+                self.metrics
+                    .add_estimated_tokens(&backend, &model, estimate_tokens(text));
+            }
+            Err(_) => self.metrics.increment_embedding_failures(&backend, &model),
+        }
+
+        result
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, AppError> {
+        let backend = self.inner.backend();
+        let model = self.model_label();
+
+        let start = Instant::now();
+        let result = self.inner.embed_batch(texts).await;
+        self.metrics
+            .record_embedding_duration(&backend, &model, start.elapsed().as_secs_f64());
+
+        match &result {
+            Ok(_) => {
+                for _ in texts {
+                    self.metrics.increment_embeddings(&backend, &model);
+                }
+                let tokens: u64 = texts.iter().map(|t| estimate_tokens(t)).sum();
+                self.metrics.add_estimated_tokens(&backend, &model, tokens);
+            }
+            Err(_) => self.metrics.increment_embedding_failures(&backend, &model),
+        }
+
+        result
+    }
+
+    fn backend(&self) -> String {
+        self.inner.backend()
+    }
+
+    fn model(&self) -> Option<String> {
+        self.inner.model()
+    }
+
+    fn device(&self) -> String {
+        self.inner.device()
+    }
+
+    fn concurrency(&self) -> usize {
+        self.inner.concurrency()
+    }
 }
 
+/// Default number of retries for transient Ollama failures (429/5xx), used
+/// when a caller doesn't override it (see `EMBEDDING_MAX_RETRIES`).
+pub const DEFAULT_EMBEDDING_MAX_RETRIES: u32 = 3;
+
+/// Base delay for the exponential backoff between retries, doubled each
+/// attempt and capped at `RETRY_BACKOFF_MAX_MS`.
+const RETRY_BACKOFF_BASE_MS: u64 = 500;
+const RETRY_BACKOFF_MAX_MS: u64 = 8_000;
+
 #[derive(Clone)]
 pub struct OllamaEmbedder {
     client: Client,
     base_url: String,
     model: String,
+    max_retries: u32,
+    concurrency: usize,
 }
 
 #[derive(Debug, Deserialize)]
@@ -24,8 +301,15 @@ struct OllamaEmbeddingResponse {
     embedding: Vec<f32>,
 }
 
+/// Response shape for Ollama's batch `/api/embed` endpoint, which accepts an
+/// `input` array and returns one vector per input, in order.
+#[derive(Debug, Deserialize)]
+struct OllamaBatchEmbeddingResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
 impl OllamaEmbedder {
-    pub fn new(base_url: String, model: String) -> Self {
+    pub fn new(base_url: String, model: String, max_retries: u32, concurrency: usize) -> Self {
         let client = Client::builder()
             .connect_timeout(Duration::from_millis(800))
             .timeout(Duration::from_secs(10))
@@ -36,6 +320,8 @@ impl OllamaEmbedder {
             client,
             base_url: base_url.trim_end_matches('/').to_string(),
             model,
+            max_retries,
+            concurrency,
         }
     }
 
@@ -43,6 +329,84 @@ impl OllamaEmbedder {
         // Ollama embeddings endpoint (common): POST /api/embeddings
         format!("{}/api/embeddings", self.base_url)
     }
+
+    fn embed_batch_url(&self) -> String {
+        // Native batch embeddings endpoint, available on newer Ollama
+        // releases: POST /api/embed, accepts `input` as an array.
+        format!("{}/api/embed", self.base_url)
+    }
+
+    /// Tries Ollama's native batch endpoint. Returns `Ok(None)` when the
+    /// backend doesn't have it (404), so the caller can fall back to
+    /// per-item calls instead of treating an older Ollama as an error.
+    async fn embed_batch_native(
+        &self,
+        texts: &[String],
+    ) -> Result<Option<Vec<Vec<f32>>>, AppError> {
+        let request = self.client.post(self.embed_batch_url()).json(&serde_json::json!({
+            "model": self.model,
+            "input": texts,
+        }));
+
+        let resp = crate::request_id::propagate_header(request)
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("Batch embedding request failed: {}", e)))?;
+
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(AppError::EmbeddingBackendError(format!(
+                "HTTP {}: {}",
+                status, body
+            )));
+        }
+
+        let data: OllamaBatchEmbeddingResponse = resp.json().await.map_err(|e| {
+            AppError::Internal(format!("Failed to parse batch embedding response: {}", e))
+        })?;
+
+        Ok(Some(data.embeddings))
+    }
+}
+
+/// Ollama's message when the configured model hasn't been pulled, e.g.
+/// `"model 'bge-m3' not found, try pulling it first"`.
+fn is_model_missing(status: StatusCode, body: &str) -> bool {
+    let body = body.to_lowercase();
+    status == StatusCode::NOT_FOUND && body.contains("model") && body.contains("not found")
+}
+
+/// Parses a `Retry-After` header as whole seconds. Ollama doesn't send this
+/// today, but a proxy sitting in front of it might.
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let seconds: u64 = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.parse().ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+/// Exponential backoff (`base * 2^attempt`, capped) plus up to 50% jitter, so
+/// concurrent callers retrying the same overloaded backend don't all wake up
+/// in lockstep.
+fn backoff_delay(attempt: u32) -> Duration {
+    let exp = RETRY_BACKOFF_BASE_MS.saturating_mul(1u64 << attempt.min(10));
+    let capped = exp.min(RETRY_BACKOFF_MAX_MS);
+    let jitter = jitter_ms(capped / 2);
+    Duration::from_millis(capped + jitter)
+}
+
+fn jitter_ms(bound: u64) -> u64 {
+    if bound == 0 {
+        return 0;
+    }
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0) as u64;
+    nanos % bound
 }
 
 #[async_trait]
@@ -54,50 +418,404 @@ impl Embedder for OllamaEmbedder {
             ));
         }
 
-        let resp = self
-            .client
-            .post(self.embeddings_url())
-            .json(&serde_json::json!({
+        let mut attempt = 0u32;
+        loop {
+            let request = self.client.post(self.embeddings_url()).json(&serde_json::json!({
                 "model": self.model,
                 "prompt": text,
-            }))
+            }));
+
+            let resp = crate::request_id::propagate_header(request)
+                .send()
+                .await
+                .map_err(|e| AppError::Internal(format!("Embedding request failed: {}", e)))?;
+
+            if resp.status().is_success() {
+                let data: OllamaEmbeddingResponse = resp.json().await.map_err(|e| {
+                    AppError::Internal(format!("Failed to parse embedding response: {}", e))
+                })?;
+
+                if data.embedding.is_empty() {
+                    return Err(AppError::Internal("Empty embedding returned".to_string()));
+                }
+
+                return Ok(data.embedding);
+            }
+
+            let status = resp.status();
+            let wait = retry_after(resp.headers());
+            let body = resp.text().await.unwrap_or_default();
+
+            if is_model_missing(status, &body) {
+                return Err(AppError::EmbeddingModelNotFound(self.model.clone()));
+            }
+
+            let retryable = status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+            if !retryable || attempt >= self.max_retries {
+                return Err(if status == StatusCode::TOO_MANY_REQUESTS {
+                    AppError::EmbeddingRateLimited(format!(
+                        "rate limited after {} attempt(s): {}",
+                        attempt + 1,
+                        body
+                    ))
+                } else if status.is_server_error() {
+                    AppError::EmbeddingBackendError(format!("HTTP {}: {}", status, body))
+                } else {
+                    AppError::Internal(format!(
+                        "Embedding request failed (HTTP {}): {}",
+                        status, body
+                    ))
+                });
+            }
+
+            tokio::time::sleep(wait.unwrap_or_else(|| backoff_delay(attempt))).await;
+            attempt += 1;
+        }
+    }
+
+    fn backend(&self) -> String {
+        "ollama".to_string()
+    }
+
+    fn model(&self) -> Option<String> {
+        Some(self.model.clone())
+    }
+
+    fn device(&self) -> String {
+        // Ollama decides device internally; we expose it as "external".
+        "external".to_string()
+    }
+
+    fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, AppError> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        if texts.iter().any(|t| t.trim().is_empty()) {
+            return Err(AppError::InvalidRequest(
+                "Text must not be empty".to_string(),
+            ));
+        }
+
+        match self.embed_batch_native(texts).await? {
+            Some(vectors) => Ok(vectors),
+            None => embed_batch_concurrent(self, texts).await,
+        }
+    }
+}
+
+/// Generic embedder for OpenAI-compatible REST APIs (OpenAI itself, LocalAI,
+/// text-embeddings-inference, vLLM, ...), driven entirely by `Config` so new
+/// backends don't need new code. Selected when `EMBEDDING_BACKEND` is `rest`
+/// or `openai`; `backend_name` just records which so `/capabilities` reports
+/// it accurately.
+///
+/// Sends `{"model": <model>, "input": <text>}` (omitting `model` when unset)
+/// and reads the embedding back out of the response at
+/// `response_path`, a dot-separated JSON path such as `data.0.embedding`
+/// (OpenAI's `{"data": [{"embedding": [...]}]}` shape).
+#[derive(Clone)]
+pub struct RestEmbedder {
+    client: Client,
+    url: String,
+    api_key: Option<String>,
+    model: Option<String>,
+    response_path: String,
+    backend_name: String,
+    concurrency: usize,
+}
+
+impl RestEmbedder {
+    pub fn new(
+        url: String,
+        api_key: Option<String>,
+        model: Option<String>,
+        response_path: String,
+        backend_name: String,
+        concurrency: usize,
+    ) -> Self {
+        let client = Client::builder()
+            .connect_timeout(Duration::from_millis(800))
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to build reqwest client");
+
+        Self {
+            client,
+            url,
+            api_key,
+            model,
+            response_path,
+            backend_name,
+            concurrency,
+        }
+    }
+
+    /// Looks up `self.response_path` in a decoded JSON response, e.g.
+    /// `data.0.embedding` against `{"data": [{"embedding": [0.1, 0.2]}]}`.
+    fn extract_embedding(&self, body: &serde_json::Value) -> Result<Vec<f32>, AppError> {
+        let pointer = format!("/{}", self.response_path.replace('.', "/"));
+        let value = body.pointer(&pointer).ok_or_else(|| {
+            AppError::Internal(format!(
+                "Embedding response missing path '{}'",
+                self.response_path
+            ))
+        })?;
+
+        serde_json::from_value(value.clone()).map_err(|e| {
+            AppError::Internal(format!(
+                "Embedding response path '{}' is not a float array: {}",
+                self.response_path, e
+            ))
+        })
+    }
+}
+
+#[async_trait]
+impl Embedder for RestEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, AppError> {
+        if text.trim().is_empty() {
+            return Err(AppError::InvalidRequest(
+                "Text must not be empty".to_string(),
+            ));
+        }
+
+        let mut body = serde_json::json!({ "input": text });
+        if let Some(model) = &self.model {
+            body["model"] = serde_json::json!(model);
+        }
+
+        let mut request = self.client.post(&self.url).json(&body);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let resp = crate::request_id::propagate_header(request)
             .send()
             .await
             .map_err(|e| AppError::Internal(format!("Embedding request failed: {}", e)))?;
 
-        if !resp.status().is_success() {
-            let status = resp.status();
+        let status = resp.status();
+        if !status.is_success() {
             let body = resp.text().await.unwrap_or_default();
-            return Err(AppError::Internal(format!(
-                "Embedding request failed (HTTP {}): {}",
-                status, body
-            )));
+            return Err(if status == StatusCode::TOO_MANY_REQUESTS {
+                AppError::EmbeddingRateLimited(format!("HTTP {}: {}", status, body))
+            } else {
+                AppError::EmbeddingBackendError(format!("HTTP {}: {}", status, body))
+            });
         }
 
-        let data: OllamaEmbeddingResponse = resp
+        let data: serde_json::Value = resp
             .json()
             .await
             .map_err(|e| AppError::Internal(format!("Failed to parse embedding response: {}", e)))?;
 
-        if data.embedding.is_empty() {
+        let vector = self.extract_embedding(&data)?;
+        if vector.is_empty() {
             return Err(AppError::Internal("Empty embedding returned".to_string()));
         }
 
-        Ok(data.embedding)
+        Ok(vector)
     }
 
     fn backend(&self) -> String {
-        "ollama".to_string()
+        self.backend_name.clone()
     }
 
     fn model(&self) -> Option<String> {
-        Some(self.model.clone())
+        self.model.clone()
     }
 
     fn device(&self) -> String {
-        // Ollama decides device internally; we expose it as "external".
         "external".to_string()
     }
+
+    fn concurrency(&self) -> usize {
+        self.concurrency
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_embedder_none_by_default() {
+        let config = Config::test_config();
+        assert!(build_embedder(&config).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_build_embedder_ollama_requires_base_url_and_model() {
+        let mut config = Config::test_config();
+        config.embedding_backend = "ollama".to_string();
+        assert!(build_embedder(&config).is_err());
+
+        config.ollama_base_url = Some("http://ollama:11434".to_string());
+        assert!(build_embedder(&config).is_err());
+
+        config.embedding_model = Some("bge-m3".to_string());
+        let embedder = build_embedder(&config).unwrap().unwrap();
+        assert_eq!(embedder.backend(), "ollama");
+        assert_eq!(embedder.model(), Some("bge-m3".to_string()));
+    }
+
+    #[test]
+    fn test_build_embedder_rest_requires_url() {
+        let mut config = Config::test_config();
+        config.embedding_backend = "openai".to_string();
+        assert!(build_embedder(&config).is_err());
+
+        config.embedding_rest_url = Some("https://api.openai.com/v1/embeddings".to_string());
+        let embedder = build_embedder(&config).unwrap().unwrap();
+        assert_eq!(embedder.backend(), "openai");
+    }
+
+    #[test]
+    fn test_model_missing_detection() {
+        assert!(is_model_missing(
+            StatusCode::NOT_FOUND,
+            "model 'bge-m3' not found, try pulling it first"
+        ));
+        assert!(!is_model_missing(StatusCode::NOT_FOUND, "route not found"));
+        assert!(!is_model_missing(StatusCode::INTERNAL_SERVER_ERROR, "not found"));
+    }
+
+    #[test]
+    fn test_backoff_delay_doubles_and_caps() {
+        let d0 = backoff_delay(0).as_millis() as u64;
+        let d5 = backoff_delay(5).as_millis() as u64;
+        let d_large = backoff_delay(20).as_millis() as u64;
+
+        assert!(d0 >= RETRY_BACKOFF_BASE_MS);
+        assert!(d0 <= RETRY_BACKOFF_BASE_MS + RETRY_BACKOFF_BASE_MS / 2);
+        assert!(d5 > d0);
+        assert!(d_large <= RETRY_BACKOFF_MAX_MS + RETRY_BACKOFF_MAX_MS / 2);
+    }
+
+    fn rest_embedder(response_path: &str) -> RestEmbedder {
+        RestEmbedder::new(
+            "http://localhost/embed".to_string(),
+            None,
+            None,
+            response_path.to_string(),
+            "rest".to_string(),
+            DEFAULT_EMBEDDING_CONCURRENCY,
+        )
+    }
+
+    #[test]
+    fn test_extract_embedding_openai_shape() {
+        let embedder = rest_embedder("data.0.embedding");
+        let body = serde_json::json!({ "data": [{ "embedding": [0.1, 0.2, 0.3] }] });
+
+        assert_eq!(embedder.extract_embedding(&body).unwrap(), vec![0.1, 0.2, 0.3]);
+    }
+
+    #[test]
+    fn test_extract_embedding_missing_path() {
+        let embedder = rest_embedder("data.0.embedding");
+        let body = serde_json::json!({ "data": [] });
+
+        assert!(embedder.extract_embedding(&body).is_err());
+    }
+
+    struct EchoLengthEmbedder {
+        concurrency: usize,
+    }
+
+    #[async_trait]
+    impl Embedder for EchoLengthEmbedder {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>, AppError> {
+            Ok(vec![text.len() as f32])
+        }
+
+        fn backend(&self) -> String {
+            "echo".to_string()
+        }
+
+        fn model(&self) -> Option<String> {
+            None
+        }
+
+        fn device(&self) -> String {
+            "test".to_string()
+        }
+
+        fn concurrency(&self) -> usize {
+            self.concurrency
+        }
+    }
+
+    #[tokio::test]
+    async fn test_embed_batch_default_preserves_order() {
+        let embedder = EchoLengthEmbedder { concurrency: 2 };
+        let texts: Vec<String> = vec!["a".into(), "bb".into(), "ccc".into(), "dddd".into()];
+
+        let vectors = embedder.embed_batch(&texts).await.unwrap();
+
+        assert_eq!(
+            vectors,
+            vec![vec![1.0], vec![2.0], vec![3.0], vec![4.0]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_vector_uses_client_vector_when_present() {
+        let vector = resolve_vector(None, Some(vec![1.0, 2.0]), Some("text"), 2)
+            .await
+            .unwrap();
+        assert_eq!(vector, vec![1.0, 2.0]);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_vector_rejects_dimension_mismatch() {
+        let err = resolve_vector(None, Some(vec![1.0, 2.0]), None, 3)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::InvalidRequest(_)));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_vector_rejects_missing_vector_and_text() {
+        let err = resolve_vector(None, None, None, 2).await.unwrap_err();
+        assert!(matches!(err, AppError::InvalidRequest(_)));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_vector_rejects_missing_embedder() {
+        let err = resolve_vector(None, None, Some("text"), 2).await.unwrap_err();
+        assert!(matches!(err, AppError::InvalidRequest(_)));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_vector_embeds_text_when_vector_absent() {
+        let embedder: Arc<dyn Embedder> = Arc::new(EchoLengthEmbedder { concurrency: 2 });
+        let vector = resolve_vector(Some(&embedder), None, Some("abcd"), 1)
+            .await
+            .unwrap();
+        assert_eq!(vector, vec![4.0]);
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 1);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcdefgh"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_instrumented_embedder_delegates_to_inner() {
+        let metrics = MetricsState::new();
+        let embedder =
+            InstrumentedEmbedder::new(EchoLengthEmbedder { concurrency: 2 }, metrics.clone());
+
+        assert_eq!(embedder.embed("hello").await.unwrap(), vec![5.0]);
+        assert_eq!(embedder.backend(), "echo");
+        assert_eq!(embedder.concurrency(), 2);
+    }
+}
 
@@ -6,18 +6,106 @@ use crate::error::AppError;
 use async_trait::async_trait;
 use std::sync::Arc;
 use tokenizers::Tokenizer;
+use tracing::info;
+
+/// Pooling strategy applied to a model's `last_hidden_state` output to
+/// collapse `[seq_len, hidden]` into a single `[hidden]` embedding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pooling {
+    /// First token's hidden state (works for BERT-family models whose
+    /// leading token is `[CLS]`).
+    Cls,
+    /// Mean of every non-padding token's hidden state, weighted by the
+    /// attention mask.
+    Mean,
+}
+
+impl Pooling {
+    fn parse(s: &str) -> Self {
+        match s.to_ascii_lowercase().as_str() {
+            "mean" => Pooling::Mean,
+            _ => Pooling::Cls,
+        }
+    }
+}
+
+/// An `ort` execution provider that can be registered on a `SessionBuilder`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExecutionProvider {
+    Cuda,
+    TensorRt,
+    CoreMl,
+    Cpu,
+}
+
+impl ExecutionProvider {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "cuda" => Some(Self::Cuda),
+            "tensorrt" => Some(Self::TensorRt),
+            "coreml" => Some(Self::CoreMl),
+            "cpu" => Some(Self::Cpu),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Cuda => "cuda",
+            Self::TensorRt => "tensorrt",
+            Self::CoreMl => "coreml",
+            Self::Cpu => "cpu",
+        }
+    }
+}
+
+/// Parses an `EMBEDDING_DEVICE` string ("cuda", "cuda:0", "cuda,cpu",
+/// "coreml,cpu", "auto", "cpu") into an ordered execution-provider fallback
+/// chain plus an optional device id for the first GPU provider in it.
+/// "auto" tries CUDA, then CoreML, then CPU (TensorRT only when named
+/// explicitly); explicit chains are tried left to right.
+fn parse_device_chain(device: &str) -> anyhow::Result<(Vec<ExecutionProvider>, Option<i32>)> {
+    let device = device.trim();
+    if device.is_empty() || device.eq_ignore_ascii_case("auto") {
+        return Ok((
+            vec![ExecutionProvider::Cuda, ExecutionProvider::CoreMl, ExecutionProvider::Cpu],
+            None,
+        ));
+    }
+
+    let mut device_id = None;
+    let mut chain = Vec::new();
+    for part in device.split(',') {
+        let mut pieces = part.trim().splitn(2, ':');
+        let name = pieces.next().unwrap_or("").to_ascii_lowercase();
+        let provider = ExecutionProvider::parse(&name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown execution provider '{name}' in EMBEDDING_DEVICE"))?;
+        if let Some(id) = pieces.next() {
+            device_id = id.parse().ok();
+        }
+        chain.push(provider);
+    }
+    Ok((chain, device_id))
+}
 
 /// Native ONNX Runtime embedder.
 ///
 /// Notes:
 /// - Uses tokenizer.json for XLM-R based tokenizer.
 /// - Runs ONNX model and returns dense embedding vector (1024 floats for bge-m3).
-/// - Uses CLS pooling (first token) + L2 normalization.
+/// - Pooling, input tensor names, and the output index holding
+///   `last_hidden_state` are configurable (see `EMBEDDING_ONNX_*` env vars)
+///   since exported graphs vary across models/toolchains.
 pub struct OnnxRuntimeEmbedder {
     tokenizer: Tokenizer,
     session: Arc<ort::Session>,
     model: String,
     device: String,
+    pooling: Pooling,
+    input_ids_name: String,
+    attention_mask_name: String,
+    token_type_ids_name: Option<String>,
+    output_index: usize,
 }
 
 impl OnnxRuntimeEmbedder {
@@ -45,8 +133,59 @@ impl OnnxRuntimeEmbedder {
         let mut builder = ort::Session::builder()
             .map_err(|e| anyhow::anyhow!("ORT session builder failed: {e}"))?;
 
-        // If the runtime has CUDA EP, it will be used automatically when the CUDA-enabled ORT lib is present.
-        // We keep device selection in capabilities only for now.
+        let (provider_chain, device_id) = parse_device_chain(&device)
+            .map_err(|e| anyhow::anyhow!("Invalid EMBEDDING_DEVICE '{device}': {e}"))?;
+        let allow_cpu_fallback = provider_chain.contains(&ExecutionProvider::Cpu);
+
+        let mut dispatch = Vec::new();
+        let mut resolved: Option<&'static str> = None;
+        for provider in provider_chain.iter().filter(|p| **p != ExecutionProvider::Cpu) {
+            let (available, mut provider_dispatch) = match provider {
+                ExecutionProvider::Cuda => {
+                    let mut cuda = ort::execution_providers::CUDAExecutionProvider::default();
+                    if let Some(id) = device_id {
+                        cuda = cuda.with_device_id(id);
+                    }
+                    (cuda.is_available().unwrap_or(false), cuda.build())
+                }
+                ExecutionProvider::TensorRt => {
+                    let trt = ort::execution_providers::TensorRTExecutionProvider::default();
+                    (trt.is_available().unwrap_or(false), trt.build())
+                }
+                ExecutionProvider::CoreMl => {
+                    let coreml = ort::execution_providers::CoreMLExecutionProvider::default();
+                    (coreml.is_available().unwrap_or(false), coreml.build())
+                }
+                ExecutionProvider::Cpu => unreachable!("filtered out above"),
+            };
+
+            if available && resolved.is_none() {
+                resolved = Some(provider.label());
+            }
+
+            // Without a CPU fallback in the chain, a GPU provider that
+            // fails to initialize should fail session creation loudly
+            // instead of ORT silently dropping back to CPU.
+            if !allow_cpu_fallback {
+                provider_dispatch = provider_dispatch.error_on_failure();
+            }
+
+            dispatch.push(provider_dispatch);
+        }
+
+        if resolved.is_none() && !allow_cpu_fallback {
+            return Err(anyhow::anyhow!(
+                "Requested execution provider(s) {:?} are unavailable and EMBEDDING_DEVICE did not include a 'cpu' fallback",
+                provider_chain
+            ));
+        }
+
+        let resolved_device = resolved.unwrap_or("cpu").to_string();
+        info!("ONNX embedder selected execution provider: {}", resolved_device);
+
+        builder = builder
+            .with_execution_providers(dispatch)
+            .map_err(|e| anyhow::anyhow!("Failed to register execution providers: {e}"))?;
         builder = builder
             .with_optimization_level(ort::GraphOptimizationLevel::Level3)
             .map_err(|e| anyhow::anyhow!("ORT optimization level failed: {e}"))?;
@@ -59,95 +198,163 @@ impl OnnxRuntimeEmbedder {
             tokenizer,
             session: Arc::new(session),
             model: model_name,
-            device,
+            device: resolved_device,
+            pooling: Pooling::parse(&config.embedding_onnx_pooling),
+            input_ids_name: config.embedding_onnx_input_ids_name.clone(),
+            attention_mask_name: config.embedding_onnx_attention_mask_name.clone(),
+            token_type_ids_name: config.embedding_onnx_token_type_ids_name.clone(),
+            output_index: config.embedding_onnx_output_index,
         })
     }
-}
 
-#[async_trait]
-impl Embedder for OnnxRuntimeEmbedder {
-    async fn embed(&self, text: &str) -> Result<Vec<f32>, AppError> {
-        if text.trim().is_empty() {
-            return Err(AppError::InvalidRequest("Text must not be empty".to_string()));
-        }
-
-        // Tokenize
-        let encoding = self
-            .tokenizer
-            .encode(text, true)
-            .map_err(|e| AppError::Internal(format!("Tokenization failed: {e}")))?;
-
-        let ids: Vec<i64> = encoding.get_ids().iter().map(|&x| x as i64).collect();
-        let mask: Vec<i64> = encoding
-            .get_attention_mask()
+    /// Tokenizes every text, pads each encoding to the batch's longest
+    /// sequence (right-padding with id/mask 0, the standard BERT-family
+    /// convention), and runs them through the model as a single
+    /// `[batch, seq_len]` forward pass instead of one `run()` per text.
+    fn run_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, AppError> {
+        let encodings: Vec<_> = texts
             .iter()
-            .map(|&x| x as i64)
-            .collect();
+            .map(|text| {
+                self.tokenizer
+                    .encode(text.as_str(), true)
+                    .map_err(|e| AppError::Internal(format!("Tokenization failed: {e}")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
-        if ids.is_empty() {
+        let seq_len = encodings.iter().map(|e| e.get_ids().len()).max().unwrap_or(0);
+        if seq_len == 0 {
             return Err(AppError::Internal("Tokenization returned empty ids".to_string()));
         }
 
-        // Create inputs: [1, seq_len]
-        let seq_len = ids.len();
-        let input_ids =
-            ort::value::Value::from_array((vec![1_i64, seq_len as i64], ids)).map_err(|e| {
-                AppError::Internal(format!("Failed to build input_ids tensor: {e}"))
-            })?;
-        let attention_mask =
-            ort::value::Value::from_array((vec![1_i64, seq_len as i64], mask)).map_err(|e| {
-                AppError::Internal(format!("Failed to build attention_mask tensor: {e}"))
-            })?;
+        let batch = encodings.len();
+        let mut ids = Vec::with_capacity(batch * seq_len);
+        let mut mask = Vec::with_capacity(batch * seq_len);
+        let mut token_type_ids = Vec::with_capacity(batch * seq_len);
+        for encoding in &encodings {
+            let row_ids = encoding.get_ids();
+            let row_mask = encoding.get_attention_mask();
+            for i in 0..seq_len {
+                ids.push(*row_ids.get(i).unwrap_or(&0) as i64);
+                mask.push(*row_mask.get(i).unwrap_or(&0) as i64);
+                token_type_ids.push(0_i64);
+            }
+        }
 
-        // Run model. We assume standard transformer input names.
-        // If your exported ONNX uses different names, we should add config options.
-        let outputs = self
-            .session
-            .run(ort::inputs! {
-                "input_ids" => input_ids,
-                "attention_mask" => attention_mask,
-            })
-            .map_err(|e| AppError::Internal(format!("ORT inference failed: {e}")))?;
+        let shape = vec![batch as i64, seq_len as i64];
+        let input_ids = ort::value::Value::from_array((shape.clone(), ids))
+            .map_err(|e| AppError::Internal(format!("Failed to build input_ids tensor: {e}")))?;
+        let attention_mask = ort::value::Value::from_array((shape.clone(), mask))
+            .map_err(|e| AppError::Internal(format!("Failed to build attention_mask tensor: {e}")))?;
 
-        // Take first output as last_hidden_state: [1, seq_len, hidden]
-        let output0 = outputs
-            .get(0)
-            .ok_or_else(|| AppError::Internal("No outputs returned from model".to_string()))?;
+        // Some exported graphs (notably several BERT-family ones) require a
+        // third `token_type_ids` input; XLM-R/BGE-style graphs typically
+        // don't, so it's only built and passed when configured.
+        let outputs = if let Some(name) = &self.token_type_ids_name {
+            let token_type_ids = ort::value::Value::from_array((shape, token_type_ids)).map_err(|e| {
+                AppError::Internal(format!("Failed to build token_type_ids tensor: {e}"))
+            })?;
+            self.session
+                .run(ort::inputs! {
+                    self.input_ids_name.as_str() => input_ids,
+                    self.attention_mask_name.as_str() => attention_mask,
+                    name.as_str() => token_type_ids,
+                })
+                .map_err(|e| AppError::Internal(format!("ORT inference failed: {e}")))?
+        } else {
+            self.session
+                .run(ort::inputs! {
+                    self.input_ids_name.as_str() => input_ids,
+                    self.attention_mask_name.as_str() => attention_mask,
+                })
+                .map_err(|e| AppError::Internal(format!("ORT inference failed: {e}")))?
+        };
+
+        let output = outputs.get(self.output_index).ok_or_else(|| {
+            AppError::Internal(format!(
+                "Model returned no output at index {} (last_hidden_state)",
+                self.output_index
+            ))
+        })?;
 
-        let tensor = output0
+        let tensor = output
             .try_extract_tensor::<f32>()
             .map_err(|e| AppError::Internal(format!("Failed to extract output tensor: {e}")))?;
 
-        let shape = tensor.shape();
-        if shape.len() != 3 || shape[0] != 1 || shape[1] != seq_len {
+        let out_shape = tensor.shape();
+        if out_shape.len() != 3 || out_shape[0] != batch || out_shape[1] != seq_len {
             return Err(AppError::Internal(format!(
-                "Unexpected output shape: {:?} (expected [1, seq_len, hidden])",
-                shape
+                "Unexpected output shape: {:?} (expected [{}, {}, hidden])",
+                out_shape, batch, seq_len
             )));
         }
 
-        let hidden = shape[2] as usize;
-        let data = tensor.as_slice().ok_or_else(|| {
-            AppError::Internal("Output tensor is not contiguous".to_string())
-        })?;
+        let hidden = out_shape[2] as usize;
+        let data = tensor
+            .as_slice()
+            .ok_or_else(|| AppError::Internal("Output tensor is not contiguous".to_string()))?;
 
-        // CLS pooling (first token at position 0)
-        let mut emb = vec![0f32; hidden];
-        let base = 0usize; // token 0
-        for i in 0..hidden {
-            emb[i] = data[base * hidden + i];
-        }
+        let mut embeddings = Vec::with_capacity(batch);
+        for (row, encoding) in encodings.iter().enumerate() {
+            let row_mask = encoding.get_attention_mask();
+            let row_offset = row * seq_len * hidden;
 
-        // L2 normalize
-        let norm = emb.iter().map(|v| (*v as f64) * (*v as f64)).sum::<f64>().sqrt();
-        if norm > 0.0 {
-            let inv = (1.0 / norm) as f32;
-            for v in emb.iter_mut() {
-                *v *= inv;
+            let mut emb = match self.pooling {
+                Pooling::Cls => data[row_offset..row_offset + hidden].to_vec(),
+                Pooling::Mean => {
+                    let mut sum = vec![0f64; hidden];
+                    let mut mask_total = 0f64;
+                    for t in 0..seq_len {
+                        let m = *row_mask.get(t).unwrap_or(&0) as f64;
+                        if m == 0.0 {
+                            continue;
+                        }
+                        let tok_offset = row_offset + t * hidden;
+                        for h in 0..hidden {
+                            sum[h] += data[tok_offset + h] as f64 * m;
+                        }
+                        mask_total += m;
+                    }
+                    let denom = mask_total.max(1.0);
+                    sum.iter().map(|v| (*v / denom) as f32).collect()
+                }
+            };
+
+            let norm = emb.iter().map(|v| (*v as f64) * (*v as f64)).sum::<f64>().sqrt();
+            if norm > 0.0 {
+                let inv = (1.0 / norm) as f32;
+                for v in emb.iter_mut() {
+                    *v *= inv;
+                }
             }
+
+            embeddings.push(emb);
         }
 
-        Ok(emb)
+        Ok(embeddings)
+    }
+}
+
+#[async_trait]
+impl Embedder for OnnxRuntimeEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, AppError> {
+        if text.trim().is_empty() {
+            return Err(AppError::InvalidRequest("Text must not be empty".to_string()));
+        }
+
+        let text = text.to_string();
+        let mut embeddings = self.run_batch(std::slice::from_ref(&text))?;
+        Ok(embeddings.remove(0))
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, AppError> {
+        if texts.iter().any(|t| t.trim().is_empty()) {
+            return Err(AppError::InvalidRequest("Text must not be empty".to_string()));
+        }
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.run_batch(texts)
     }
 
     fn backend(&self) -> String {
@@ -162,5 +369,3 @@ impl Embedder for OnnxRuntimeEmbedder {
         self.device.clone()
     }
 }
-
-
@@ -14,17 +14,118 @@ pub enum AppError {
     #[error("Not found: {0}")]
     NotFound(String),
 
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
     #[error("Invalid request: {0}")]
     InvalidRequest(String),
 
     #[error("Internal error: {0}")]
     Internal(String),
+
+    #[error("Service unavailable: {0}")]
+    Unavailable(String),
+
+    /// A collection's recorded `_schema` marker (vector dimension/distance)
+    /// doesn't match the running config, e.g. after the embedding model (and
+    /// thus dimension) was changed without migrating existing collections.
+    #[error("Collection '{collection}' schema mismatch: expected dimension {expected_dimension}, found {found_dimension}")]
+    SchemaMismatch {
+        collection: String,
+        expected_dimension: u64,
+        found_dimension: u64,
+    },
+
+    /// The embedding backend doesn't have the configured model available
+    /// (e.g. Ollama reporting it hasn't been pulled). Not retried, since
+    /// retrying can't make the model appear.
+    #[error("Embedding model '{0}' is not available on the backend; run `ollama pull {0}`")]
+    EmbeddingModelNotFound(String),
+
+    /// The embedding backend rate-limited every retry attempt.
+    #[error("Embedding backend rate limited: {0}")]
+    EmbeddingRateLimited(String),
+
+    /// The embedding backend kept returning server errors after all retries.
+    #[error("Embedding backend error: {0}")]
+    EmbeddingBackendError(String),
+
+    /// An `expected_version` precondition on a write didn't match the
+    /// point's current version (optimistic concurrency failure).
+    #[error("Version conflict: {0}")]
+    Conflict(String),
+}
+
+/// Broad classification of an [`AppError`], mirroring MeiliSearch's
+/// `ResponseError` taxonomy: `invalid_request` for caller mistakes the
+/// client can fix and retry differently, `auth` for permission failures,
+/// `internal` for bugs/unexpected states in this service and failed Qdrant
+/// operations, and `system` for conditions the caller can't fix by changing
+/// their request (an unavailable service, a collection schema mismatch, the
+/// embedding backend failing).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorType {
+    InvalidRequest,
+    Auth,
+    Internal,
+    System,
+}
+
+impl ErrorType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ErrorType::InvalidRequest => "invalid_request",
+            ErrorType::Auth => "auth",
+            ErrorType::Internal => "internal",
+            ErrorType::System => "system",
+        }
+    }
+}
+
+impl AppError {
+    /// Stable, snake_case identifier for this error variant. Part of the
+    /// response body, so backends can dispatch on it instead of matching
+    /// on the human-readable message.
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "not_found",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::InvalidRequest(_) => "invalid_request",
+            AppError::Qdrant(_) => "qdrant_error",
+            AppError::Internal(_) => "internal_error",
+            AppError::Unavailable(_) => "service_unavailable",
+            AppError::SchemaMismatch { .. } => "schema_mismatch",
+            AppError::EmbeddingModelNotFound(_) => "embedding_model_not_found",
+            AppError::EmbeddingRateLimited(_) => "embedding_rate_limited",
+            AppError::EmbeddingBackendError(_) => "embedding_backend_error",
+            AppError::Conflict(_) => "conflict",
+        }
+    }
+
+    pub fn error_type(&self) -> ErrorType {
+        match self {
+            AppError::NotFound(_) | AppError::InvalidRequest(_) | AppError::Conflict(_) => {
+                ErrorType::InvalidRequest
+            }
+            AppError::Forbidden(_) => ErrorType::Auth,
+            AppError::Qdrant(_) | AppError::Internal(_) => ErrorType::Internal,
+            AppError::Unavailable(_)
+            | AppError::SchemaMismatch { .. }
+            | AppError::EmbeddingModelNotFound(_)
+            | AppError::EmbeddingRateLimited(_)
+            | AppError::EmbeddingBackendError(_) => ErrorType::System,
+        }
+    }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        let code = self.error_code();
+        let error_type = self.error_type();
+
         let (status, message) = match self {
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
             AppError::InvalidRequest(msg) => (StatusCode::BAD_REQUEST, msg),
             AppError::Qdrant(ref e) => {
                 tracing::error!("Qdrant error: {:?}", e);
@@ -37,11 +138,31 @@ impl IntoResponse for AppError {
                 tracing::error!("Internal error: {}", msg);
                 (StatusCode::INTERNAL_SERVER_ERROR, msg)
             }
+            AppError::Unavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
+            AppError::SchemaMismatch {
+                collection,
+                expected_dimension,
+                found_dimension,
+            } => (
+                StatusCode::CONFLICT,
+                format!(
+                    "Collection '{collection}' schema mismatch: expected dimension {expected_dimension}, found {found_dimension}"
+                ),
+            ),
+            AppError::EmbeddingModelNotFound(model) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("Embedding model '{model}' is not available on the backend; run `ollama pull {model}`"),
+            ),
+            AppError::EmbeddingRateLimited(msg) => (StatusCode::TOO_MANY_REQUESTS, msg),
+            AppError::EmbeddingBackendError(msg) => (StatusCode::BAD_GATEWAY, msg),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg),
         };
 
         let body = Json(json!({
-            "error": message,
-            "status": status.as_u16(),
+            "message": message,
+            "code": code,
+            "type": error_type.as_str(),
+            "link": format!("https://docs.synaplan-memories.dev/errors#{code}"),
         }));
 
         (status, body).into_response()
@@ -66,10 +187,98 @@ mod tests {
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[test]
+    fn test_forbidden_error() {
+        let err = AppError::Forbidden("Missing required scope: upsert".to_string());
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
     #[test]
     fn test_internal_error() {
         let err = AppError::Internal("Something went wrong".to_string());
         let response = err.into_response();
         assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
     }
+
+    #[test]
+    fn test_unavailable_error() {
+        let err = AppError::Unavailable("Job queue is saturated".to_string());
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[test]
+    fn test_schema_mismatch_error() {
+        let err = AppError::SchemaMismatch {
+            collection: "user_memories".to_string(),
+            expected_dimension: 1024,
+            found_dimension: 768,
+        };
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn test_embedding_model_not_found_error() {
+        let err = AppError::EmbeddingModelNotFound("bge-m3".to_string());
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[test]
+    fn test_embedding_rate_limited_error() {
+        let err = AppError::EmbeddingRateLimited("retries exhausted".to_string());
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[test]
+    fn test_embedding_backend_error() {
+        let err = AppError::EmbeddingBackendError("HTTP 503".to_string());
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    #[test]
+    fn test_conflict_error() {
+        let err = AppError::Conflict("expected version 2 but found 3".to_string());
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_error_body_is_structured() {
+        let err = AppError::NotFound("Memory not found".to_string());
+        let response = err.into_response();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(json["message"], "Memory not found");
+        assert_eq!(json["code"], "not_found");
+        assert_eq!(json["type"], "invalid_request");
+        assert_eq!(json["link"], "https://docs.synaplan-memories.dev/errors#not_found");
+    }
+
+    #[test]
+    fn test_forbidden_error_type_is_auth() {
+        assert_eq!(
+            AppError::Forbidden("nope".to_string()).error_type(),
+            ErrorType::Auth
+        );
+    }
+
+    #[test]
+    fn test_embedding_errors_are_system_type() {
+        assert_eq!(
+            AppError::EmbeddingRateLimited("retries exhausted".to_string()).error_type(),
+            ErrorType::System
+        );
+        assert_eq!(
+            AppError::Unavailable("saturated".to_string()).error_type(),
+            ErrorType::System
+        );
+    }
 }
@@ -0,0 +1,579 @@
+//! Small filter expression DSL for `SearchMemoriesRequest`,
+//! `ScrollMemoriesRequest`, and `SearchDocumentsRequest`'s `filter` field,
+//! e.g. `"category = notes AND created_at > 1700000000"`. [`parse_filter`]
+//! compiles such a string into the Qdrant `Filter`/`Condition` tree
+//! `QdrantService` ANDs alongside its own mandatory `user_id`/`active`
+//! conditions, the way [`crate::aggregation`] compiles a declarative
+//! aggregation spec instead of hard-coding one fixed shape.
+//!
+//! Grammar (lowest to highest precedence): `OR`, `AND`, unary `NOT`, then
+//! comparisons. Parentheses group sub-expressions.
+//!
+//! ```text
+//! expr       := or_expr
+//! or_expr    := and_expr ("OR" and_expr)*
+//! and_expr   := unary ("AND" unary)*
+//! unary      := "NOT" unary | "(" expr ")" | comparison
+//! comparison := IDENT op value | IDENT "IN" "[" value ("," value)* "]"
+//! op         := "=" | "!=" | "<" | ">" | "<=" | ">="
+//! value      := STRING | NUMBER | "true" | "false" | bare word
+//! ```
+
+use crate::error::AppError;
+use qdrant_client::qdrant::{Condition, Filter, Range};
+
+/// Parses `expr` and compiles it into a Qdrant [`Filter`].
+pub fn parse_filter(expr: &str) -> Result<Filter, AppError> {
+    let tokens = tokenize(expr)?;
+    if tokens.is_empty() {
+        return Err(AppError::InvalidRequest("Filter expression is empty".to_string()));
+    }
+
+    let mut parser = Parser { tokens: &tokens, pos: 0, depth: 0 };
+    let parsed = parser.parse_expr()?;
+    if parser.pos != tokens.len() {
+        return Err(AppError::InvalidRequest(
+            "Unexpected trailing tokens in filter expression".to_string(),
+        ));
+    }
+
+    expr_to_filter(&parsed)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Str(String),
+    Num(f64),
+    Bool(bool),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+}
+
+impl CmpOp {
+    fn symbol(&self) -> &'static str {
+        match self {
+            CmpOp::Eq => "=",
+            CmpOp::Ne => "!=",
+            CmpOp::Lt => "<",
+            CmpOp::Gt => ">",
+            CmpOp::Le => "<=",
+            CmpOp::Ge => ">=",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Cmp(String, CmpOp, Value),
+    In(String, Vec<Value>),
+    /// A run of one or more `AND`-joined operands, built flat by
+    /// `parse_and` rather than as a left-nested `Box` chain, so an
+    /// expression with many `AND`s doesn't produce a tree `n` levels deep -
+    /// neither `expr_to_filter` nor `Expr`'s `Drop` glue recurses per
+    /// operand.
+    And(Vec<Expr>),
+    /// Same flattening as `And`, for `OR`.
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Bool(bool),
+    Op(CmpOp),
+    And,
+    Or,
+    Not,
+    In,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, AppError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        match c {
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            '[' => {
+                tokens.push(Token::LBracket);
+                i += 1;
+            }
+            ']' => {
+                tokens.push(Token::RBracket);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '=' => {
+                tokens.push(Token::Op(CmpOp::Eq));
+                i += 1;
+            }
+            '!' if chars.get(i + 1) == Some(&'=') => {
+                tokens.push(Token::Op(CmpOp::Ne));
+                i += 2;
+            }
+            '<' if chars.get(i + 1) == Some(&'=') => {
+                tokens.push(Token::Op(CmpOp::Le));
+                i += 2;
+            }
+            '<' => {
+                tokens.push(Token::Op(CmpOp::Lt));
+                i += 1;
+            }
+            '>' if chars.get(i + 1) == Some(&'=') => {
+                tokens.push(Token::Op(CmpOp::Ge));
+                i += 2;
+            }
+            '>' => {
+                tokens.push(Token::Op(CmpOp::Gt));
+                i += 1;
+            }
+            '"' | '\'' => {
+                let quote = c;
+                i += 1;
+                let start = i;
+                while i < chars.len() && chars[i] != quote {
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    return Err(AppError::InvalidRequest(
+                        "Unterminated string literal in filter expression".to_string(),
+                    ));
+                }
+                tokens.push(Token::Str(chars[start..i].iter().collect()));
+                i += 1;
+            }
+            _ if c.is_ascii_digit() || (c == '-' && chars.get(i + 1).is_some_and(char::is_ascii_digit)) => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let num: f64 = text.parse().map_err(|_| {
+                    AppError::InvalidRequest(format!("Invalid number literal `{}` in filter expression", text))
+                })?;
+                tokens.push(Token::Num(num));
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.') {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                tokens.push(match word.to_ascii_uppercase().as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    "IN" => Token::In,
+                    "TRUE" => Token::Bool(true),
+                    "FALSE" => Token::Bool(false),
+                    _ => Token::Ident(word),
+                });
+            }
+            other => {
+                return Err(AppError::InvalidRequest(format!(
+                    "Unexpected character `{}` in filter expression",
+                    other
+                )));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Upper bound on how deeply `(...)` groups and `NOT` chains may nest.
+/// Without this, a filter like `((((...))))` or `NOT NOT NOT ...` recurses
+/// unbounded through `parse_expr`/`parse_unary` and can overflow the stack
+/// on ordinary authenticated input.
+const MAX_FILTER_DEPTH: usize = 32;
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    /// Enters one more level of `(...)`/`NOT` nesting, erroring instead of
+    /// recursing once [`MAX_FILTER_DEPTH`] is exceeded. Pair with `leave_nested`.
+    fn enter_nested(&mut self) -> Result<(), AppError> {
+        self.depth += 1;
+        if self.depth > MAX_FILTER_DEPTH {
+            return Err(AppError::InvalidRequest(format!(
+                "Filter expression is nested too deeply (max depth {})",
+                MAX_FILTER_DEPTH
+            )));
+        }
+        Ok(())
+    }
+
+    fn leave_nested(&mut self) {
+        self.depth -= 1;
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), AppError> {
+        match self.advance() {
+            Some(t) if *t == expected => Ok(()),
+            other => Err(AppError::InvalidRequest(format!(
+                "Expected {:?} in filter expression, got {:?}",
+                expected, other
+            ))),
+        }
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, AppError> {
+        self.parse_or()
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, AppError> {
+        let mut items = vec![self.parse_and()?];
+        while matches!(self.peek(), Some(Token::Or)) {
+            self.advance();
+            items.push(self.parse_and()?);
+        }
+        Ok(if items.len() == 1 { items.pop().unwrap() } else { Expr::Or(items) })
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, AppError> {
+        let mut items = vec![self.parse_unary()?];
+        while matches!(self.peek(), Some(Token::And)) {
+            self.advance();
+            items.push(self.parse_unary()?);
+        }
+        Ok(if items.len() == 1 { items.pop().unwrap() } else { Expr::And(items) })
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, AppError> {
+        if matches!(self.peek(), Some(Token::Not)) {
+            self.advance();
+            self.enter_nested()?;
+            let inner = self.parse_unary();
+            self.leave_nested();
+            return Ok(Expr::Not(Box::new(inner?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, AppError> {
+        match self.advance().cloned() {
+            Some(Token::LParen) => {
+                self.enter_nested()?;
+                let expr = self.parse_expr();
+                self.leave_nested();
+                let expr = expr?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            Some(Token::Ident(field)) => match self.advance().cloned() {
+                Some(Token::Op(op)) => {
+                    let value = self.parse_value()?;
+                    Ok(Expr::Cmp(field, op, value))
+                }
+                Some(Token::In) => {
+                    self.expect(Token::LBracket)?;
+                    let mut values = vec![self.parse_value()?];
+                    while matches!(self.peek(), Some(Token::Comma)) {
+                        self.advance();
+                        values.push(self.parse_value()?);
+                    }
+                    self.expect(Token::RBracket)?;
+                    Ok(Expr::In(field, values))
+                }
+                other => Err(AppError::InvalidRequest(format!(
+                    "Expected an operator or `IN` after `{}` in filter expression, got {:?}",
+                    field, other
+                ))),
+            },
+            other => Err(AppError::InvalidRequest(format!(
+                "Unexpected token in filter expression: {:?}",
+                other
+            ))),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Value, AppError> {
+        match self.advance().cloned() {
+            Some(Token::Str(s)) => Ok(Value::Str(s)),
+            Some(Token::Num(n)) => Ok(Value::Num(n)),
+            Some(Token::Bool(b)) => Ok(Value::Bool(b)),
+            Some(Token::Ident(word)) => Ok(Value::Str(word)),
+            other => Err(AppError::InvalidRequest(format!(
+                "Expected a value in filter expression, got {:?}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Compiles a parsed expression into a `Filter`. `Expr::And`/`Expr::Or`
+/// already hold their operands as a flat `Vec` (see [`Expr`]), so this is a
+/// single pass over that list rather than a recursive flatten of a
+/// binary-operator tree.
+fn expr_to_filter(expr: &Expr) -> Result<Filter, AppError> {
+    match expr {
+        Expr::And(items) => {
+            let must = items.iter().map(expr_to_condition).collect::<Result<Vec<_>, _>>()?;
+            Ok(Filter { must, ..Default::default() })
+        }
+        Expr::Or(items) => {
+            let should = items.iter().map(expr_to_condition).collect::<Result<Vec<_>, _>>()?;
+            Ok(Filter { should, ..Default::default() })
+        }
+        other => Ok(Filter::must(vec![expr_to_condition(other)?])),
+    }
+}
+
+fn expr_to_condition(expr: &Expr) -> Result<Condition, AppError> {
+    match expr {
+        Expr::Cmp(field, op, value) => cmp_condition(field, op, value),
+        Expr::In(field, values) => in_condition(field, values),
+        Expr::Not(inner) => Ok(Condition::from(Filter {
+            must_not: vec![expr_to_condition(inner)?],
+            ..Default::default()
+        })),
+        Expr::And(..) | Expr::Or(..) => Ok(Condition::from(expr_to_filter(expr)?)),
+    }
+}
+
+fn cmp_condition(field: &str, op: &CmpOp, value: &Value) -> Result<Condition, AppError> {
+    match op {
+        CmpOp::Eq => value_match_condition(field, value),
+        CmpOp::Ne => Ok(Condition::from(Filter {
+            must_not: vec![value_match_condition(field, value)?],
+            ..Default::default()
+        })),
+        CmpOp::Lt | CmpOp::Gt | CmpOp::Le | CmpOp::Ge => {
+            let Value::Num(n) = value else {
+                return Err(AppError::InvalidRequest(format!(
+                    "`{}` only supports numeric values, used on field `{}`",
+                    op.symbol(),
+                    field
+                )));
+            };
+            let mut range = Range { lt: None, gt: None, gte: None, lte: None };
+            match op {
+                CmpOp::Lt => range.lt = Some(*n),
+                CmpOp::Gt => range.gt = Some(*n),
+                CmpOp::Le => range.lte = Some(*n),
+                CmpOp::Ge => range.gte = Some(*n),
+                CmpOp::Eq | CmpOp::Ne => unreachable!("handled above"),
+            }
+            Ok(Condition::range(field, range))
+        }
+    }
+}
+
+fn value_match_condition(field: &str, value: &Value) -> Result<Condition, AppError> {
+    match value {
+        Value::Str(s) => Ok(Condition::matches(field, s.clone())),
+        Value::Bool(b) => Ok(Condition::matches(field, *b)),
+        Value::Num(n) => Ok(Condition::matches(field, whole_number(field, *n)?)),
+    }
+}
+
+fn in_condition(field: &str, values: &[Value]) -> Result<Condition, AppError> {
+    if values.is_empty() {
+        return Err(AppError::InvalidRequest(format!("IN list for `{}` must not be empty", field)));
+    }
+    if values.iter().all(|v| matches!(v, Value::Str(_))) {
+        let strings: Vec<String> = values
+            .iter()
+            .map(|v| match v {
+                Value::Str(s) => s.clone(),
+                _ => unreachable!("checked all-Str above"),
+            })
+            .collect();
+        Ok(Condition::matches(field, strings))
+    } else if values.iter().all(|v| matches!(v, Value::Num(_))) {
+        let ints = values
+            .iter()
+            .map(|v| match v {
+                Value::Num(n) => whole_number(field, *n),
+                _ => unreachable!("checked all-Num above"),
+            })
+            .collect::<Result<Vec<i64>, _>>()?;
+        Ok(Condition::matches(field, ints))
+    } else {
+        Err(AppError::InvalidRequest(format!(
+            "IN list for `{}` must be all strings or all whole numbers",
+            field
+        )))
+    }
+}
+
+fn whole_number(field: &str, n: f64) -> Result<i64, AppError> {
+    if n.fract() != 0.0 {
+        return Err(AppError::InvalidRequest(format!(
+            "`{}` only supports whole numbers for exact matches, got {}",
+            field, n
+        )));
+    }
+    Ok(n as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_equality() {
+        let filter = parse_filter("category = notes").unwrap();
+        assert_eq!(filter.must.len(), 1);
+        assert!(filter.should.is_empty());
+        assert!(filter.must_not.is_empty());
+    }
+
+    #[test]
+    fn test_quoted_string_value() {
+        let filter = parse_filter("category = \"feedback false positive\"").unwrap();
+        assert_eq!(filter.must.len(), 1);
+    }
+
+    #[test]
+    fn test_and_chain_flattens_into_single_must_list() {
+        let filter = parse_filter("category = notes AND created_at > 1700000000 AND active = true").unwrap();
+        assert_eq!(filter.must.len(), 3);
+    }
+
+    #[test]
+    fn test_or_chain_flattens_into_single_should_list() {
+        let filter = parse_filter("category = notes OR category = personal").unwrap();
+        assert_eq!(filter.should.len(), 2);
+    }
+
+    #[test]
+    fn test_not_equal_becomes_nested_must_not() {
+        let filter = parse_filter("category != notes").unwrap();
+        assert_eq!(filter.must.len(), 1);
+    }
+
+    #[test]
+    fn test_not_wraps_inner_condition() {
+        let filter = parse_filter("NOT category = notes").unwrap();
+        assert_eq!(filter.must.len(), 1);
+    }
+
+    #[test]
+    fn test_parenthesized_or_inside_and() {
+        let filter = parse_filter("user_id = 1730 AND (category = notes OR category = personal)").unwrap();
+        assert_eq!(filter.must.len(), 2);
+    }
+
+    #[test]
+    fn test_in_list_of_strings() {
+        let filter = parse_filter("category IN [notes, personal, feedback]").unwrap();
+        assert_eq!(filter.must.len(), 1);
+    }
+
+    #[test]
+    fn test_comparison_operators() {
+        for op in ["<", ">", "<=", ">="] {
+            let expr = format!("created_at {} 1700000000", op);
+            assert!(parse_filter(&expr).is_ok(), "operator {} should parse", op);
+        }
+    }
+
+    #[test]
+    fn test_unknown_operator_is_rejected() {
+        assert!(parse_filter("category ~= notes").is_err());
+    }
+
+    #[test]
+    fn test_non_numeric_range_comparison_is_rejected() {
+        assert!(parse_filter("category > notes").is_err());
+    }
+
+    #[test]
+    fn test_fractional_equality_is_rejected() {
+        assert!(parse_filter("score = 1.5").is_err());
+    }
+
+    #[test]
+    fn test_empty_filter_is_rejected() {
+        assert!(parse_filter("").is_err());
+        assert!(parse_filter("   ").is_err());
+    }
+
+    #[test]
+    fn test_unterminated_string_is_rejected() {
+        assert!(parse_filter("category = \"notes").is_err());
+    }
+
+    #[test]
+    fn test_trailing_tokens_are_rejected() {
+        assert!(parse_filter("category = notes extra").is_err());
+    }
+
+    #[test]
+    fn test_deeply_nested_parens_are_rejected_not_stack_overflow() {
+        let expr = format!("{}category = notes{}", "(".repeat(64), ")".repeat(64));
+        assert!(parse_filter(&expr).is_err());
+    }
+
+    #[test]
+    fn test_deeply_nested_not_is_rejected_not_stack_overflow() {
+        let expr = format!("{}category = notes", "NOT ".repeat(64));
+        assert!(parse_filter(&expr).is_err());
+    }
+
+    #[test]
+    fn test_moderately_nested_parens_still_parse() {
+        let expr = format!("{}category = notes{}", "(".repeat(8), ")".repeat(8));
+        assert!(parse_filter(&expr).is_ok());
+    }
+
+    #[test]
+    fn test_long_flat_and_chain_does_not_overflow_stack() {
+        let expr = std::iter::repeat("category = a")
+            .take(50_000)
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let filter = parse_filter(&expr).unwrap();
+        assert_eq!(filter.must.len(), 50_000);
+    }
+}
@@ -1,12 +1,17 @@
+use crate::auth::{Action, ApiKeyPermissions};
+use crate::embedding::{resolve_vector, Embedder as _};
 use crate::error::AppError;
+use crate::jobs::{JobAcceptedResponse, JobStatusResponse};
 use crate::models::*;
+use crate::wire::{Accepts, Wire, Wired};
 use crate::AppState;
 use axum::{
     extract::{Path, Query, State},
     http::{header, StatusCode},
     response::IntoResponse,
-    Json,
+    Extension, Json,
 };
+use futures::StreamExt;
 use tracing::info;
 use utoipa;
 #[derive(Debug, serde::Deserialize)]
@@ -16,7 +21,8 @@ pub struct NamespaceQuery {
 
 /// Get service capabilities and configuration
 ///
-/// **Purpose:** Returns service version, vector dimensions, and embedding capabilities.
+/// **Purpose:** Returns service version, vector dimensions, and embedding capabilities,
+/// reflecting whatever `Embedder` `build_embedder` actually resolved from config.
 /// Useful for backend to validate compatibility before sending requests.
 ///
 /// **Cache:** Response is cached for 30 seconds (`Cache-Control: public, max-age=30`).
@@ -34,11 +40,15 @@ pub async fn get_capabilities(
     // Keep this lightweight and cacheable; do not call external services here.
     let config = state.config.as_ref();
 
-    let body = ServiceCapabilities {
-        service: "synaplan-qdrant-service".to_string(),
-        version: env!("CARGO_PKG_VERSION").to_string(),
-        vector_dimension: config.vector_dimension,
-        embedding: EmbeddingCapabilities {
+    let embedding = match &state.embedder {
+        Some(embedder) => EmbeddingCapabilities {
+            supported: true,
+            backend: embedder.backend(),
+            model: embedder.model(),
+            device: embedder.device(),
+            vector_dimension: config.vector_dimension,
+        },
+        None => EmbeddingCapabilities {
             supported: false,
             backend: "none".to_string(),
             model: None,
@@ -47,6 +57,14 @@ pub async fn get_capabilities(
         },
     };
 
+    let body = ServiceCapabilities {
+        service: "synaplan-qdrant-service".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        vector_dimension: config.vector_dimension,
+        embedding,
+        lexical_search_available: !config.memory_encryption_enabled,
+    };
+
     Ok((
         [(header::CACHE_CONTROL, "public, max-age=30")],
         Json(body),
@@ -74,33 +92,52 @@ pub async fn get_capabilities(
             "message": "Memory upserted successfully"
         })),
         (status = 400, description = "Invalid request (wrong vector dimension, invalid payload)"),
+        (status = 409, description = "`expected_version` didn't match the point's current version"),
         (status = 500, description = "Qdrant error")
     )
 )]
 pub async fn upsert_memory(
     State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
     Json(req): Json<UpsertMemoryRequest>,
-) -> Result<Json<serde_json::Value>, AppError> {
+) -> Result<impl IntoResponse, AppError> {
+    permissions.require(Action::Upsert)?;
+    permissions.check_user_scope(req.payload.user_id)?;
+    permissions.check_namespace_scope(req.namespace.as_deref())?;
     info!("Upserting memory: {}", req.point_id);
 
-    state
+    let vector = resolve_vector(
+        state.embedder.as_ref(),
+        req.vector,
+        Some(req.payload.value.as_str()),
+        state.config.vector_dimension,
+    )
+    .await?;
+
+    let version = state
         .qdrant
-        .upsert_memory(
+        .upsert_memory_with_sparse(
             req.point_id.clone(),
-            req.vector,
+            vector,
+            req.sparse_vector,
             req.payload,
             req.namespace.as_deref(),
+            req.expected_version,
         )
         .await?;
 
     // Track stats
     state.stats.increment_upserts(1);
 
-    Ok(Json(serde_json::json!({
-        "success": true,
-        "point_id": req.point_id,
-        "message": "Memory upserted successfully"
-    })))
+    Ok((
+        [(header::ETAG, version.to_string())],
+        Json(serde_json::json!({
+            "success": true,
+            "point_id": req.point_id,
+            "version": version,
+            "message": "Memory upserted successfully"
+        })),
+    ))
 }
 
 /// Get a single memory by ID
@@ -118,15 +155,20 @@ pub async fn upsert_memory(
         ("namespace" = Option<String>, Query, description = "Optional namespace for alternative collection")
     ),
     responses(
-        (status = 200, description = "Memory found", body = MemoryResponse),
+        (status = 200, description = "Memory found", body = MemoryResponse, headers(
+            ("ETag" = String, description = "The memory's current version; pass back as `expected_version` on upsert")
+        )),
         (status = 404, description = "Memory not found")
     )
 )]
 pub async fn get_memory(
     State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
     Path(point_id): Path<String>,
     Query(query): Query<NamespaceQuery>,
-) -> Result<Json<MemoryResponse>, AppError> {
+) -> Result<impl IntoResponse, AppError> {
+    permissions.require(Action::Search)?;
+    permissions.check_namespace_scope(query.namespace.as_deref())?;
     info!("Getting memory: {}", point_id);
 
     let payload = state
@@ -134,10 +176,76 @@ pub async fn get_memory(
         .get_memory(&point_id, query.namespace.as_deref())
         .await?
         .ok_or_else(|| AppError::NotFound(format!("Memory not found: {}", point_id)))?;
+    permissions.check_user_scope(payload.user_id)?;
+
+    let version = payload.version;
+
+    Ok((
+        [(header::ETAG, version.to_string())],
+        Json(MemoryResponse {
+            id: point_id,
+            payload,
+        }),
+    ))
+}
+
+/// Fetch many memories by ID in a single round trip
+///
+/// **Purpose:** Mirrors `/memories/batch` for reads — display N specific
+/// memories without N separate `GET /memories/{point_id}` calls.
+///
+/// **Usage:**
+/// - `point_ids`: up to 256 IDs to fetch
+/// - Returns `found` (as `MemoryResponse`s) plus `missing` (IDs with no matching point)
+#[utoipa::path(
+    post,
+    path = "/memories/get-batch",
+    tag = "Memories",
+    request_body = GetMemoriesBatchRequest,
+    responses(
+        (status = 200, description = "Batch retrieval completed", body = GetMemoriesBatchResponse),
+        (status = 400, description = "Invalid request (too many point_ids)"),
+        (status = 500, description = "Qdrant error")
+    )
+)]
+pub async fn get_memories_batch(
+    State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
+    Json(req): Json<GetMemoriesBatchRequest>,
+) -> Result<Json<GetMemoriesBatchResponse>, AppError> {
+    permissions.require(Action::Search)?;
+    permissions.check_namespace_scope(req.namespace.as_deref())?;
+    if req.point_ids.is_empty() {
+        return Err(AppError::InvalidRequest(
+            "point_ids cannot be empty".to_string(),
+        ));
+    }
+    if req.point_ids.len() > 256 {
+        return Err(AppError::InvalidRequest(
+            "Maximum 256 point_ids per batch".to_string(),
+        ));
+    }
 
-    Ok(Json(MemoryResponse {
-        id: point_id,
-        payload,
+    let (found, mut missing) = state
+        .qdrant
+        .get_memories(&req.point_ids, req.namespace.as_deref())
+        .await?;
+
+    // Points outside the key's scope are reported the same way as points
+    // that don't exist at all, rather than leaking which of another user's
+    // IDs are valid via a distinct error.
+    let mut scoped_found = Vec::with_capacity(found.len());
+    for (id, payload) in found {
+        if permissions.check_user_scope(payload.user_id).is_ok() {
+            scoped_found.push(MemoryResponse { id, payload });
+        } else {
+            missing.push(id);
+        }
+    }
+
+    Ok(Json(GetMemoriesBatchResponse {
+        found: scoped_found,
+        missing,
     }))
 }
 
@@ -166,9 +274,19 @@ pub async fn get_memory(
 )]
 pub async fn delete_memory(
     State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
     Path(point_id): Path<String>,
     Query(query): Query<NamespaceQuery>,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    permissions.require(Action::Delete)?;
+    permissions.check_namespace_scope(query.namespace.as_deref())?;
+    if let Some(payload) = state
+        .qdrant
+        .get_memory(&point_id, query.namespace.as_deref())
+        .await?
+    {
+        permissions.check_user_scope(payload.user_id)?;
+    }
     info!("Deleting memory: {}", point_id);
 
     state
@@ -186,6 +304,89 @@ pub async fn delete_memory(
     })))
 }
 
+/// Delete all memories for a user matching an optional category
+#[utoipa::path(
+    post,
+    path = "/memories/delete-by-category",
+    tag = "Memories",
+    request_body = DeleteMemoriesByCategoryRequest,
+    responses(
+        (status = 200, description = "Memories deleted", body = u64),
+        (status = 500, description = "Qdrant error")
+    )
+)]
+pub async fn delete_memories_by_category(
+    State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
+    Json(req): Json<DeleteMemoriesByCategoryRequest>,
+) -> Result<Json<u64>, AppError> {
+    permissions.require(Action::Delete)?;
+    permissions.check_user_scope(req.user_id)?;
+    permissions.check_namespace_scope(req.namespace.as_deref())?;
+    let deleted = state
+        .qdrant
+        .delete_memories_by_category(req.user_id, Some(req.category), req.namespace.as_deref())
+        .await?;
+    Ok(Json(deleted))
+}
+
+/// Delete all memories matching a [`crate::filter`] expression, ANDed with `user_id`
+#[utoipa::path(
+    post,
+    path = "/memories/delete-by-filter",
+    tag = "Memories",
+    request_body = DeleteMemoriesByFilterRequest,
+    responses(
+        (status = 200, description = "Memories deleted", body = u64),
+        (status = 400, description = "Invalid filter expression"),
+        (status = 500, description = "Qdrant error")
+    )
+)]
+pub async fn delete_memories_by_filter(
+    State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
+    Json(req): Json<DeleteMemoriesByFilterRequest>,
+) -> Result<Json<u64>, AppError> {
+    permissions.require(Action::Delete)?;
+    permissions.check_user_scope(req.user_id)?;
+    permissions.check_namespace_scope(req.namespace.as_deref())?;
+    let deleted = state
+        .qdrant
+        .delete_memories_by_filter(req.user_id, &req.filter, req.namespace.as_deref())
+        .await?;
+    Ok(Json(deleted))
+}
+
+/// Delete all memories for a user
+#[utoipa::path(
+    delete,
+    path = "/memories/user/{user_id}",
+    tag = "Memories",
+    params(
+        ("user_id" = i64, Path, description = "User ID"),
+        ("namespace" = Option<String>, Query, description = "Optional namespace for alternative collection")
+    ),
+    responses(
+        (status = 200, description = "All user memories deleted", body = u64),
+        (status = 500, description = "Qdrant error")
+    )
+)]
+pub async fn delete_all_memories_for_user(
+    State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
+    Path(user_id): Path<i64>,
+    Query(query): Query<NamespaceQuery>,
+) -> Result<Json<u64>, AppError> {
+    permissions.require(Action::Delete)?;
+    permissions.check_user_scope(user_id)?;
+    permissions.check_namespace_scope(query.namespace.as_deref())?;
+    let deleted = state
+        .qdrant
+        .delete_all_memories_for_user(user_id, query.namespace.as_deref())
+        .await?;
+    Ok(Json(deleted))
+}
+
 /// Search memories by similarity
 ///
 /// **Purpose:** Find similar memories using vector search (cosine similarity).
@@ -210,29 +411,94 @@ pub async fn delete_memory(
 
 pub async fn search_memories(
     State(state): State<AppState>,
+    request_id: crate::request_id::RequestId,
+    Extension(permissions): Extension<ApiKeyPermissions>,
     Json(req): Json<SearchMemoriesRequest>,
 ) -> Result<Json<SearchMemoriesResponse>, AppError> {
+    permissions.require(Action::Search)?;
+    permissions.check_user_scope(req.user_id)?;
+    permissions.check_namespace_scope(req.namespace.as_deref())?;
     info!(
-        "Searching memories for user {} with limit {}",
-        req.user_id, req.limit
+        "[{}] Searching memories for user {} with limit {}",
+        request_id, req.user_id, req.limit
     );
 
-    let results = state
-        .qdrant
-        .search_memories(
-            req.query_vector,
-            req.user_id,
-            req.category,
-            req.limit,
-            req.min_score,
-            req.namespace.as_deref(),
-        )
-        .await?;
+    let query_vector = resolve_vector(
+        state.embedder.as_ref(),
+        req.query_vector,
+        req.query_text.as_deref(),
+        state.config.vector_dimension,
+    )
+    .await?;
 
-    let search_results: Vec<SearchResult> = results
-        .into_iter()
-        .map(|(id, score, payload)| SearchResult { id, score, payload })
-        .collect();
+    let search_results: Vec<SearchResult> = if let Some(query_text) = req.query_text.as_deref() {
+        state
+            .qdrant
+            .search_memories_semantic_lexical(
+                query_vector,
+                query_text,
+                req.semantic_ratio,
+                req.user_id,
+                req.category,
+                req.limit,
+                req.min_score,
+                req.namespace.as_deref(),
+                req.filter.as_deref(),
+            )
+            .await?
+            .into_iter()
+            .map(|(id, score, payload, modality)| SearchResult {
+                id,
+                score,
+                payload,
+                modality,
+            })
+            .collect()
+    } else {
+        match req.query_sparse_vector {
+            Some(sparse) => state
+                .qdrant
+                .search_memories_hybrid(
+                    query_vector,
+                    sparse,
+                    req.user_id,
+                    req.category,
+                    req.limit,
+                    req.min_score,
+                    req.namespace.as_deref(),
+                    req.filter.as_deref(),
+                )
+                .await?
+                .into_iter()
+                .map(|(id, score, payload, modality)| SearchResult {
+                    id,
+                    score,
+                    payload,
+                    modality,
+                })
+                .collect(),
+            None => state
+                .qdrant
+                .search_memories(
+                    query_vector,
+                    req.user_id,
+                    req.category,
+                    req.limit,
+                    req.min_score,
+                    req.namespace.as_deref(),
+                    req.filter.as_deref(),
+                )
+                .await?
+                .into_iter()
+                .map(|(id, score, payload)| SearchResult {
+                    id,
+                    score,
+                    payload,
+                    modality: "dense".to_string(),
+                })
+                .collect(),
+        }
+    };
 
     let count = search_results.len();
 
@@ -264,8 +530,11 @@ pub async fn search_memories(
 )]
 pub async fn get_collection_info(
     State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
     Query(query): Query<NamespaceQuery>,
 ) -> Result<Json<CollectionInfo>, AppError> {
+    permissions.require(Action::Stats)?;
+    permissions.check_namespace_scope(query.namespace.as_deref())?;
     let (status, points_count, vectors_count, indexed_vectors_count) =
         state.qdrant.get_collection_info(query.namespace.as_deref()).await?;
 
@@ -277,40 +546,65 @@ pub async fn get_collection_info(
     }))
 }
 
-/// Scroll (list) all memories for a user
+/// Scroll (list) memories for a user, one page at a time
 ///
-/// **Purpose:** Retrieve all memories for a user without vector search.
+/// **Purpose:** Retrieve memories for a user without vector search, paging
+/// through large result sets instead of loading them all at once.
 /// Useful for displaying a complete memory list in the UI.
 ///
 /// **Usage:**
 /// - Filter by `user_id` (required) and optionally `category`
-/// - Set `limit` (max 10,000 to avoid memory issues)
-///
-/// **Performance:** Can be slow for users with >10k memories. Consider pagination or caching.
+/// - Set `limit` (default 100, max 1,000 per page)
+/// - Pass the previous response's `next_offset` back as `offset` to fetch
+///   the next page; stop once `next_offset` is `null`
 #[utoipa::path(
     post,
     path = "/memories/scroll",
     tag = "Memories",
     request_body = ScrollMemoriesRequest,
     responses(
-        (status = 200, description = "All memories for user", body = ScrollMemoriesResponse),
+        (status = 200, description = "Page of memories for user", body = ScrollMemoriesResponse),
+        (status = 400, description = "Invalid request"),
         (status = 500, description = "Qdrant error")
     )
 )]
 pub async fn scroll_memories(
     State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
     Json(req): Json<ScrollMemoriesRequest>,
 ) -> Result<Json<ScrollMemoriesResponse>, AppError> {
+    permissions.require(Action::Search)?;
+    permissions.check_user_scope(req.user_id)?;
+    permissions.check_namespace_scope(req.namespace.as_deref())?;
+    if req.limit < 1 || req.limit > MAX_SCROLL_LIMIT {
+        return Err(AppError::InvalidRequest(format!(
+            "limit must be between 1 and {}, got {}",
+            MAX_SCROLL_LIMIT, req.limit
+        )));
+    }
     info!(
         "Scrolling memories for user {} with limit {}",
         req.user_id, req.limit
     );
 
-    let results = state
+    let (results, next_offset) = state
         .qdrant
-        .scroll_memories(req.user_id, req.category, req.limit, req.namespace.as_deref())
+        .scroll_memories(
+            req.user_id,
+            req.category.clone(),
+            req.limit,
+            req.namespace.as_deref(),
+            req.offset,
+            req.filter.as_deref(),
+        )
         .await?;
 
+    let total = state
+        .qdrant
+        .count_memories(req.user_id, req.category, req.namespace.as_deref())
+        .await
+        .ok();
+
     let memories: Vec<MemoryResponse> = results
         .into_iter()
         .map(|(id, payload)| MemoryResponse { id, payload })
@@ -318,7 +612,12 @@ pub async fn scroll_memories(
 
     let count = memories.len();
 
-    Ok(Json(ScrollMemoriesResponse { memories, count }))
+    Ok(Json(ScrollMemoriesResponse {
+        memories,
+        count,
+        next_offset,
+        total,
+    }))
 }
 
 /// Batch upsert multiple memories
@@ -334,6 +633,12 @@ pub async fn scroll_memories(
 /// **Performance:**
 /// - Individual: 50 requests × ~10ms = 500ms
 /// - Batch: 1 request × ~50ms = **50ms** (10× faster!)
+///
+/// **Binary wire format:** accepts `Content-Type: application/x-postcard` (or
+/// `application/octet-stream`) as a compact alternative to JSON for this
+/// vector-heavy payload, and returns `postcard` instead of JSON when the
+/// caller sends `Accept: application/x-postcard`. Defaults to JSON either
+/// way, so existing clients are unaffected.
 #[utoipa::path(
     post,
     path = "/memories/batch",
@@ -354,8 +659,11 @@ pub async fn scroll_memories(
 )]
 pub async fn batch_upsert_memories(
     State(state): State<AppState>,
-    Json(req): Json<BatchUpsertRequest>,
-) -> Result<Json<BatchOperationResponse>, AppError> {
+    Extension(permissions): Extension<ApiKeyPermissions>,
+    Accepts(format): Accepts,
+    Wire(req): Wire<BatchUpsertRequest>,
+) -> Result<Wired<BatchOperationResponse>, AppError> {
+    permissions.require(Action::Upsert)?;
     let point_count = req.points.len();
     info!("Batch upserting {} memories", point_count);
 
@@ -375,19 +683,35 @@ pub async fn batch_upsert_memories(
     let mut failed_count = 0;
     let mut errors = Vec::new();
 
-    // Process each point individually (could be optimized with Qdrant batch API later)
+    let mut items = Vec::with_capacity(point_count);
     for point in req.points {
-        match state
-            .qdrant
-            .upsert_memory(
-                point.point_id.clone(),
-                point.vector,
-                point.payload,
-                point.namespace.as_deref(),
-            )
-            .await
+        if let Err(e) = permissions
+            .check_user_scope(point.payload.user_id)
+            .and_then(|()| permissions.check_namespace_scope(point.namespace.as_deref()))
         {
-            Ok(_) => success_count += 1,
+            failed_count += 1;
+            errors.push(BatchError {
+                point_id: point.point_id,
+                error: e.to_string(),
+            });
+            continue;
+        }
+
+        match resolve_vector(
+            state.embedder.as_ref(),
+            point.vector,
+            Some(point.payload.value.as_str()),
+            state.config.vector_dimension,
+        )
+        .await
+        {
+            Ok(vector) => items.push(crate::qdrant::MemoryUpsertItem {
+                point_id: point.point_id,
+                vector,
+                sparse_vector: point.sparse_vector,
+                payload: point.payload,
+                namespace: point.namespace,
+            }),
             Err(e) => {
                 failed_count += 1;
                 errors.push(BatchError {
@@ -398,16 +722,291 @@ pub async fn batch_upsert_memories(
         }
     }
 
+    for (point_id, result) in state.qdrant.upsert_memories_batch(items).await {
+        match result {
+            Ok(_) => success_count += 1,
+            Err(e) => {
+                failed_count += 1;
+                errors.push(BatchError {
+                    point_id,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
     // Track stats
     state.stats.increment_upserts(success_count as u64);
 
-    Ok(Json(BatchOperationResponse {
+    Ok(Wired::new(
+        format,
+        BatchOperationResponse {
+            success_count,
+            failed_count,
+            errors,
+        },
+    ))
+}
+
+/// Enqueue a batch of memories for asynchronous upsert
+///
+/// **Purpose:** Accepts the same payload as `/memories/batch` but returns
+/// immediately with a job ID instead of blocking the connection for the
+/// duration of the upsert. Large imports should use this endpoint and poll
+/// `GET /jobs/:job_id` for progress.
+#[utoipa::path(
+    post,
+    path = "/memories/batch/async",
+    tag = "Memories",
+    request_body = BatchUpsertRequest,
+    responses(
+        (status = 202, description = "Batch accepted for background processing", body = JobAcceptedResponse),
+        (status = 400, description = "Invalid request (too many points or validation error)"),
+        (status = 503, description = "Job queue is saturated; retry later")
+    )
+)]
+pub async fn batch_upsert_memories_async(
+    State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
+    Json(req): Json<BatchUpsertRequest>,
+) -> Result<(StatusCode, Json<JobAcceptedResponse>), AppError> {
+    permissions.require(Action::Upsert)?;
+    let point_count = req.points.len();
+
+    if point_count == 0 {
+        return Err(AppError::InvalidRequest(
+            "Batch cannot be empty".to_string(),
+        ));
+    }
+    if point_count > 100 {
+        return Err(AppError::InvalidRequest(
+            "Batch size exceeds maximum of 100 points".to_string(),
+        ));
+    }
+    for point in &req.points {
+        permissions.check_user_scope(point.payload.user_id)?;
+        permissions.check_namespace_scope(point.namespace.as_deref())?;
+    }
+
+    info!("Enqueuing async batch of {} memories", point_count);
+
+    match state.jobs.enqueue(req.points) {
+        Ok(accepted) => Ok((StatusCode::ACCEPTED, Json(accepted))),
+        Err(_) => Err(AppError::Unavailable(
+            "Job queue is saturated, please retry later".to_string(),
+        )),
+    }
+}
+
+/// Get the status of an asynchronous batch job
+#[utoipa::path(
+    get,
+    path = "/jobs/{job_id}",
+    tag = "Memories",
+    params(
+        ("job_id" = String, Path, description = "Job ID returned by POST /memories/batch/async")
+    ),
+    responses(
+        (status = 200, description = "Job status", body = JobStatusResponse),
+        (status = 404, description = "Job not found (never existed, or evicted after TTL)")
+    )
+)]
+pub async fn get_job_status(
+    State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
+    Path(job_id): Path<String>,
+) -> Result<Json<JobStatusResponse>, AppError> {
+    permissions.require(Action::Upsert)?;
+
+    state
+        .jobs
+        .get_status(&job_id)
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("Job '{}' not found", job_id)))
+}
+
+/// Lines buffered before a flush to Qdrant during NDJSON ingestion. Keeps
+/// peak memory bounded regardless of how many lines the request body has,
+/// while still batching round trips like `/memories/batch` does.
+const NDJSON_FLUSH_SIZE: usize = 256;
+
+/// Upper bound on a single NDJSON line's buffered size. `NDJSON_FLUSH_SIZE`
+/// only caps how many *parsed* lines are held before a flush; without this,
+/// one line with no `\n` (a huge payload, or a client that forgets the
+/// trailing newline) would grow the line buffer unbounded for as long as
+/// that line keeps arriving.
+const MAX_NDJSON_LINE_BYTES: usize = 8 * 1024 * 1024;
+
+/// Streaming NDJSON ingestion of memories
+///
+/// **Purpose:** Bulk-load memories without the 100-point ceiling of
+/// `/memories/batch`. Unlike the batch endpoint, the body is never fully
+/// materialized as a parsed array: lines are read off the request body as
+/// they arrive and upserted in flushes of [`NDJSON_FLUSH_SIZE`] via the same
+/// batch path, so peak memory stays bounded regardless of file size — a
+/// single line is also capped at [`MAX_NDJSON_LINE_BYTES`] so one unbroken
+/// line can't grow the buffer without limit.
+///
+/// **Usage:**
+/// - `Content-Type: application/x-ndjson`
+/// - Body: one `UpsertMemoryRequest` JSON object per line, no surrounding array
+/// - Returns success/failure counts plus per-line errors keyed by 1-based line number
+#[utoipa::path(
+    post,
+    path = "/memories/ndjson",
+    tag = "Memories",
+    request_body(content = String, content_type = "application/x-ndjson"),
+    responses(
+        (status = 200, description = "Ingestion completed", body = NdjsonUpsertResponse),
+        (status = 400, description = "Request body could not be read"),
+        (status = 500, description = "Qdrant error")
+    )
+)]
+pub async fn ndjson_upsert_memories(
+    State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
+    body: axum::body::Body,
+) -> Result<Json<NdjsonUpsertResponse>, AppError> {
+    permissions.require(Action::Upsert)?;
+
+    let mut success_count = 0usize;
+    let mut failed_count = 0usize;
+    let mut errors = Vec::new();
+    let mut pending: Vec<(usize, UpsertMemoryRequest)> = Vec::with_capacity(NDJSON_FLUSH_SIZE);
+
+    let mut line_no = 0usize;
+    let mut buf = String::new();
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk
+            .map_err(|e| AppError::InvalidRequest(format!("Failed to read request body: {}", e)))?;
+        buf.push_str(&String::from_utf8_lossy(&chunk));
+
+        while let Some(pos) = buf.find('\n') {
+            let line: String = buf.drain(..=pos).collect();
+            line_no += 1;
+            parse_ndjson_memory_line(line.trim(), line_no, &mut pending, &mut failed_count, &mut errors);
+            if pending.len() >= NDJSON_FLUSH_SIZE {
+                flush_ndjson_memories(&state, &permissions, &mut pending, &mut success_count, &mut failed_count, &mut errors).await;
+            }
+        }
+
+        if buf.len() > MAX_NDJSON_LINE_BYTES {
+            return Err(AppError::InvalidRequest(format!(
+                "NDJSON line {} exceeds the {}-byte limit without a newline",
+                line_no + 1,
+                MAX_NDJSON_LINE_BYTES
+            )));
+        }
+    }
+    line_no += 1;
+    parse_ndjson_memory_line(buf.trim(), line_no, &mut pending, &mut failed_count, &mut errors);
+    if !pending.is_empty() {
+        flush_ndjson_memories(&state, &permissions, &mut pending, &mut success_count, &mut failed_count, &mut errors).await;
+    }
+
+    state.stats.increment_upserts(success_count as u64);
+
+    Ok(Json(NdjsonUpsertResponse {
         success_count,
         failed_count,
         errors,
     }))
 }
 
+fn parse_ndjson_memory_line(
+    line: &str,
+    line_no: usize,
+    pending: &mut Vec<(usize, UpsertMemoryRequest)>,
+    failed_count: &mut usize,
+    errors: &mut Vec<NdjsonLineError>,
+) {
+    if line.is_empty() {
+        return;
+    }
+    match serde_json::from_str::<UpsertMemoryRequest>(line) {
+        Ok(req) => pending.push((line_no, req)),
+        Err(e) => {
+            *failed_count += 1;
+            errors.push(NdjsonLineError {
+                line: line_no,
+                error: e.to_string(),
+            });
+        }
+    }
+}
+
+async fn flush_ndjson_memories(
+    state: &AppState,
+    permissions: &ApiKeyPermissions,
+    pending: &mut Vec<(usize, UpsertMemoryRequest)>,
+    success_count: &mut usize,
+    failed_count: &mut usize,
+    errors: &mut Vec<NdjsonLineError>,
+) {
+    let batch = std::mem::take(pending);
+    let mut lines_by_point: std::collections::HashMap<String, Vec<usize>> = std::collections::HashMap::new();
+    let mut items = Vec::with_capacity(batch.len());
+    for (line_no, req) in batch {
+        if let Err(e) = permissions
+            .check_user_scope(req.payload.user_id)
+            .and_then(|()| permissions.check_namespace_scope(req.namespace.as_deref()))
+        {
+            *failed_count += 1;
+            errors.push(NdjsonLineError {
+                line: line_no,
+                error: e.to_string(),
+            });
+            continue;
+        }
+
+        let vector = match resolve_vector(
+            state.embedder.as_ref(),
+            req.vector,
+            Some(req.payload.value.as_str()),
+            state.config.vector_dimension,
+        )
+        .await
+        {
+            Ok(vector) => vector,
+            Err(e) => {
+                *failed_count += 1;
+                errors.push(NdjsonLineError {
+                    line: line_no,
+                    error: e.to_string(),
+                });
+                continue;
+            }
+        };
+        lines_by_point.entry(req.point_id.clone()).or_default().push(line_no);
+        items.push(crate::qdrant::MemoryUpsertItem {
+            point_id: req.point_id,
+            vector,
+            sparse_vector: req.sparse_vector,
+            payload: req.payload,
+            namespace: req.namespace,
+        });
+    }
+
+    for (point_id, result) in state.qdrant.upsert_memories_batch(items).await {
+        let line_no = lines_by_point
+            .get_mut(&point_id)
+            .filter(|lines| !lines.is_empty())
+            .map(|lines| lines.remove(0))
+            .unwrap_or(0);
+        match result {
+            Ok(_) => *success_count += 1,
+            Err(e) => {
+                *failed_count += 1;
+                errors.push(NdjsonLineError {
+                    line: line_no,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+}
+
 /// Get service info (version, stats, etc.)
 /// Protected endpoint - requires API key
 #[utoipa::path(
@@ -438,7 +1037,9 @@ pub async fn batch_upsert_memories(
 )]
 pub async fn get_service_info(
     State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    permissions.require(Action::Stats)?;
     info!("Getting service info");
 
     // Get Qdrant collection stats
@@ -484,22 +1085,137 @@ pub async fn get_service_info(
 )]
 pub async fn upsert_document(
     State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
     Json(req): Json<UpsertDocumentRequest>,
 ) -> Result<StatusCode, AppError> {
-    // Validate vector dimension
-    if req.vector.len() != 1024 {
-        return Err(AppError::InvalidRequest(format!(
-            "Vector must have exactly 1024 dimensions, got {}",
-            req.vector.len()
-        )));
-    }
+    permissions.require(Action::Upsert)?;
+    permissions.check_user_scope(req.payload.user_id)?;
+    let vector = resolve_vector(
+        state.embedder.as_ref(),
+        req.vector,
+        Some(req.payload.text.as_str()),
+        state.config.vector_dimension,
+    )
+    .await?;
+
+    state
+        .qdrant
+        .upsert_document_with_sparse(&req.point_id, &vector, req.sparse_vector, &req.payload)
+        .await?;
 
-    state.qdrant.upsert_document(&req.point_id, &req.vector, &req.payload).await?;
-    
     Ok(StatusCode::OK)
 }
 
+/// Upload a whole document and let the service split it into chunks
+///
+/// **Purpose:** `/documents`/`/documents/batch` require the caller to have
+/// already split a file into fixed chunks, so re-uploading a lightly edited
+/// document re-chunks (and re-embeds) the whole thing. This endpoint splits
+/// `text` server-side with content-defined chunking (see
+/// [`crate::chunking::fastcdc_chunks`]) and stores each chunk via
+/// [`crate::qdrant::QdrantService::upsert_document_deduped`], so most chunk
+/// boundaries - and the points behind them - stay put across an edit, and
+/// identical chunk content shared by two files is stored once. Use
+/// `/documents/upload/delete` rather than `/documents/delete-by-file` to
+/// remove a document uploaded this way, so a chunk it shares with another
+/// file isn't destroyed out from under it.
+///
+/// **Usage:**
+/// - `text`: the full document body; chunking and embedding both happen
+///   server-side, so a server-side embedder must be configured (see
+///   `/capabilities`)
+/// - Returns the chunk point IDs in document order
+#[utoipa::path(
+    post,
+    path = "/documents/upload",
+    request_body = UploadDocumentRequest,
+    responses(
+        (status = 200, description = "Document chunked and upserted", body = UploadDocumentResponse),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Unauthorized"),
+    ),
+    tag = "documents"
+)]
+pub async fn upload_document(
+    State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
+    Json(req): Json<UploadDocumentRequest>,
+) -> Result<Json<UploadDocumentResponse>, AppError> {
+    permissions.require(Action::Upsert)?;
+    permissions.check_user_scope(req.user_id)?;
+
+    let chunks = crate::chunking::fastcdc_chunks(req.text.as_bytes(), &crate::chunking::FastCdcConfig::for_documents());
+    let mut point_ids = Vec::with_capacity(chunks.len());
+
+    for (index, chunk) in chunks.iter().enumerate() {
+        // `fastcdc_chunks` cuts on raw byte offsets with no UTF-8 awareness,
+        // so a cut can land mid-codepoint; floor both ends to the nearest
+        // char boundary before slicing `req.text` so a multi-byte character
+        // never panics the request. Adjacent chunks stay contiguous because
+        // the shared cut point floors to the same value on both sides.
+        let start = floor_char_boundary(&req.text, chunk.start);
+        let end = floor_char_boundary(&req.text, chunk.end);
+        let chunk_text = &req.text[start..end];
+
+        let vector = resolve_vector(
+            state.embedder.as_ref(),
+            None,
+            Some(chunk_text),
+            state.config.vector_dimension,
+        )
+        .await?;
+
+        let payload = DocumentPayload {
+            user_id: req.user_id,
+            file_id: req.file_id,
+            group_key: req.group_key.clone(),
+            file_type: req.file_type,
+            chunk_index: index as i32,
+            start_line: line_number_at(&req.text, start),
+            end_line: line_number_at(&req.text, end),
+            text: chunk_text.to_string(),
+            created: req.created,
+            ref_files: Vec::new(),
+        };
+
+        // Deduplicated on content rather than keyed by `file_id`, so a
+        // chunk shared verbatim by another file (or an earlier version of
+        // this one) is stored once and just gains this file as another
+        // reference, instead of inserting a duplicate point.
+        let point_id = state
+            .qdrant
+            .upsert_document_deduped(&vector, None, payload)
+            .await?;
+        point_ids.push(point_id);
+    }
+
+    Ok(Json(UploadDocumentResponse { point_ids }))
+}
+
+/// 1-based line number containing byte offset `pos` of `text`, for
+/// populating `DocumentPayload::start_line`/`end_line` from a chunk's byte
+/// range in [`upload_document`].
+fn line_number_at(text: &str, pos: usize) -> i32 {
+    text.as_bytes()[..pos].iter().filter(|&&b| b == b'\n').count() as i32 + 1
+}
+
+/// Nearest char boundary at or before `pos`, for snapping a byte offset
+/// from [`crate::chunking::fastcdc_chunks`] (which has no UTF-8 awareness)
+/// before it's used to slice `text`, in [`upload_document`].
+fn floor_char_boundary(text: &str, mut pos: usize) -> usize {
+    if pos >= text.len() {
+        return text.len();
+    }
+    while pos > 0 && !text.is_char_boundary(pos) {
+        pos -= 1;
+    }
+    pos
+}
+
 /// Batch upsert document chunks
+///
+/// **Binary wire format:** see [`batch_upsert_memories`] — accepts and can
+/// return `postcard` instead of JSON, negotiated the same way.
 #[utoipa::path(
     post,
     path = "/documents/batch",
@@ -513,8 +1229,11 @@ pub async fn upsert_document(
 )]
 pub async fn batch_upsert_documents(
     State(state): State<AppState>,
-    Json(req): Json<BatchUpsertDocumentsRequest>,
-) -> Result<Json<BatchUpsertResponse>, AppError> {
+    Extension(permissions): Extension<ApiKeyPermissions>,
+    Accepts(format): Accepts,
+    Wire(req): Wire<BatchUpsertDocumentsRequest>,
+) -> Result<Wired<BatchUpsertResponse>, AppError> {
+    permissions.require(Action::Upsert)?;
     if req.documents.len() > 100 {
         return Err(AppError::InvalidRequest("Maximum 100 documents per batch".into()));
     }
@@ -523,29 +1242,220 @@ pub async fn batch_upsert_documents(
     let mut failed_count = 0;
     let mut errors = Vec::new();
 
-    for doc in &req.documents {
-        if doc.vector.len() != 1024 {
+    let mut items = Vec::with_capacity(req.documents.len());
+    for doc in req.documents {
+        if let Err(e) = permissions.check_user_scope(doc.payload.user_id) {
             failed_count += 1;
-            errors.push(format!("Document {}: invalid vector dimension", doc.point_id));
+            errors.push(format!("Document {}: {}", doc.point_id, e));
             continue;
         }
 
-        match state.qdrant.upsert_document(&doc.point_id, &doc.vector, &doc.payload).await {
-            Ok(_) => success_count += 1,
+        let vector = match resolve_vector(
+            state.embedder.as_ref(),
+            doc.vector,
+            Some(doc.payload.text.as_str()),
+            state.config.vector_dimension,
+        )
+        .await
+        {
+            Ok(vector) => vector,
             Err(e) => {
                 failed_count += 1;
                 errors.push(format!("Document {}: {}", doc.point_id, e));
+                continue;
+            }
+        };
+        items.push(crate::qdrant::DocumentUpsertItem {
+            point_id: doc.point_id,
+            vector,
+            sparse_vector: doc.sparse_vector,
+            payload: doc.payload,
+        });
+    }
+
+    for (point_id, result) in state.qdrant.upsert_documents_batch(items).await {
+        match result {
+            Ok(_) => success_count += 1,
+            Err(e) => {
+                failed_count += 1;
+                errors.push(format!("Document {}: {}", point_id, e));
             }
         }
     }
 
-    Ok(Json(BatchUpsertResponse {
+    Ok(Wired::new(
+        format,
+        BatchUpsertResponse {
+            success_count,
+            failed_count,
+            errors,
+        },
+    ))
+}
+
+/// Streaming NDJSON ingestion of document chunks
+///
+/// **Purpose:** Bulk-load document chunks without the 100-document ceiling
+/// of `/documents/batch`. Behaves like [`ndjson_upsert_memories`]: lines are
+/// read off the request body as they arrive and upserted in flushes of
+/// [`NDJSON_FLUSH_SIZE`] via the same batch path, keeping peak memory
+/// bounded regardless of file size, including the per-line cap at
+/// [`MAX_NDJSON_LINE_BYTES`].
+///
+/// **Usage:**
+/// - `Content-Type: application/x-ndjson`
+/// - Body: one `UpsertDocumentRequest` JSON object per line, no surrounding array
+/// - Returns success/failure counts plus per-line errors keyed by 1-based line number
+#[utoipa::path(
+    post,
+    path = "/documents/ndjson",
+    request_body(content = String, content_type = "application/x-ndjson"),
+    responses(
+        (status = 200, description = "Ingestion completed", body = NdjsonUpsertResponse),
+        (status = 400, description = "Request body could not be read"),
+        (status = 401, description = "Unauthorized"),
+    ),
+    tag = "documents"
+)]
+pub async fn ndjson_upsert_documents(
+    State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
+    body: axum::body::Body,
+) -> Result<Json<NdjsonUpsertResponse>, AppError> {
+    permissions.require(Action::Upsert)?;
+
+    let mut success_count = 0usize;
+    let mut failed_count = 0usize;
+    let mut errors = Vec::new();
+    let mut pending: Vec<(usize, UpsertDocumentRequest)> = Vec::with_capacity(NDJSON_FLUSH_SIZE);
+
+    let mut line_no = 0usize;
+    let mut buf = String::new();
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk
+            .map_err(|e| AppError::InvalidRequest(format!("Failed to read request body: {}", e)))?;
+        buf.push_str(&String::from_utf8_lossy(&chunk));
+
+        while let Some(pos) = buf.find('\n') {
+            let line: String = buf.drain(..=pos).collect();
+            line_no += 1;
+            parse_ndjson_document_line(line.trim(), line_no, &mut pending, &mut failed_count, &mut errors);
+            if pending.len() >= NDJSON_FLUSH_SIZE {
+                flush_ndjson_documents(&state, &permissions, &mut pending, &mut success_count, &mut failed_count, &mut errors).await;
+            }
+        }
+
+        if buf.len() > MAX_NDJSON_LINE_BYTES {
+            return Err(AppError::InvalidRequest(format!(
+                "NDJSON line {} exceeds the {}-byte limit without a newline",
+                line_no + 1,
+                MAX_NDJSON_LINE_BYTES
+            )));
+        }
+    }
+    line_no += 1;
+    parse_ndjson_document_line(buf.trim(), line_no, &mut pending, &mut failed_count, &mut errors);
+    if !pending.is_empty() {
+        flush_ndjson_documents(&state, &permissions, &mut pending, &mut success_count, &mut failed_count, &mut errors).await;
+    }
+
+    Ok(Json(NdjsonUpsertResponse {
         success_count,
         failed_count,
         errors,
     }))
 }
 
+fn parse_ndjson_document_line(
+    line: &str,
+    line_no: usize,
+    pending: &mut Vec<(usize, UpsertDocumentRequest)>,
+    failed_count: &mut usize,
+    errors: &mut Vec<NdjsonLineError>,
+) {
+    if line.is_empty() {
+        return;
+    }
+    match serde_json::from_str::<UpsertDocumentRequest>(line) {
+        Ok(req) => pending.push((line_no, req)),
+        Err(e) => {
+            *failed_count += 1;
+            errors.push(NdjsonLineError {
+                line: line_no,
+                error: e.to_string(),
+            });
+        }
+    }
+}
+
+async fn flush_ndjson_documents(
+    state: &AppState,
+    permissions: &ApiKeyPermissions,
+    pending: &mut Vec<(usize, UpsertDocumentRequest)>,
+    success_count: &mut usize,
+    failed_count: &mut usize,
+    errors: &mut Vec<NdjsonLineError>,
+) {
+    let batch = std::mem::take(pending);
+    let mut lines_by_point: std::collections::HashMap<String, Vec<usize>> = std::collections::HashMap::new();
+    let mut items = Vec::with_capacity(batch.len());
+    for (line_no, req) in batch {
+        if let Err(e) = permissions.check_user_scope(req.payload.user_id) {
+            *failed_count += 1;
+            errors.push(NdjsonLineError {
+                line: line_no,
+                error: e.to_string(),
+            });
+            continue;
+        }
+
+        let vector = match resolve_vector(
+            state.embedder.as_ref(),
+            req.vector,
+            Some(req.payload.text.as_str()),
+            state.config.vector_dimension,
+        )
+        .await
+        {
+            Ok(vector) => vector,
+            Err(e) => {
+                *failed_count += 1;
+                errors.push(NdjsonLineError {
+                    line: line_no,
+                    error: e.to_string(),
+                });
+                continue;
+            }
+        };
+        lines_by_point.entry(req.point_id.clone()).or_default().push(line_no);
+        items.push(crate::qdrant::DocumentUpsertItem {
+            point_id: req.point_id,
+            vector,
+            sparse_vector: req.sparse_vector,
+            payload: req.payload,
+        });
+    }
+
+    for (point_id, result) in state.qdrant.upsert_documents_batch(items).await {
+        let line_no = lines_by_point
+            .get_mut(&point_id)
+            .filter(|lines| !lines.is_empty())
+            .map(|lines| lines.remove(0))
+            .unwrap_or(0);
+        match result {
+            Ok(_) => *success_count += 1,
+            Err(e) => {
+                *failed_count += 1;
+                errors.push(NdjsonLineError {
+                    line: line_no,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+}
+
 /// Search documents by vector similarity
 #[utoipa::path(
     post,
@@ -560,22 +1470,64 @@ pub async fn batch_upsert_documents(
 )]
 pub async fn search_documents(
     State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
     Json(req): Json<SearchDocumentsRequest>,
 ) -> Result<Json<Vec<DocumentSearchResult>>, AppError> {
-    if req.vector.len() != 1024 {
-        return Err(AppError::InvalidRequest(format!(
-            "Vector must have exactly 1024 dimensions, got {}",
-            req.vector.len()
-        )));
-    }
+    permissions.require(Action::Search)?;
+    permissions.check_user_scope(req.user_id)?;
+    let query_vector = resolve_vector(
+        state.embedder.as_ref(),
+        req.vector,
+        req.query_text.as_deref(),
+        state.config.vector_dimension,
+    )
+    .await?;
 
-    let results = state.qdrant.search_documents(
-        &req.vector,
-        req.user_id,
-        req.group_key.as_deref(),
-        req.limit,
-        req.min_score,
-    ).await?;
+    let results = if let Some(query_text) = req.query_text.as_deref() {
+        state
+            .qdrant
+            .search_documents_semantic_lexical(
+                &query_vector,
+                query_text,
+                req.semantic_ratio,
+                req.user_id,
+                req.group_key.as_deref(),
+                req.limit,
+                req.min_score,
+                req.filter.as_deref(),
+            )
+            .await?
+    } else {
+        match req.query_sparse_vector {
+            Some(sparse) => {
+                state
+                    .qdrant
+                    .search_documents_hybrid(
+                        &query_vector,
+                        sparse,
+                        req.user_id,
+                        req.group_key.as_deref(),
+                        req.limit,
+                        req.min_score,
+                        req.filter.as_deref(),
+                    )
+                    .await?
+            }
+            None => {
+                state
+                    .qdrant
+                    .search_documents(
+                        &query_vector,
+                        req.user_id,
+                        req.group_key.as_deref(),
+                        req.limit,
+                        req.min_score,
+                        req.filter.as_deref(),
+                    )
+                    .await?
+            }
+        }
+    };
 
     Ok(Json(results))
 }
@@ -596,11 +1548,14 @@ pub async fn search_documents(
 )]
 pub async fn get_document(
     State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
     Path(point_id): Path<String>,
 ) -> Result<Json<DocumentSearchResult>, AppError> {
+    permissions.require(Action::Search)?;
     let doc = state.qdrant.get_document(&point_id).await?
         .ok_or_else(|| AppError::NotFound("Document not found".into()))?;
-    
+    permissions.check_user_scope(doc.payload.user_id)?;
+
     Ok(Json(doc))
 }
 
@@ -620,8 +1575,13 @@ pub async fn get_document(
 )]
 pub async fn delete_document(
     State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
     Path(point_id): Path<String>,
 ) -> Result<StatusCode, AppError> {
+    permissions.require(Action::Delete)?;
+    if let Some(doc) = state.qdrant.get_document(&point_id).await? {
+        permissions.check_user_scope(doc.payload.user_id)?;
+    }
     state.qdrant.delete_document(&point_id).await?;
     Ok(StatusCode::OK)
 }
@@ -640,12 +1600,47 @@ pub async fn delete_document(
 )]
 pub async fn delete_by_file(
     State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
     Json(req): Json<DeleteByFileRequest>,
 ) -> Result<Json<u64>, AppError> {
+    permissions.require(Action::Delete)?;
+    permissions.check_user_scope(req.user_id)?;
     let deleted = state.qdrant.delete_documents_by_file(req.user_id, req.file_id).await?;
     Ok(Json(deleted))
 }
 
+/// Drop a file's references to chunks uploaded via `/documents/upload`
+///
+/// **Purpose:** The counterpart to `/documents/upload`: unlike
+/// `/documents/delete-by-file`, which blindly deletes every point matching
+/// `file_id` and would destroy a chunk another file still shares, this
+/// drops only `file_id`'s reference to each chunk it touched, deleting a
+/// chunk once no file references it any more.
+#[utoipa::path(
+    post,
+    path = "/documents/upload/delete",
+    request_body = DeleteByFileRequest,
+    responses(
+        (status = 200, description = "References removed", body = u64),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Unauthorized"),
+    ),
+    tag = "documents"
+)]
+pub async fn delete_uploaded_document(
+    State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
+    Json(req): Json<DeleteByFileRequest>,
+) -> Result<Json<u64>, AppError> {
+    permissions.require(Action::Delete)?;
+    permissions.check_user_scope(req.user_id)?;
+    let removed = state
+        .qdrant
+        .remove_document_refs_for_file(req.user_id, req.file_id)
+        .await?;
+    Ok(Json(removed))
+}
+
 /// Delete all documents for a group key
 #[utoipa::path(
     post,
@@ -660,8 +1655,11 @@ pub async fn delete_by_file(
 )]
 pub async fn delete_by_group_key(
     State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
     Json(req): Json<DeleteByGroupKeyRequest>,
 ) -> Result<Json<u64>, AppError> {
+    permissions.require(Action::Delete)?;
+    permissions.check_user_scope(req.user_id)?;
     let deleted = state.qdrant.delete_documents_by_group_key(req.user_id, &req.group_key).await?;
     Ok(Json(deleted))
 }
@@ -681,8 +1679,11 @@ pub async fn delete_by_group_key(
 )]
 pub async fn delete_all_for_user(
     State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
     Path(user_id): Path<i64>,
 ) -> Result<Json<u64>, AppError> {
+    permissions.require(Action::Delete)?;
+    permissions.check_user_scope(user_id)?;
     let deleted = state.qdrant.delete_all_documents_for_user(user_id).await?;
     Ok(Json(deleted))
 }
@@ -701,8 +1702,11 @@ pub async fn delete_all_for_user(
 )]
 pub async fn update_group_key(
     State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
     Json(req): Json<UpdateGroupKeyRequest>,
 ) -> Result<Json<u64>, AppError> {
+    permissions.require(Action::Upsert)?;
+    permissions.check_user_scope(req.user_id)?;
     let updated = state.qdrant.update_document_group_key(
         req.user_id,
         req.file_id,
@@ -726,29 +1730,70 @@ pub async fn update_group_key(
 )]
 pub async fn get_document_stats(
     State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
     Path(user_id): Path<i64>,
 ) -> Result<Json<DocumentStatsResponse>, AppError> {
-    let stats = state.qdrant.get_document_stats(user_id).await?;
+    permissions.require(Action::Stats)?;
+    permissions.check_user_scope(user_id)?;
+    let shards = state.config.document_stats_shards;
+    let stats = if shards > 1 {
+        state.qdrant.get_document_stats_parallel(user_id, shards).await?
+    } else {
+        state.qdrant.get_document_stats(user_id).await?
+    };
     Ok(Json(stats))
 }
 
-/// Get distinct group keys for a user
+/// Get distinct group keys for a user, one page at a time
+///
+/// **Usage:** Set `limit` (default 100, max 1,000) and `offset`; keep
+/// requesting with the previous response's `next_offset` until it is `null`.
 #[utoipa::path(
     get,
     path = "/documents/groups/{user_id}",
     params(
-        ("user_id" = i64, Path, description = "User ID")
+        ("user_id" = i64, Path, description = "User ID"),
+        ("limit" = Option<u64>, Query, description = "Page size, default 100, max 1000"),
+        ("offset" = Option<u64>, Query, description = "Number of group keys to skip")
     ),
     responses(
-        (status = 200, description = "Group keys", body = Vec<String>),
+        (status = 200, description = "Page of group keys", body = GroupKeysResponse),
+        (status = 400, description = "Invalid request"),
         (status = 401, description = "Unauthorized"),
     ),
     tag = "documents"
 )]
 pub async fn get_group_keys(
     State(state): State<AppState>,
+    Extension(permissions): Extension<ApiKeyPermissions>,
     Path(user_id): Path<i64>,
-) -> Result<Json<Vec<String>>, AppError> {
-    let groups = state.qdrant.get_document_group_keys(user_id).await?;
-    Ok(Json(groups))
+    Query(query): Query<GroupKeysQuery>,
+) -> Result<Json<GroupKeysResponse>, AppError> {
+    permissions.require(Action::Stats)?;
+    permissions.check_user_scope(user_id)?;
+    if query.limit < 1 || query.limit > MAX_SCROLL_LIMIT {
+        return Err(AppError::InvalidRequest(format!(
+            "limit must be between 1 and {}, got {}",
+            MAX_SCROLL_LIMIT, query.limit
+        )));
+    }
+
+    let mut groups = state.qdrant.get_document_group_keys(user_id).await?;
+    groups.sort_unstable();
+    let total = groups.len() as u64;
+
+    let offset = query.offset as usize;
+    let limit = query.limit as usize;
+    let page: Vec<String> = groups.into_iter().skip(offset).take(limit).collect();
+    let next_offset = if offset + page.len() < total as usize {
+        Some(query.offset + page.len() as u64)
+    } else {
+        None
+    };
+
+    Ok(Json(GroupKeysResponse {
+        groups: page,
+        next_offset,
+        total,
+    }))
 }
@@ -0,0 +1,322 @@
+//! Background job queue for large batch upserts.
+//!
+//! `batch_upsert_memories` processes its payload synchronously within the
+//! request, so very large imports risk blocking the connection until they
+//! time out. `JobQueue` gives callers an async alternative: enqueue a batch
+//! into a bounded channel drained by a fixed pool of worker tasks started at
+//! startup, get back a job ID immediately, and poll `GET /jobs/:job_id` for
+//! progress. Job state lives in an in-memory map with TTL-based eviction, so
+//! it does not survive a restart.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::{mpsc, Mutex as AsyncMutex};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::embedding::{resolve_vector, Embedder};
+use crate::models::{BatchError, UpsertMemoryRequest};
+use crate::qdrant::QdrantService;
+use crate::stats::StatsTracker;
+
+/// How long a finished job's status is kept around before the janitor evicts it.
+const JOB_TTL: Duration = Duration::from_secs(3600);
+/// Bounded channel capacity; `enqueue` fails once this many jobs are pending.
+const QUEUE_CAPACITY: usize = 1000;
+/// Fixed worker pool size, spawned once at startup.
+const WORKER_COUNT: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+/// Status of a single enqueued batch, returned by `GET /jobs/:job_id`.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct JobStatusResponse {
+    pub job_id: String,
+    pub status: JobStatus,
+    pub processed: usize,
+    pub failed: usize,
+    pub total: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<BatchError>,
+}
+
+/// Returned immediately by `POST /memories/batch/async` once a batch is enqueued.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct JobAcceptedResponse {
+    pub job_id: String,
+    pub status: JobStatus,
+    pub total: usize,
+}
+
+struct JobRecord {
+    status: JobStatus,
+    processed: usize,
+    failed: usize,
+    total: usize,
+    errors: Vec<BatchError>,
+    last_touched: Instant,
+}
+
+struct QueuedBatch {
+    job_id: String,
+    points: Vec<UpsertMemoryRequest>,
+}
+
+/// Handle shared via `AppState`: enqueues batches and reports on their status.
+/// Cloning shares the same queue, job map, and gauges.
+#[derive(Clone)]
+pub struct JobQueue {
+    sender: mpsc::Sender<QueuedBatch>,
+    jobs: Arc<RwLock<HashMap<String, JobRecord>>>,
+    queue_depth: Arc<AtomicUsize>,
+    active_workers: Arc<AtomicUsize>,
+}
+
+impl JobQueue {
+    /// Spawns the worker pool and TTL janitor, returning the queue handle.
+    ///
+    /// `embedder`/`vector_dimension` mirror `AppState`'s, so a queued point
+    /// missing `vector` gets embedded from `payload.value` the same way the
+    /// synchronous `/memories/batch` path does.
+    pub fn spawn(
+        qdrant: Arc<QdrantService>,
+        stats: StatsTracker,
+        embedder: Option<Arc<dyn Embedder>>,
+        vector_dimension: u64,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+        let jobs: Arc<RwLock<HashMap<String, JobRecord>>> = Arc::new(RwLock::new(HashMap::new()));
+        let queue_depth = Arc::new(AtomicUsize::new(0));
+        let active_workers = Arc::new(AtomicUsize::new(0));
+        let receiver = Arc::new(AsyncMutex::new(receiver));
+
+        for _ in 0..WORKER_COUNT {
+            let receiver = receiver.clone();
+            let jobs = jobs.clone();
+            let qdrant = qdrant.clone();
+            let stats = stats.clone();
+            let queue_depth = queue_depth.clone();
+            let active_workers = active_workers.clone();
+            let embedder = embedder.clone();
+
+            tokio::spawn(async move {
+                loop {
+                    let next = receiver.lock().await.recv().await;
+                    let Some(batch) = next else {
+                        break;
+                    };
+                    queue_depth.fetch_sub(1, Ordering::Relaxed);
+                    active_workers.fetch_add(1, Ordering::Relaxed);
+
+                    run_batch(batch, &jobs, &qdrant, &stats, embedder.as_ref(), vector_dimension).await;
+
+                    active_workers.fetch_sub(1, Ordering::Relaxed);
+                }
+            });
+        }
+
+        spawn_janitor(jobs.clone());
+
+        Self {
+            sender,
+            jobs,
+            queue_depth,
+            active_workers,
+        }
+    }
+
+    /// Enqueues a batch and returns its job ID. If the bounded channel is
+    /// full, the batch is handed back unmodified so the caller can surface a
+    /// "try again later" error without losing it.
+    pub fn enqueue(&self, points: Vec<UpsertMemoryRequest>) -> Result<JobAcceptedResponse, Vec<UpsertMemoryRequest>> {
+        let job_id = Uuid::new_v4().to_string();
+        let total = points.len();
+
+        self.jobs.write().unwrap().insert(
+            job_id.clone(),
+            JobRecord {
+                status: JobStatus::Queued,
+                processed: 0,
+                failed: 0,
+                total,
+                errors: Vec::new(),
+                last_touched: Instant::now(),
+            },
+        );
+
+        // Counted before the send so a worker that receives the batch at once
+        // never decrements the gauge below zero.
+        self.queue_depth.fetch_add(1, Ordering::Relaxed);
+        match self.sender.try_send(QueuedBatch {
+            job_id: job_id.clone(),
+            points,
+        }) {
+            Ok(()) => Ok(JobAcceptedResponse {
+                job_id,
+                status: JobStatus::Queued,
+                total,
+            }),
+            Err(mpsc::error::TrySendError::Full(batch)) => {
+                self.queue_depth.fetch_sub(1, Ordering::Relaxed);
+                self.jobs.write().unwrap().remove(&job_id);
+                Err(batch.points)
+            }
+            Err(mpsc::error::TrySendError::Closed(batch)) => {
+                self.queue_depth.fetch_sub(1, Ordering::Relaxed);
+                self.jobs.write().unwrap().remove(&job_id);
+                Err(batch.points)
+            }
+        }
+    }
+
+    pub fn get_status(&self, job_id: &str) -> Option<JobStatusResponse> {
+        self.jobs.read().unwrap().get(job_id).map(|job| JobStatusResponse {
+            job_id: job_id.to_string(),
+            status: job.status,
+            processed: job.processed,
+            failed: job.failed,
+            total: job.total,
+            errors: job.errors.clone(),
+        })
+    }
+
+    /// Number of batches currently queued (not yet picked up by a worker).
+    pub fn queue_depth(&self) -> usize {
+        self.queue_depth.load(Ordering::Relaxed)
+    }
+
+    /// Number of workers currently processing a batch.
+    pub fn active_workers(&self) -> usize {
+        self.active_workers.load(Ordering::Relaxed)
+    }
+
+    /// How saturated the bounded channel is, in `[0.0, 1.0]`.
+    pub fn saturation(&self) -> f64 {
+        self.queue_depth() as f64 / QUEUE_CAPACITY as f64
+    }
+
+    /// Bounded channel capacity (fixed at compile time).
+    pub fn capacity(&self) -> usize {
+        QUEUE_CAPACITY
+    }
+}
+
+async fn run_batch(
+    batch: QueuedBatch,
+    jobs: &Arc<RwLock<HashMap<String, JobRecord>>>,
+    qdrant: &Arc<QdrantService>,
+    stats: &StatsTracker,
+    embedder: Option<&Arc<dyn Embedder>>,
+    vector_dimension: u64,
+) {
+    if let Some(record) = jobs.write().unwrap().get_mut(&batch.job_id) {
+        record.status = JobStatus::Running;
+        record.last_touched = Instant::now();
+    }
+
+    let mut processed = 0usize;
+    let mut failed = 0usize;
+    let mut errors = Vec::new();
+
+    let mut items = Vec::with_capacity(batch.points.len());
+    for point in batch.points {
+        match resolve_vector(
+            embedder,
+            point.vector,
+            Some(point.payload.value.as_str()),
+            vector_dimension,
+        )
+        .await
+        {
+            Ok(vector) => items.push(crate::qdrant::MemoryUpsertItem {
+                point_id: point.point_id,
+                vector,
+                sparse_vector: point.sparse_vector,
+                payload: point.payload,
+                namespace: point.namespace,
+            }),
+            Err(e) => {
+                failed += 1;
+                errors.push(BatchError {
+                    point_id: point.point_id,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    for (point_id, result) in qdrant.upsert_memories_batch(items).await {
+        match result {
+            Ok(_) => processed += 1,
+            Err(e) => {
+                failed += 1;
+                errors.push(BatchError {
+                    point_id,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    stats.increment_upserts(processed as u64);
+
+    if let Some(record) = jobs.write().unwrap().get_mut(&batch.job_id) {
+        record.status = if failed > 0 && processed == 0 {
+            JobStatus::Failed
+        } else {
+            JobStatus::Completed
+        };
+        record.processed = processed;
+        record.failed = failed;
+        record.errors = errors;
+        record.last_touched = Instant::now();
+    }
+}
+
+/// Periodically evicts job records older than [`JOB_TTL`] so the map doesn't
+/// grow unbounded across a long-running process.
+fn spawn_janitor(jobs: Arc<RwLock<HashMap<String, JobRecord>>>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            jobs.write()
+                .unwrap()
+                .retain(|_, job| job.last_touched.elapsed() < JOB_TTL);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_status_serializes_snake_case() {
+        let json = serde_json::to_string(&JobStatus::Running).unwrap();
+        assert_eq!(json, "\"running\"");
+    }
+
+    #[test]
+    fn test_job_accepted_response_shape() {
+        let accepted = JobAcceptedResponse {
+            job_id: "abc".to_string(),
+            status: JobStatus::Queued,
+            total: 3,
+        };
+        let json = serde_json::to_string(&accepted).unwrap();
+        assert!(json.contains("\"job_id\":\"abc\""));
+        assert!(json.contains("\"total\":3"));
+    }
+}
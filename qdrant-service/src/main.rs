@@ -12,19 +12,30 @@ use utoipa_swagger_ui::SwaggerUi;
 
 mod auth;
 mod alerts;
+mod aggregation;
+mod chunking;
 mod config;
+mod crypto;
+mod embedding;
+#[cfg(feature = "native_onnx")]
+mod embedding_onnx;
 mod error;
+mod filter;
 mod handlers;
+mod jobs;
 mod metrics;
 mod models;
+mod panic_hook;
 mod qdrant;
 mod request_id;
 mod stats;
+mod wire;
 
 use auth::{auth_middleware, AuthState};
 use alerts::WebhookAlerts;
 use config::Config;
 use error::AppError;
+use jobs::JobQueue;
 use metrics::MetricsState;
 use qdrant::QdrantService;
 use stats::StatsTracker;
@@ -41,24 +52,36 @@ use stats::StatsTracker;
         - ‚úÖ Semantic search with cosine similarity\n\
         - ‚úÖ Batch operations (up to 100 points)\n\
         - ‚úÖ User-scoped and category filtering\n\
-        - ‚úÖ Prometheus metrics + Generic webhook alerts\n\
+        - ‚úÖ Prometheus metrics + pluggable alert sinks (webhook, Telegram)\n\
         - ‚ö†Ô∏è Embedding removed - backend must send pre-computed vectors\n\n\
-        **Authentication:** Protected endpoints require `X-API-Key` header.\n\n\
+        **Authentication:** Protected endpoints require an `X-API-Key` (or `Authorization: Bearer`) header. \
+        Keys are scoped to specific actions (`search`, `upsert`, `delete`, `stats`, `admin`) and may expire; \
+        manage them via the `/keys` endpoints with an admin-scoped key.\n\n\
         **Performance:** 2-5ms search for 10k points, ~50ms for 100k points."
     ),
     paths(
         handlers::get_capabilities,
         handlers::upsert_memory,
         handlers::get_memory,
+        handlers::get_memories_batch,
         handlers::delete_memory,
         handlers::search_memories,
         handlers::get_collection_info,
         handlers::scroll_memories,
+        handlers::delete_memories_by_category,
+        handlers::delete_memories_by_filter,
+        handlers::delete_all_memories_for_user,
         handlers::batch_upsert_memories,
+        handlers::batch_upsert_memories_async,
+        handlers::ndjson_upsert_memories,
+        handlers::get_job_status,
         handlers::get_service_info,
         // Document endpoints
         handlers::upsert_document,
+        handlers::upload_document,
+        handlers::delete_uploaded_document,
         handlers::batch_upsert_documents,
+        handlers::ndjson_upsert_documents,
         handlers::search_documents,
         handlers::get_document,
         handlers::delete_document,
@@ -68,8 +91,15 @@ use stats::StatsTracker;
         handlers::update_group_key,
         handlers::get_document_stats,
         handlers::get_group_keys,
+        // Admin endpoints
+        auth::create_key,
+        auth::list_keys,
+        auth::delete_key,
     ),
     components(schemas(
+        auth::Action,
+        auth::ApiKey,
+        auth::CreateKeyRequest,
         models::ServiceCapabilities,
         models::EmbeddingCapabilities,
         models::MemoryPayload,
@@ -81,12 +111,23 @@ use stats::StatsTracker;
         models::SearchMemoriesResponse,
         models::ScrollMemoriesRequest,
         models::ScrollMemoriesResponse,
+        models::GetMemoriesBatchRequest,
+        models::GetMemoriesBatchResponse,
+        models::DeleteMemoriesByCategoryRequest,
+        models::DeleteMemoriesByFilterRequest,
         models::CollectionInfo,
         models::BatchOperationResponse,
         models::BatchError,
+        models::NdjsonUpsertResponse,
+        models::NdjsonLineError,
+        jobs::JobStatus,
+        jobs::JobAcceptedResponse,
+        jobs::JobStatusResponse,
         // Document schemas
         models::DocumentPayload,
         models::UpsertDocumentRequest,
+        models::UploadDocumentRequest,
+        models::UploadDocumentResponse,
         models::BatchUpsertDocumentsRequest,
         models::BatchUpsertResponse,
         models::SearchDocumentsRequest,
@@ -95,11 +136,14 @@ use stats::StatsTracker;
         models::DeleteByGroupKeyRequest,
         models::UpdateGroupKeyRequest,
         models::DocumentStatsResponse,
+        models::GroupKeysQuery,
+        models::GroupKeysResponse,
     )),
     tags(
         (name = "Service Info", description = "Service capabilities, version, and statistics"),
         (name = "Memories", description = "CRUD operations for memory storage and search"),
-        (name = "documents", description = "CRUD operations for document chunk storage and search")
+        (name = "documents", description = "CRUD operations for document chunk storage and search"),
+        (name = "Admin", description = "API key management (requires an admin-scoped key)")
     )
 )]
 struct ApiDoc;
@@ -111,22 +155,19 @@ pub struct AppState {
     metrics: MetricsState,
     alerts: WebhookAlerts,
     stats: StatsTracker,
+    auth: Arc<AuthState>,
+    jobs: JobQueue,
+    embedder: Option<Arc<dyn embedding::Embedder>>,
+    prometheus_handle: metrics_exporter_prometheus::PrometheusHandle,
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "synaplan_qdrant_service=info,tower_http=info".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-
-    // Load configuration
+    // Load configuration (before tracing init, since OTEL export is driven by env)
     dotenvy::dotenv().ok();
     let config = Config::from_env()?;
+
+    init_tracing(&config);
     info!("Configuration loaded: {:?}", config);
     if config.tls_cert_path.is_some() || config.tls_key_path.is_some() {
         info!(
@@ -149,10 +190,27 @@ async fn main() -> anyhow::Result<()> {
     // Initialize stats tracker
     let stats = StatsTracker::new();
 
-    // Initialize webhook alerts (filter out empty strings)
-    let alerts = WebhookAlerts::new(
-        config.webhook_url.clone().filter(|url| !url.is_empty())
-    );
+    // Initialize alert sinks (filter out empty strings; any combination may be configured)
+    let mut alert_sinks: Vec<Arc<dyn alerts::AlertSink>> = Vec::new();
+    if let Some(webhook_url) = config.discord_webhook_url.clone().filter(|url| !url.is_empty()) {
+        let provider_override = config
+            .webhook_provider
+            .as_deref()
+            .and_then(alerts::WebhookProvider::parse);
+        alert_sinks.push(Arc::new(alerts::WebhookSink::with_provider(
+            webhook_url,
+            provider_override,
+            config.telegram_chat_id.clone(),
+        )));
+    }
+    if let (Some(bot_token), Some(chat_id)) = (
+        config.telegram_bot_token.clone().filter(|t| !t.is_empty()),
+        config.telegram_chat_id.clone().filter(|c| !c.is_empty()),
+    ) {
+        alert_sinks.push(Arc::new(alerts::TelegramSink::new(bot_token, chat_id)));
+    }
+    let alerts = WebhookAlerts::new(alert_sinks);
+    panic_hook::install(alerts.clone());
 
     // Initialize Qdrant service
     let qdrant = match QdrantService::new(&config).await {
@@ -166,6 +224,28 @@ async fn main() -> anyhow::Result<()> {
     };
     info!("Connected to Qdrant at {}", config.qdrant_url);
 
+    // Build the configured embedder (validating required fields for its
+    // backend up front) and, if one is configured, infer its output
+    // dimension and validate it against QDRANT_VECTOR_DIMENSION and the
+    // existing collection's schema before provisioning anything, so a
+    // misconfigured or unreachable backend fails fast here instead of
+    // surfacing later as a Qdrant insert error.
+    let embedder: Option<Arc<dyn embedding::Embedder>> =
+        embedding::build_embedder(&config)?.map(|inner| {
+            Arc::new(embedding::InstrumentedEmbedder::new(inner, metrics.clone()))
+                as Arc<dyn embedding::Embedder>
+        });
+
+    if let Some(embedder) = &embedder {
+        use embedding::Embedder as _;
+
+        let inferred_dimension = embedder.probe_dimension().await?;
+        qdrant
+            .verify_embedding_dimension(&config.collection_name, inferred_dimension)
+            .await?;
+        info!("Embedding dimension verified: {}", inferred_dimension);
+    }
+
     // Ensure collection exists
     qdrant.ensure_collection_exists().await?;
     info!("Collections ready");
@@ -175,36 +255,63 @@ async fn main() -> anyhow::Result<()> {
         .alert_service_started(env!("CARGO_PKG_VERSION"))
         .await;
 
+    // Create auth state (loads the persisted key store, bootstrapping an
+    // admin key from SERVICE_API_KEY on first run)
+    let auth_state = Arc::new(AuthState::load(
+        &config.api_keys_path,
+        config.service_api_key.clone(),
+    ));
+    if auth_state.is_enabled() {
+        info!("API key authentication enabled");
+    } else {
+        info!("API key authentication disabled");
+    }
+
+    let qdrant = Arc::new(qdrant);
+
+    // Start the background batch job queue (fixed worker pool draining a
+    // bounded channel; see jobs::JobQueue)
+    let jobs = JobQueue::spawn(qdrant.clone(), stats.clone(), embedder.clone(), config.vector_dimension);
+
     // Create app state
     let state = AppState {
-        qdrant: Arc::new(qdrant),
+        qdrant,
         config: Arc::new(config.clone()),
         metrics: metrics.clone(),
         alerts: alerts.clone(),
         stats: stats.clone(),
+        auth: auth_state.clone(),
+        jobs,
+        embedder,
+        prometheus_handle: prometheus_handle.clone(),
     };
 
-    // Create auth state
-    let auth_state = Arc::new(AuthState::new(config.service_api_key.clone()));
-    if auth_state.is_enabled() {
-        info!("API key authentication enabled");
-    } else {
-        info!("API key authentication disabled");
-    }
-
     // Build protected routes (require API key if configured)
     let protected_routes = Router::new()
+        .route("/keys", post(auth::create_key))
+        .route("/keys", get(auth::list_keys))
+        .route("/keys/:key", delete(auth::delete_key))
         .route("/memories", post(handlers::upsert_memory))
         .route("/memories/batch", post(handlers::batch_upsert_memories))
+        .route("/memories/batch/async", post(handlers::batch_upsert_memories_async))
+        .route("/memories/ndjson", post(handlers::ndjson_upsert_memories))
+        .route("/jobs/:job_id", get(handlers::get_job_status))
         .route("/memories/:point_id", get(handlers::get_memory))
         .route("/memories/:point_id", delete(handlers::delete_memory))
+        .route("/memories/get-batch", post(handlers::get_memories_batch))
+        .route("/memories/delete-by-category", post(handlers::delete_memories_by_category))
+        .route("/memories/delete-by-filter", post(handlers::delete_memories_by_filter))
+        .route("/memories/user/:user_id", delete(handlers::delete_all_memories_for_user))
         .route("/memories/search", post(handlers::search_memories))
         .route("/memories/scroll", post(handlers::scroll_memories))
         .route("/collection/info", get(handlers::get_collection_info))
         .route("/service/info", get(handlers::get_service_info))
         // Document routes
         .route("/documents", post(handlers::upsert_document))
+        .route("/documents/upload", post(handlers::upload_document))
+        .route("/documents/upload/delete", post(handlers::delete_uploaded_document))
         .route("/documents/batch", post(handlers::batch_upsert_documents))
+        .route("/documents/ndjson", post(handlers::ndjson_upsert_documents))
         .route("/documents/search", post(handlers::search_documents))
         .route("/documents/:point_id", get(handlers::get_document))
         .route("/documents/:point_id", delete(handlers::delete_document))
@@ -225,9 +332,7 @@ async fn main() -> anyhow::Result<()> {
     let public_routes = Router::new()
         .route("/health", get(health_check))
         .route("/capabilities", get(handlers::get_capabilities))
-        .route("/metrics", get(move || async move {
-            prometheus_handle.render()
-        }))
+        .route("/metrics", get(metrics_endpoint))
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
         .with_state(state.clone());
 
@@ -235,14 +340,23 @@ async fn main() -> anyhow::Result<()> {
     let app = Router::new()
         .merge(public_routes)
         .merge(protected_routes)
-        .layer(axum::middleware::from_fn(request_id::request_id_middleware))
+        .layer(axum::middleware::from_fn_with_state(
+            request_id::RequestIdState::new(
+                request_id::RequestIdMaker::from_strategy(&config.request_id_strategy),
+                request_id::RequestIdPolicy {
+                    id_reuse: request_id::IdReuse::from_strategy(&config.request_id_trust),
+                    reject_invalid: config.request_id_reject_invalid,
+                },
+            ),
+            request_id::request_id_middleware,
+        ))
         .layer(axum::middleware::from_fn_with_state(
             metrics.clone(),
             metrics::track_metrics,
         ))
         .layer(
             tower_http::trace::TraceLayer::new_for_http()
-                .make_span_with(tower_http::trace::DefaultMakeSpan::new())
+                .make_span_with(request_id::RequestSpan)
                 .on_response(tower_http::trace::DefaultOnResponse::new()),
         )
         .layer(
@@ -274,14 +388,21 @@ async fn main() -> anyhow::Result<()> {
     // Check if TLS is enabled
     #[cfg(feature = "tls")]
     if config.tls_enabled {
-        info!("TLS enabled - starting HTTPS server");
-        
         let tls_config = load_tls_config(&config)?;
-        
-        axum_server::bind_rustls(addr, tls_config)
-            .serve(app.into_make_service())
-            .with_graceful_shutdown(shutdown_signal())
-            .await?;
+
+        if config.tls_client_ca_path.is_some() {
+            info!("TLS enabled - starting HTTPS server with mutual TLS client authentication");
+            axum_server::bind_rustls(addr, tls_config)
+                .serve(app.into_make_service_with_connect_info::<ClientCertInfo>())
+                .with_graceful_shutdown(shutdown_signal())
+                .await?;
+        } else {
+            info!("TLS enabled - starting HTTPS server");
+            axum_server::bind_rustls(addr, tls_config)
+                .serve(app.into_make_service())
+                .with_graceful_shutdown(shutdown_signal())
+                .await?;
+        }
     } else {
         info!("TLS disabled - starting HTTP server");
         axum::serve(listener, app)
@@ -303,9 +424,76 @@ async fn main() -> anyhow::Result<()> {
             .await?;
     }
 
+    // Flush any spans still buffered in the OTLP exporter before exiting.
+    opentelemetry::global::shutdown_tracer_provider();
+
     Ok(())
 }
 
+/// Initialize the tracing subscriber.
+///
+/// Always installs the `fmt` layer. When `OTEL_EXPORTER_OTLP_ENDPOINT` is set,
+/// additionally installs an OpenTelemetry layer that exports spans (requests,
+/// `QdrantService` client calls, webhook alert dispatches) to that collector
+/// via OTLP/gRPC, tagged with `service.name`/`service.version` resource
+/// attributes. When unset, behavior is unchanged from before (fmt layer only).
+fn init_tracing(config: &Config) {
+    let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| "synaplan_qdrant_service=info,tower_http=info".into());
+
+    // Always understand incoming W3C traceparent headers, even without an
+    // OTLP exporter configured, so request_id middleware can join a trace.
+    opentelemetry::global::set_text_map_propagator(
+        opentelemetry_sdk::propagation::TraceContextPropagator::new(),
+    );
+
+    let Some(endpoint) = config.otel_exporter_otlp_endpoint.clone() else {
+        tracing_subscriber::registry()
+            .with(env_filter)
+            .with(tracing_subscriber::fmt::layer())
+            .init();
+        return;
+    };
+
+    let resource = opentelemetry_sdk::Resource::new(vec![
+        opentelemetry::KeyValue::new("service.name", config.otel_service_name.clone()),
+        opentelemetry::KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+    ]);
+
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(opentelemetry_sdk::trace::config().with_resource(resource))
+        .install_batch(opentelemetry_sdk::runtime::Tokio);
+
+    match tracer {
+        Ok(tracer) => {
+            let otel_layer = tracing_opentelemetry::layer().with_tracer(tracer);
+            tracing_subscriber::registry()
+                .with(env_filter)
+                .with(tracing_subscriber::fmt::layer())
+                .with(otel_layer)
+                .init();
+            info!(
+                "OTLP tracing export enabled (service: {})",
+                config.otel_service_name
+            );
+        }
+        Err(e) => {
+            // Fall back to fmt-only so a misconfigured collector doesn't take the service down.
+            tracing_subscriber::registry()
+                .with(env_filter)
+                .with(tracing_subscriber::fmt::layer())
+                .init();
+            error!("Failed to install OTLP tracer, continuing without it: {}", e);
+        }
+    }
+}
+
 /// Daily statistics reporting task
 ///
 /// Runs in the background and sends stats to webhook at configured intervals.
@@ -374,7 +562,10 @@ async fn shutdown_signal() {
 fn load_tls_config(config: &Config) -> anyhow::Result<axum_server::tls_rustls::RustlsConfig> {
     use std::fs::File;
     use std::io::BufReader;
+    use std::sync::Arc as StdArc;
     use rustls::{ServerConfig, pki_types::{CertificateDer, PrivateKeyDer}};
+    use rustls::server::WebPkiClientVerifier;
+    use rustls::RootCertStore;
     use rustls_pemfile::{certs, pkcs8_private_keys};
 
     let cert_path = config.tls_cert_path.as_ref()
@@ -400,16 +591,106 @@ fn load_tls_config(config: &Config) -> anyhow::Result<axum_server::tls_rustls::R
 
     let key = PrivateKeyDer::Pkcs8(keys.remove(0));
 
-    let server_config = ServerConfig::builder()
-        .with_no_client_auth()
-        .with_single_cert(certs, key)?;
+    // When TLS_CLIENT_CA_PATH is set, require and verify a client certificate
+    // chaining to one of those CAs (mutual TLS) instead of accepting anonymous
+    // clients. This complements, and can coexist with, the API-key auth.
+    let builder = match config.tls_client_ca_path.as_ref() {
+        Some(ca_path) => {
+            let ca_file = File::open(ca_path)?;
+            let mut ca_reader = BufReader::new(ca_file);
+            let ca_certs: Vec<CertificateDer> = certs(&mut ca_reader)
+                .collect::<Result<_, _>>()?;
+
+            let mut roots = RootCertStore::empty();
+            for ca_cert in ca_certs {
+                roots.add(ca_cert)?;
+            }
+
+            let verifier = WebPkiClientVerifier::builder(StdArc::new(roots))
+                .build()
+                .map_err(|e| anyhow::anyhow!("Failed to build client cert verifier: {}", e))?;
+
+            ServerConfig::builder().with_client_cert_verifier(verifier)
+        }
+        None => ServerConfig::builder().with_no_client_auth(),
+    };
+
+    let server_config = builder.with_single_cert(certs, key)?;
 
     Ok(axum_server::tls_rustls::RustlsConfig::from_config(Arc::new(server_config)))
 }
 
+/// Verified mTLS client certificate info, extracted once per connection via
+/// [`axum::extract::connect_info::Connected`] and available to handlers as
+/// `ConnectInfo<ClientCertInfo>` (and recorded next to the request ID span).
+#[cfg(feature = "tls")]
+#[derive(Clone, Debug, Default)]
+pub struct ClientCertInfo {
+    pub common_name: Option<String>,
+}
+
+#[cfg(feature = "tls")]
+impl axum::extract::connect_info::Connected<&tokio_rustls::server::TlsStream<axum_server::AddrStream>>
+    for ClientCertInfo
+{
+    fn connect_info(
+        target: &tokio_rustls::server::TlsStream<axum_server::AddrStream>,
+    ) -> Self {
+        let (_, session) = target.get_ref();
+        let common_name = session
+            .peer_certificates()
+            .and_then(|certs| certs.first())
+            .and_then(|cert| {
+                let (_, parsed) = x509_parser::parse_x509_certificate(cert.as_ref()).ok()?;
+                parsed
+                    .subject()
+                    .iter_common_name()
+                    .next()
+                    .and_then(|cn| cn.as_str().ok())
+                    .map(|s| s.to_string())
+            });
+
+        ClientCertInfo { common_name }
+    }
+}
+
+/// Serves the generic `metrics`-crate registry (request/embedding/qdrant
+/// gauges, all registered in [`MetricsState`]) alongside the
+/// [`StatsTracker`] counters/gauge used for the daily Discord report, both
+/// in Prometheus text exposition format, so a single scrape target covers
+/// everything.
+async fn metrics_endpoint(State(state): State<AppState>) -> String {
+    let mut body = state.prometheus_handle.render();
+    body.push_str(&state.stats.render_prometheus(Some(&state.config.collection_name)));
+    body
+}
+
 async fn health_check(State(state): State<AppState>) -> Result<Json<serde_json::Value>, AppError> {
     let healthy = state.qdrant.health_check().await?;
-    
+
+    // Live readiness check: ping the configured embedding backend so a
+    // misconfigured or unreachable Ollama/REST endpoint is surfaced here
+    // rather than on the first write.
+    let embedding_status = match &state.embedder {
+        Some(embedder) => {
+            use embedding::Embedder as _;
+            match embedder.probe_dimension().await {
+                Ok(_) => serde_json::json!({
+                    "status": "connected",
+                    "backend": embedder.backend(),
+                    "model": embedder.model(),
+                }),
+                Err(e) => serde_json::json!({
+                    "status": "disconnected",
+                    "backend": embedder.backend(),
+                    "model": embedder.model(),
+                    "error": e.to_string(),
+                }),
+            }
+        }
+        None => serde_json::json!({ "status": "disabled" }),
+    };
+
     // Get Qdrant stats for metrics
     let (coll_status, points_count, vectors_count, _) = state.qdrant.get_collection_info(None).await.unwrap_or((
         "unknown".to_string(),
@@ -420,6 +701,9 @@ async fn health_check(State(state): State<AppState>) -> Result<Json<serde_json::
     
     // Update Prometheus metrics
     state.metrics.update_qdrant_stats(points_count, vectors_count);
+    state
+        .metrics
+        .update_job_queue_stats(state.jobs.queue_depth(), state.jobs.active_workers());
 
     // Calculate metrics
     let requests_total = state.metrics.get_requests_total();
@@ -443,6 +727,18 @@ async fn health_check(State(state): State<AppState>) -> Result<Json<serde_json::
         });
     }
 
+    // Check for a saturated job queue and alert
+    if state.jobs.saturation() >= 0.9 {
+        let queue_depth = state.jobs.queue_depth();
+        let capacity = state.jobs.capacity();
+        tokio::spawn({
+            let alerts = state.alerts.clone();
+            async move {
+                alerts.alert_job_queue_saturated(queue_depth, capacity).await;
+            }
+        });
+    }
+
     Ok(Json(serde_json::json!({
         "status": if healthy { "healthy" } else { "unhealthy" },
         "service": "synaplan-qdrant-service",
@@ -454,6 +750,7 @@ async fn health_check(State(state): State<AppState>) -> Result<Json<serde_json::
             "points_count": points_count,
             "vectors_count": vectors_count,
         },
+        "embedding": embedding_status,
         "metrics": {
             "requests_total": requests_total,
             "requests_failed": requests_failed,
@@ -26,6 +26,24 @@ impl MetricsState {
         describe_gauge!("uptime_seconds", "Service uptime in seconds");
         describe_gauge!("qdrant_points_total", "Total number of points in Qdrant");
         describe_gauge!("qdrant_vectors_total", "Total number of vectors in Qdrant");
+        describe_gauge!("job_queue_depth", "Number of batch jobs waiting to be picked up by a worker");
+        describe_gauge!("job_queue_active_workers", "Number of job queue workers currently processing a batch");
+        describe_histogram!(
+            "embedding_duration_seconds",
+            "Embedding backend call duration in seconds, labeled by backend and model"
+        );
+        describe_counter!(
+            "embeddings_total",
+            "Total number of embedding calls, labeled by backend and model"
+        );
+        describe_counter!(
+            "embeddings_failed",
+            "Total number of failed embedding calls, labeled by backend and model"
+        );
+        describe_counter!(
+            "tokens_estimated_total",
+            "Estimated number of tokens embedded, labeled by backend and model"
+        );
 
         Self {
             start_time: Arc::new(Instant::now()),
@@ -71,6 +89,51 @@ impl MetricsState {
         gauge!("qdrant_vectors_total").set(vectors_count as f64);
         gauge!("uptime_seconds").set(self.uptime_seconds() as f64);
     }
+
+    pub fn update_job_queue_stats(&self, queue_depth: usize, active_workers: usize) {
+        gauge!("job_queue_depth").set(queue_depth as f64);
+        gauge!("job_queue_active_workers").set(active_workers as f64);
+    }
+
+    #[inline]
+    pub fn record_embedding_duration(&self, backend: &str, model: &str, duration: f64) {
+        histogram!(
+            "embedding_duration_seconds",
+            "backend" => backend.to_string(),
+            "model" => model.to_string()
+        )
+        .record(duration);
+    }
+
+    #[inline]
+    pub fn increment_embeddings(&self, backend: &str, model: &str) {
+        counter!(
+            "embeddings_total",
+            "backend" => backend.to_string(),
+            "model" => model.to_string()
+        )
+        .increment(1);
+    }
+
+    #[inline]
+    pub fn increment_embedding_failures(&self, backend: &str, model: &str) {
+        counter!(
+            "embeddings_failed",
+            "backend" => backend.to_string(),
+            "model" => model.to_string()
+        )
+        .increment(1);
+    }
+
+    #[inline]
+    pub fn add_estimated_tokens(&self, backend: &str, model: &str, tokens: u64) {
+        counter!(
+            "tokens_estimated_total",
+            "backend" => backend.to_string(),
+            "model" => model.to_string()
+        )
+        .increment(tokens);
+    }
 }
 
 impl Default for MetricsState {
@@ -8,9 +8,17 @@ pub struct ServiceCapabilities {
     pub version: String,
     pub vector_dimension: u64,
     pub embedding: EmbeddingCapabilities,
+    /// `false` when `MEMORY_ENCRYPTION_ENABLED` is set, since
+    /// `QdrantService::ensure_collection_exists_for`/`ensure_collection_exists_for_documents`
+    /// then skip the full-text index and the lexical leg of
+    /// `search_memories_semantic_lexical`/`search_documents_semantic_lexical`
+    /// falls back to dense-only results.
+    pub lexical_search_available: bool,
 }
 
-/// Embedding capabilities (always disabled - backend handles embedding)
+/// Embedding capabilities actually resolved by `embedding::build_embedder`
+/// from `Config` (`supported` is `false` when `EMBEDDING_BACKEND=none` or
+/// unset, in which case callers must send pre-computed vectors).
 #[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct EmbeddingCapabilities {
     pub supported: bool,
@@ -21,6 +29,15 @@ pub struct EmbeddingCapabilities {
     pub vector_dimension: u64,
 }
 
+/// Sparse (lexical/keyword) vector: index -> weight pairs produced by a
+/// BM25/SPLADE-style tokenizer. Used alongside a dense vector for hybrid
+/// search (see `QdrantService::search_memories_hybrid`).
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SparseVector {
+    pub indices: Vec<u32>,
+    pub values: Vec<f32>,
+}
+
 /// Memory payload structure
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[schema(example = json!({
@@ -32,7 +49,8 @@ pub struct EmbeddingCapabilities {
     "message_id": 4488,
     "created": 1769034136,
     "updated": 1769034136,
-    "active": true
+    "active": true,
+    "version": 3
 }))]
 pub struct MemoryPayload {
     #[schema(example = 1730)]
@@ -54,6 +72,14 @@ pub struct MemoryPayload {
     pub updated: i64,
     #[schema(example = true)]
     pub active: bool,
+    /// Monotonically increasing version, bumped on every single-point upsert
+    /// (`POST /memories`); the batch, async-job and NDJSON upserts store the
+    /// value as sent. Lets
+    /// clients do optimistic-concurrency read-modify-write via
+    /// `UpsertMemoryRequest::expected_version` without a distributed lock.
+    #[serde(default)]
+    #[schema(example = 3)]
+    pub version: i64,
 }
 
 /// Upsert memory with pre-computed vector
@@ -75,12 +101,26 @@ pub struct MemoryPayload {
 pub struct UpsertMemoryRequest {
     #[schema(example = "mem_1730_12345")]
     pub point_id: String,
+    /// Precomputed dense vector. Optional when the service has an embedder
+    /// configured (see `/capabilities`): omit it to have `payload.value`
+    /// embedded server-side instead. Required otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     #[schema(example = json!([0.1, 0.2, 0.3]))]
-    pub vector: Vec<f32>,
+    pub vector: Option<Vec<f32>>,
     pub payload: MemoryPayload,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[schema(example = "feedback_false_positive")]
     pub namespace: Option<String>,
+    /// Optional sparse/lexical vector stored alongside the dense one, enabling
+    /// hybrid search over this point.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sparse_vector: Option<SparseVector>,
+    /// Optimistic-concurrency precondition: reject the write with
+    /// `AppError::Conflict` unless the point's current `version` (0 if it
+    /// doesn't exist yet) equals this value. Omit to upsert unconditionally.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 2)]
+    pub expected_version: Option<i64>,
 }
 
 /// Batch upsert multiple memories
@@ -97,6 +137,42 @@ pub struct MemoryResponse {
     pub payload: MemoryPayload,
 }
 
+/// Request to fetch many memories by ID in one round trip
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct GetMemoriesBatchRequest {
+    #[schema(min_items = 1, max_items = 256)]
+    pub point_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+}
+
+/// Response for batch memory retrieval
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GetMemoriesBatchResponse {
+    pub found: Vec<MemoryResponse>,
+    pub missing: Vec<String>,
+}
+
+/// Request to delete memories by category
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DeleteMemoriesByCategoryRequest {
+    pub user_id: i64,
+    pub category: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+}
+
+/// Request to delete memories matching a filter expression, ANDed with
+/// `user_id`. See [`crate::filter`] for the supported grammar.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DeleteMemoriesByFilterRequest {
+    pub user_id: i64,
+    #[schema(example = "category = notes AND created < 1700000000")]
+    pub filter: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+}
+
 /// Search memories by vector
 #[derive(Debug, Deserialize, ToSchema)]
 #[schema(example = json!({
@@ -107,8 +183,13 @@ pub struct MemoryResponse {
     "min_score": 0.35
 }))]
 pub struct SearchMemoriesRequest {
+    /// Precomputed dense query vector. Optional when the service has an
+    /// embedder configured (see `/capabilities`): omit it to have
+    /// `query_text` embedded server-side instead. Required otherwise; at
+    /// least one of the two must be present.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     #[schema(example = json!([0.1, 0.2, 0.3]))]
-    pub query_vector: Vec<f32>,
+    pub query_vector: Option<Vec<f32>>,
     #[schema(example = 1730)]
     pub user_id: i64,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -123,6 +204,31 @@ pub struct SearchMemoriesRequest {
     #[serde(default = "default_min_score")]
     #[schema(example = 0.35, minimum = 0.0, maximum = 1.0)]
     pub min_score: f32,
+    /// Optional sparse/lexical query vector. When present, the search runs
+    /// in hybrid mode: a dense query and a sparse query are issued and fused
+    /// with Reciprocal Rank Fusion.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_sparse_vector: Option<SparseVector>,
+    /// Optional filter expression over payload keys, e.g.
+    /// `"category = notes AND created_at > 1700000000"`. ANDed alongside
+    /// `category`. See [`crate::filter`] for the supported grammar.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "category = notes AND created_at > 1700000000")]
+    pub filter: Option<String>,
+    /// Optional lexical query text. When present, takes priority over
+    /// `query_sparse_vector`: the search runs `query_vector` against the
+    /// dense index and `query_text` against Qdrant's full-text index on the
+    /// `value` field, fusing both with Reciprocal Rank Fusion weighted by
+    /// `semantic_ratio`. Useful for exact keyword/ID recall that a purely
+    /// semantic match can miss.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "Yusuf Senel")]
+    pub query_text: Option<String>,
+    /// Weight of the dense/semantic leg when `query_text` is set; the
+    /// lexical leg gets `1.0 - semantic_ratio`. Ignored otherwise.
+    #[serde(default = "default_semantic_ratio")]
+    #[schema(example = 0.5, minimum = 0.0, maximum = 1.0)]
+    pub semantic_ratio: f32,
 }
 
 /// Search result with score
@@ -132,6 +238,13 @@ pub struct SearchResult {
     #[schema(example = 0.95, minimum = 0.0, maximum = 1.0)]
     pub score: f32,
     pub payload: MemoryPayload,
+    /// Which modality(ies) contributed this hit: `"dense"`, `"sparse"`, or
+    /// `"hybrid"` when fused from both. A search with `query_text` reports
+    /// `"semantic"` or `"lexical"` for a hit found by one leg only, and
+    /// `"hybrid"` for one found by both.
+    #[serde(default = "default_modality")]
+    #[schema(example = "dense")]
+    pub modality: String,
 }
 
 /// Search response
@@ -153,8 +266,18 @@ pub struct ScrollMemoriesRequest {
     #[schema(example = "feedback_false_positive")]
     pub namespace: Option<String>,
     #[serde(default = "default_scroll_limit")]
-    #[schema(example = 1000, minimum = 1, maximum = 10000)]
+    #[schema(example = 100, minimum = 1, maximum = 1000)]
     pub limit: u64,
+    /// Opaque cursor from a previous page's `next_offset`. Omit (or pass
+    /// `null`) to fetch the first page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "18446744071562067201")]
+    pub offset: Option<String>,
+    /// Optional filter expression over payload keys, ANDed alongside
+    /// `category`. See [`crate::filter`] for the supported grammar.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "category = notes AND created_at > 1700000000")]
+    pub filter: Option<String>,
 }
 
 /// Scroll memories response
@@ -162,6 +285,15 @@ pub struct ScrollMemoriesRequest {
 pub struct ScrollMemoriesResponse {
     pub memories: Vec<MemoryResponse>,
     pub count: usize,
+    /// Cursor to pass back as `offset` on the next request; `None` once the
+    /// last page has been returned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<String>,
+    /// Exact count of the user's active memories (narrowed by `category`
+    /// when set), independent of paging, via Qdrant's Count API. The
+    /// request's `filter` expression is not applied to this count.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
 }
 
 /// Collection information
@@ -178,7 +310,10 @@ pub struct CollectionInfo {
 pub struct BatchOperationResponse {
     pub success_count: usize,
     pub failed_count: usize,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    /// Always serialized (never omitted when empty), unlike most optional
+    /// fields elsewhere: this type is also returned postcard-encoded (see
+    /// [`crate::wire`]), whose fixed-arity layout breaks under
+    /// `skip_serializing_if`.
     pub errors: Vec<BatchError>,
 }
 
@@ -189,6 +324,23 @@ pub struct BatchError {
     pub error: String,
 }
 
+/// Response for streaming NDJSON ingestion (`/memories/ndjson`, `/documents/ndjson`)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NdjsonUpsertResponse {
+    pub success_count: usize,
+    pub failed_count: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<NdjsonLineError>,
+}
+
+/// Per-line NDJSON ingestion error. Keyed by 1-based line number rather than
+/// point ID since a line can fail to parse before a point ID is even known.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NdjsonLineError {
+    pub line: usize,
+    pub error: String,
+}
+
 /// Document chunk payload stored in Qdrant
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct DocumentPayload {
@@ -210,6 +362,13 @@ pub struct DocumentPayload {
     pub text: String,
     /// Unix timestamp
     pub created: i64,
+    /// File IDs that reference this exact chunk content. Populated by
+    /// [`crate::qdrant::QdrantService::upsert_document_deduped`], which
+    /// stores identical chunk content once and appends to this list instead
+    /// of inserting a duplicate point. Empty/absent for chunks written
+    /// through the non-deduplicating upsert path.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ref_files: Vec<i64>,
 }
 
 /// Request to upsert a document chunk
@@ -217,10 +376,17 @@ pub struct DocumentPayload {
 pub struct UpsertDocumentRequest {
     /// Unique point ID (e.g., "doc_1_123_0")
     pub point_id: String,
-    /// Vector embedding (must be exactly 1024 dimensions)
-    pub vector: Vec<f32>,
+    /// Precomputed dense vector (must match the configured
+    /// `vector_dimension`). Optional when the service has an embedder
+    /// configured (see `/capabilities`): omit it to have `payload.text`
+    /// embedded server-side instead. Required otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vector: Option<Vec<f32>>,
     /// Document payload
     pub payload: DocumentPayload,
+    /// Optional sparse/lexical vector stored alongside the dense one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sparse_vector: Option<SparseVector>,
 }
 
 /// Request for batch document upsert
@@ -238,11 +404,44 @@ pub struct BatchUpsertResponse {
     pub errors: Vec<String>,
 }
 
+/// Request to upload a whole document and have it split into chunks
+/// server-side, instead of the caller pre-chunking it for `/documents` or
+/// `/documents/batch`. See [`crate::chunking`] for how the split is done.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UploadDocumentRequest {
+    pub user_id: i64,
+    /// Reference to source file (BFILES.BID)
+    pub file_id: i64,
+    /// Grouping key (e.g., "WIDGET:xxx", "TASKPROMPT:xxx", "DEFAULT")
+    pub group_key: String,
+    /// File type identifier
+    pub file_type: i32,
+    /// Unix timestamp
+    pub created: i64,
+    /// Full document text to chunk and embed
+    pub text: String,
+}
+
+/// Response for [`crate::handlers::upload_document`]
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UploadDocumentResponse {
+    /// Content-derived point IDs of the chunks this document was split
+    /// into, in document order. A point ID may already have existed (and
+    /// simply gained this file as an additional reference) if another file
+    /// contains an identical chunk.
+    pub point_ids: Vec<String>,
+}
+
 /// Request to search documents
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct SearchDocumentsRequest {
-    /// Query vector (must be exactly 1024 dimensions)
-    pub vector: Vec<f32>,
+    /// Precomputed dense query vector (must match the configured
+    /// `vector_dimension`). Optional when the service has an embedder
+    /// configured (see `/capabilities`): omit it to have `query_text`
+    /// embedded server-side instead. Required otherwise; at least one of
+    /// the two must be present.
+    #[serde(default)]
+    pub vector: Option<Vec<f32>>,
     /// User ID (required for isolation)
     pub user_id: i64,
     /// Optional group key filter
@@ -254,6 +453,24 @@ pub struct SearchDocumentsRequest {
     /// Minimum similarity score (default: 0.3)
     #[serde(default = "default_min_score")]
     pub min_score: f32,
+    /// Optional sparse/lexical query vector; when present, runs hybrid
+    /// dense+sparse search fused with Reciprocal Rank Fusion.
+    #[serde(default)]
+    pub query_sparse_vector: Option<SparseVector>,
+    /// Optional filter expression over payload keys, ANDed alongside
+    /// `group_key`. See [`crate::filter`] for the supported grammar.
+    #[serde(default)]
+    pub filter: Option<String>,
+    /// Optional lexical query text; when present, takes priority over
+    /// `query_sparse_vector` and runs hybrid dense+lexical search (matching
+    /// Qdrant's full-text index on the `text` field) fused with Reciprocal
+    /// Rank Fusion weighted by `semantic_ratio`.
+    #[serde(default)]
+    pub query_text: Option<String>,
+    /// Weight of the dense/semantic leg when `query_text` is set; the
+    /// lexical leg gets `1.0 - semantic_ratio`. Ignored otherwise.
+    #[serde(default = "default_semantic_ratio")]
+    pub semantic_ratio: f32,
 }
 
 /// Search result
@@ -268,6 +485,12 @@ pub struct DocumentSearchResult {
     /// Vector (optional, only returned by get_document)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vector: Option<Vec<f32>>,
+    /// Which modality(ies) contributed this hit: `"dense"`, `"sparse"`, or
+    /// `"hybrid"` when fused from both. A search with `query_text` reports
+    /// `"semantic"` or `"lexical"` for a hit found by one leg only, and
+    /// `"hybrid"` for one found by both.
+    #[serde(default = "default_modality")]
+    pub modality: String,
 }
 
 /// Request to delete documents by file
@@ -299,6 +522,46 @@ pub struct DocumentStatsResponse {
     pub total_files: u64,
     pub total_groups: u64,
     pub chunks_by_group: std::collections::HashMap<String, u64>,
+    /// Number of distinct chunk contents, grouped by content hash. Equal to
+    /// `total_chunks` when no two stored chunks share identical text.
+    pub unique_chunks: u64,
+    /// Bytes of chunk text not physically stored a second (or Nth) time
+    /// because their content hash matched an already-seen chunk, the way
+    /// backup tools report deduplication savings.
+    pub bytes_saved: u64,
+}
+
+/// Query params for paginating [`GroupKeysResponse`].
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct GroupKeysQuery {
+    #[serde(default = "default_scroll_limit")]
+    #[schema(example = 100, minimum = 1, maximum = 1000)]
+    pub limit: u64,
+    #[serde(default)]
+    #[schema(example = 0)]
+    pub offset: u64,
+}
+
+/// A page of a user's distinct document group keys, sorted for stable paging.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GroupKeysResponse {
+    pub groups: Vec<String>,
+    /// `offset` to request the next page; `None` once the last page has
+    /// been returned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<u64>,
+    /// Total number of distinct group keys, independent of paging.
+    pub total: u64,
+}
+
+/// A single collection (or full-storage) snapshot, as reported by Qdrant.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SnapshotInfo {
+    pub name: String,
+    /// RFC 3339 creation timestamp, when Qdrant reports one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creation_time: Option<String>,
+    pub size: u64,
 }
 
 // Default functions
@@ -314,7 +577,21 @@ const fn default_min_score() -> f32 {
 
 #[inline]
 const fn default_scroll_limit() -> u64 {
-    1000
+    100
+}
+
+/// Upper bound on [`ScrollMemoriesRequest::limit`], enforced by the
+/// `scroll_memories` handler so one page can't pull the whole collection.
+pub const MAX_SCROLL_LIMIT: u64 = 1000;
+
+#[inline]
+fn default_modality() -> String {
+    "dense".to_string()
+}
+
+#[inline]
+const fn default_semantic_ratio() -> f32 {
+    0.5
 }
 
 // Unit tests for models
@@ -335,6 +612,7 @@ mod tests {
             created: 1737115234,
             updated: 1737115234,
             active: true,
+            version: 0,
         };
 
         let json = serde_json::to_string(&payload).unwrap();
@@ -349,15 +627,21 @@ mod tests {
     #[test]
     fn test_search_request_defaults() {
         let req = SearchMemoriesRequest {
-            query_vector: vec![0.1; 1024],
+            query_vector: Some(vec![0.1; 1024]),
             user_id: 1,
             category: None,
+            namespace: None,
             limit: default_limit(),
             min_score: default_min_score(),
+            query_sparse_vector: None,
+            filter: None,
+            query_text: None,
+            semantic_ratio: default_semantic_ratio(),
         };
 
         assert_eq!(req.limit, 5);
         assert_eq!(req.min_score, 0.7);
+        assert_eq!(req.semantic_ratio, 0.5);
     }
 
     #[test]
@@ -379,10 +663,27 @@ mod tests {
 
         let req: UpsertMemoryRequest = serde_json::from_str(json).unwrap();
         assert_eq!(req.point_id, "mem_1_123");
-        assert_eq!(req.vector.len(), 3);
+        assert_eq!(req.vector.unwrap().len(), 3);
         assert_eq!(req.payload.user_id, 1);
     }
 
+    #[test]
+    fn test_scroll_request_defaults_to_first_page() {
+        let json = r#"{"user_id": 1}"#;
+
+        let req: ScrollMemoriesRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.limit, 100);
+        assert!(req.offset.is_none());
+    }
+
+    #[test]
+    fn test_scroll_request_carries_cursor_offset() {
+        let json = r#"{"user_id": 1, "offset": "18446744071562067201"}"#;
+
+        let req: ScrollMemoriesRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.offset.as_deref(), Some("18446744071562067201"));
+    }
+
     #[test]
     fn test_search_result_serialization() {
         let result = SearchResult {
@@ -398,7 +699,9 @@ mod tests {
                 created: 1234567890,
                 updated: 1234567890,
                 active: true,
+                version: 0,
             },
+            modality: default_modality(),
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -419,6 +722,7 @@ mod tests {
                 device: "auto".to_string(),
                 vector_dimension: 1024,
             },
+            lexical_search_available: true,
         };
 
         let json = serde_json::to_string(&caps).unwrap();
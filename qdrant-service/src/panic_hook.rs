@@ -0,0 +1,168 @@
+//! Panic hook that captures a demangled backtrace and forwards it to the
+//! configured alert sinks before falling through to the default panic
+//! behavior (logging, and abort/unwind per the configured panic strategy).
+//!
+//! Modeled on how Zed uploads crash reports: resolve the panic's message and
+//! location, walk the `backtrace` crate's frames through
+//! `rustc_demangle::demangle` so symbols read as Rust paths instead of
+//! mangled `_ZN...` blobs, then hand the formatted report to
+//! [`WebhookAlerts::alert_panic`].
+
+use std::cell::Cell;
+use std::panic::PanicInfo;
+
+use crate::alerts::WebhookAlerts;
+
+/// Discord embed descriptions cap out around 4096 chars; leave headroom for
+/// the message/location header above the backtrace.
+const MAX_REPORT_CHARS: usize = 3500;
+const MAX_FRAMES: usize = 40;
+
+thread_local! {
+    /// Set while this hook is running on the current thread. Guards against
+    /// a panic inside the hook itself (e.g. the blocking webhook call)
+    /// recursing back into `install`'s closure.
+    static IN_PANIC_HOOK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Installs a panic hook that alerts `alerts` at [`crate::alerts::AlertLevel::Critical`]
+/// with a demangled backtrace, then runs the previously installed hook so
+/// existing logging/abort behavior is preserved.
+pub fn install(alerts: WebhookAlerts) {
+    let previous_hook = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        let already_in_hook = IN_PANIC_HOOK.with(|flag| flag.replace(true));
+        if already_in_hook {
+            previous_hook(info);
+            return;
+        }
+
+        let report = format_panic_report(info);
+        let alerts = alerts.clone();
+
+        // The hook runs synchronously on the panicking thread, which may not
+        // have a Tokio runtime available (e.g. a non-async worker thread),
+        // so spin up a throwaway one and block on it from a fresh thread
+        // rather than assuming we're inside `#[tokio::main]`.
+        // The guard is thread-local, so mark the alert thread too: a panic
+        // inside the webhook call then skips the alert instead of recursing.
+        let sent = std::thread::spawn(move || {
+            IN_PANIC_HOOK.with(|flag| flag.set(true));
+            match tokio::runtime::Runtime::new() {
+                Ok(rt) => rt.block_on(alerts.alert_panic(&report)),
+                Err(e) => tracing::error!("Failed to start runtime for panic alert: {}", e),
+            }
+        })
+        .join();
+
+        if sent.is_err() {
+            tracing::error!("Panic alert thread itself panicked while reporting a panic");
+        }
+
+        IN_PANIC_HOOK.with(|flag| flag.set(false));
+        previous_hook(info);
+    }));
+}
+
+fn format_panic_report(info: &PanicInfo<'_>) -> String {
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Box<dyn Any>");
+
+    let location = info
+        .location()
+        .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+        .unwrap_or_else(|| "unknown location".to_string());
+
+    let backtrace = backtrace::Backtrace::new();
+    let mut frames = Vec::new();
+    for (index, frame) in backtrace.frames().iter().enumerate().take(MAX_FRAMES) {
+        for symbol in frame.symbols() {
+            let name = symbol
+                .name()
+                .map(|n| rustc_demangle::demangle(&n.to_string()).to_string())
+                .unwrap_or_else(|| "<unknown>".to_string());
+            frames.push(format!("  {index}: {name}"));
+        }
+    }
+
+    let mut report = format!(
+        "Panic at {location}: {message}\n\nBacktrace:\n{}",
+        frames.join("\n")
+    );
+    if report.len() > MAX_REPORT_CHARS {
+        truncate_at_char_boundary(&mut report, MAX_REPORT_CHARS);
+        report.push_str("\n... (truncated)");
+    }
+    report
+}
+
+/// `String::truncate` panics if `max_bytes` doesn't land on a UTF-8 char
+/// boundary, which a panic message containing multi-byte characters can
+/// easily hit. Walk back to the nearest boundary instead, since panicking
+/// inside the panic hook would abort the process before `alert_panic` runs.
+fn truncate_at_char_boundary(s: &mut String, max_bytes: usize) {
+    let mut boundary = max_bytes;
+    while boundary > 0 && !s.is_char_boundary(boundary) {
+        boundary -= 1;
+    }
+    s.truncate(boundary);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::{Arc, Mutex, OnceLock};
+
+    /// `std::panic::set_hook` is process-global, so serialize tests that
+    /// swap it to avoid one test's hook shadowing another's.
+    fn hook_test_lock() -> &'static Mutex<()> {
+        static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+        LOCK.get_or_init(|| Mutex::new(()))
+    }
+
+    #[test]
+    fn test_format_panic_report_includes_message_and_location() {
+        let _guard = hook_test_lock().lock().unwrap();
+        let captured: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+        let captured_clone = captured.clone();
+
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            *captured_clone.lock().unwrap() = Some(format_panic_report(info));
+        }));
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| panic!("boom")));
+        panic::set_hook(previous);
+        assert!(result.is_err());
+
+        let report = captured.lock().unwrap().take().unwrap();
+        assert!(report.contains("boom"));
+        assert!(report.contains("Backtrace:"));
+    }
+
+    #[test]
+    fn test_report_is_truncated_to_the_discord_embed_cap() {
+        let mut report = "x".repeat(MAX_REPORT_CHARS + 500);
+        if report.len() > MAX_REPORT_CHARS {
+            truncate_at_char_boundary(&mut report, MAX_REPORT_CHARS);
+            report.push_str("\n... (truncated)");
+        }
+        assert!(report.len() <= MAX_REPORT_CHARS + "\n... (truncated)".len());
+        assert!(report.ends_with("... (truncated)"));
+    }
+
+    #[test]
+    fn test_truncate_at_char_boundary_does_not_split_multibyte_chars() {
+        // Each "é" is 2 bytes, so a naive `truncate(MAX_REPORT_CHARS)` lands
+        // mid-codepoint whenever the cap is odd relative to the run's start.
+        let mut report = "é".repeat(MAX_REPORT_CHARS);
+        truncate_at_char_boundary(&mut report, MAX_REPORT_CHARS - 1);
+        assert!(report.is_char_boundary(report.len()));
+    }
+}
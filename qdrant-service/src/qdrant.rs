@@ -1,17 +1,101 @@
 use crate::config::Config;
 use crate::error::AppError;
-use crate::models::{MemoryPayload, DocumentPayload, DocumentStatsResponse, DocumentSearchResult};
+use crate::models::{MemoryPayload, DocumentPayload, DocumentStatsResponse, DocumentSearchResult, SnapshotInfo, SparseVector};
 use qdrant_client::qdrant::{
-    point_id::PointIdOptions, Condition, CreateCollectionBuilder, DeletePointsBuilder, Distance,
-    Filter, GetPointsBuilder, PointId, PointStruct, SearchPointsBuilder,
-    UpsertPointsBuilder, VectorParamsBuilder, ScrollPointsBuilder, HnswConfigDiff, vectors_config, VectorParams, FieldType,
+    point_id::PointIdOptions, Condition, CountPointsBuilder, CreateCollectionBuilder,
+    DeletePointsBuilder, Distance, Filter, GetPointsBuilder, NamedVectors, PointId, PointStruct,
+    SearchPointsBuilder, SparseVectorParamsBuilder, UpsertPointsBuilder, VectorParamsBuilder,
+    ScrollPointsBuilder, HnswConfigDiff, vectors_config, VectorParams, FieldType, Range, Vector,
 };
 use qdrant_client::{Payload, Qdrant};
+use serde::{Deserialize, Serialize};
 use serde_json::{self, json};
+use sha2::{Digest, Sha256};
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use tracing::{debug, info, warn};
 
+/// Named sparse vector on the collection, alongside the default dense vector.
+const SPARSE_VECTOR_NAME: &str = "sparse";
+/// Points per `UpsertPointsBuilder` call in the batch upsert paths, bounding
+/// request size while still avoiding one round trip per point. Both
+/// `upsert_memories_batch` and `upsert_documents_batch` already submit each
+/// chunk as a single real Qdrant batch call rather than looping per point,
+/// with per-point dimension/payload validation happening client-side first;
+/// the 256-point cap is about bounding one gRPC message's size, not emulating
+/// a point-at-a-time path.
+const BATCH_CHUNK_SIZE: usize = 256;
+/// Shard count for [`QdrantService::upsert_locks`]. Fixed-size rather than
+/// one lock per point ID so the lock table can't grow without bound across
+/// the life of the process.
+const UPSERT_LOCK_SHARDS: usize = 128;
+/// RRF constant: fused_score = Σ weight/(k + rank), rank 1-based within each list.
+const RRF_K: f64 = 60.0;
+
+/// Current schema version for memory collections. Bump this when the
+/// collection layout changes in a way that needs detection/migration.
+const SCHEMA_VERSION: u32 = 1;
+/// Well-known point ID for the sidecar `_schema` marker stored in every
+/// memory collection, so its recorded dimension/distance survives restarts
+/// and config changes can be detected instead of silently corrupting reads.
+const SCHEMA_MARKER_ID: &str = "__schema_marker__";
+
+/// The compatibility-relevant parts of a collection's schema, stored as the
+/// payload of the `_schema` marker point created alongside the collection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CollectionSchema {
+    version: u32,
+    vector_dimension: u64,
+    distance: String,
+}
+
+/// Partial stats accumulated by one shard of
+/// [`QdrantService::get_document_stats_parallel`]. Every field is merged via
+/// simple sum/union, so combining partials is commutative and associative —
+/// the final [`DocumentStatsResponse`] doesn't depend on the order shards
+/// finish in.
+#[derive(Debug, Default)]
+struct StatsPartial {
+    total_chunks: u64,
+    file_ids: std::collections::HashSet<i64>,
+    chunks_by_group: HashMap<String, u64>,
+    chunks_by_content_hash: HashMap<String, (u64, u64)>,
+}
+
+impl StatsPartial {
+    fn merge(mut self, other: StatsPartial) -> Self {
+        self.total_chunks += other.total_chunks;
+        self.file_ids.extend(other.file_ids);
+        for (key, count) in other.chunks_by_group {
+            *self.chunks_by_group.entry(key).or_insert(0) += count;
+        }
+        for (key, (count, byte_len)) in other.chunks_by_content_hash {
+            let entry = self.chunks_by_content_hash.entry(key).or_insert((0, byte_len));
+            entry.0 += count;
+        }
+        self
+    }
+}
+
+/// One memory to upsert as part of [`QdrantService::upsert_memories_batch`].
+pub struct MemoryUpsertItem {
+    pub point_id: String,
+    pub vector: Vec<f32>,
+    pub sparse_vector: Option<SparseVector>,
+    pub payload: MemoryPayload,
+    pub namespace: Option<String>,
+}
+
+/// One document chunk to upsert as part of
+/// [`QdrantService::upsert_documents_batch`].
+pub struct DocumentUpsertItem {
+    pub point_id: String,
+    pub vector: Vec<f32>,
+    pub sparse_vector: Option<SparseVector>,
+    pub payload: DocumentPayload,
+}
+
 /// Convert string ID to numeric ID using consistent hashing
 #[inline]
 fn string_to_point_id(point_id: &str) -> u64 {
@@ -20,11 +104,181 @@ fn string_to_point_id(point_id: &str) -> u64 {
     hasher.finish()
 }
 
+/// Encode a Qdrant scroll `next_page_offset` as the opaque cursor string
+/// handed back to API callers as `next_offset`.
+#[inline]
+fn point_id_to_cursor(id: PointId) -> String {
+    match id.point_id_options {
+        Some(PointIdOptions::Num(num)) => num.to_string(),
+        Some(PointIdOptions::Uuid(uuid)) => uuid,
+        None => String::new(),
+    }
+}
+
+/// Inverse of [`point_id_to_cursor`]: parse an API-supplied cursor back into
+/// the `PointId` Qdrant's scroll `offset` expects. This service's point IDs
+/// are always hashed to a `u64` via [`string_to_point_id`], so cursors are
+/// numeric in practice; a non-numeric cursor is treated as a UUID point ID
+/// for forward compatibility.
+#[inline]
+fn cursor_to_point_id(cursor: &str) -> PointId {
+    let point_id_options = match cursor.parse::<u64>() {
+        Ok(num) => PointIdOptions::Num(num),
+        Err(_) => PointIdOptions::Uuid(cursor.to_string()),
+    };
+    PointId {
+        point_id_options: Some(point_id_options),
+    }
+}
+
+/// Parses an optional [`crate::filter`] DSL string and, if present, ANDs it
+/// into `conditions` as one more condition alongside the caller's own
+/// mandatory filters (`user_id`, `active`, etc).
+fn push_filter_expr(conditions: &mut Vec<Condition>, filter_expr: Option<&str>) -> Result<(), AppError> {
+    if let Some(expr) = filter_expr {
+        conditions.push(Condition::from(crate::filter::parse_filter(expr)?));
+    }
+    Ok(())
+}
+
+/// Decrypts a payload field for dedup/stats hashing, outside the `&self`
+/// encrypt/decrypt helpers so shard-local static functions (which only carry
+/// a cloned `Option<PayloadCipher>`) can use it too. Passes the text through
+/// unchanged when encryption is disabled or the field predates it.
+fn decrypt_text_field(
+    cipher: Option<&crate::crypto::PayloadCipher>,
+    user_id: i64,
+    text: &str,
+) -> Result<String, AppError> {
+    match cipher {
+        Some(cipher) => cipher.decrypt_field(user_id, text),
+        None => Ok(text.to_string()),
+    }
+}
+
+/// Reciprocal Rank Fusion contribution of a single list for a 1-based rank
+/// (i.e. the first result in a list passes `rank = 1`), scaled by that
+/// list's fusion weight (`1.0` for equally-weighted lists).
+#[inline]
+fn rrf_score(rank: usize, weight: f64) -> f64 {
+    weight / (RRF_K + rank as f64)
+}
+
+/// Extracts the original string point ID and deserializes the payload of a
+/// `ScoredPoint` returned by `search_points`, the shared tail end of every
+/// memory search path (dense or sparse).
+fn scored_point_to_memory(
+    scored_point: qdrant_client::qdrant::ScoredPoint,
+) -> Result<(String, f32, MemoryPayload), AppError> {
+    let point_id = scored_point
+        .payload
+        .get("_point_id")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| match scored_point.id {
+            Some(id) => match id.point_id_options {
+                Some(PointIdOptions::Num(num)) => num.to_string(),
+                Some(PointIdOptions::Uuid(uuid)) => uuid,
+                None => "unknown".to_string(),
+            },
+            None => "unknown".to_string(),
+        });
+
+    let payload_json = serde_json::to_value(&scored_point.payload)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize payload: {}", e)))?;
+
+    let memory_payload: MemoryPayload = serde_json::from_value(payload_json)
+        .map_err(|e| AppError::Internal(format!("Failed to deserialize payload: {}", e)))?;
+
+    Ok((point_id, scored_point.score, memory_payload))
+}
+
+/// Document-flavored counterpart to [`scored_point_to_memory`]: extracts the
+/// original string point ID and deserializes the payload of a `ScoredPoint`.
+fn document_scored_point(
+    scored_point: qdrant_client::qdrant::ScoredPoint,
+) -> Result<(String, DocumentPayload), AppError> {
+    let id = scored_point
+        .payload
+        .get("_point_id")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+
+    let payload_json = serde_json::to_value(&scored_point.payload)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize payload: {}", e)))?;
+
+    let payload: DocumentPayload = serde_json::from_value(payload_json)
+        .map_err(|e| AppError::Internal(format!("Failed to deserialize payload: {}", e)))?;
+
+    Ok((id, payload))
+}
+
+/// Lexical-match counterpart to [`scored_point_to_memory`] for `scroll`
+/// results: a full-text filter match is boolean (matched the payload index
+/// or didn't), so `RetrievedPoint` carries no relevance score to extract.
+fn retrieved_point_to_memory(
+    point: qdrant_client::qdrant::RetrievedPoint,
+) -> Result<(String, MemoryPayload), AppError> {
+    let point_id = point
+        .payload
+        .get("_point_id")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| match point.id {
+            Some(id) => match id.point_id_options {
+                Some(PointIdOptions::Num(num)) => num.to_string(),
+                Some(PointIdOptions::Uuid(uuid)) => uuid,
+                None => "unknown".to_string(),
+            },
+            None => "unknown".to_string(),
+        });
+
+    let payload_json = serde_json::to_value(&point.payload)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize payload: {}", e)))?;
+
+    let memory_payload: MemoryPayload = serde_json::from_value(payload_json)
+        .map_err(|e| AppError::Internal(format!("Failed to deserialize payload: {}", e)))?;
+
+    Ok((point_id, memory_payload))
+}
+
+/// Document-flavored counterpart to [`retrieved_point_to_memory`].
+fn retrieved_point_to_document(
+    point: qdrant_client::qdrant::RetrievedPoint,
+) -> Result<(String, DocumentPayload), AppError> {
+    let id = point
+        .payload
+        .get("_point_id")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+
+    let payload_json = serde_json::to_value(&point.payload)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize payload: {}", e)))?;
+
+    let payload: DocumentPayload = serde_json::from_value(payload_json)
+        .map_err(|e| AppError::Internal(format!("Failed to deserialize payload: {}", e)))?;
+
+    Ok((id, payload))
+}
+
 pub struct QdrantService {
     client: Qdrant,
     collection_name: String,
     documents_collection_name: String,
     vector_dimension: u64,
+    /// Encrypts/decrypts memory and document content at rest when
+    /// `config.memory_encryption_enabled` is set. `None` leaves content as
+    /// plaintext, matching pre-encryption behavior.
+    cipher: Option<crate::crypto::PayloadCipher>,
+    /// Serializes the read-current-version-then-write-new-version sequence
+    /// in [`Self::upsert_memory_with_sparse`], sharded by point ID so two
+    /// concurrent upserts to the *same* point can't both read the same
+    /// `current_version` and race past each other's optimistic-concurrency
+    /// check; unrelated point IDs landing in the same shard just serialize
+    /// unnecessarily rather than losing correctness.
+    upsert_locks: Vec<tokio::sync::Mutex<()>>,
 }
 
 impl QdrantService {
@@ -38,14 +292,35 @@ impl QdrantService {
 
         let client = client_builder.build()?;
 
+        let cipher = if config.memory_encryption_enabled {
+            let master_key = config.memory_encryption_master_key.as_deref().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "MEMORY_ENCRYPTION_MASTER_KEY must be set when memory_encryption_enabled is true"
+                )
+            })?;
+            Some(crate::crypto::PayloadCipher::from_master_key_base64(master_key)?)
+        } else {
+            None
+        };
+
         Ok(Self {
             client,
             collection_name: config.collection_name.clone(),
             documents_collection_name: config.documents_collection_name.clone(),
             vector_dimension: config.vector_dimension,
+            cipher,
+            upsert_locks: (0..UPSERT_LOCK_SHARDS).map(|_| tokio::sync::Mutex::new(())).collect(),
         })
     }
 
+    /// Picks the shard of [`Self::upsert_locks`] that serializes upserts to
+    /// `point_id`, so callers can hold it across the read-modify-write in
+    /// [`Self::upsert_memory_with_sparse`].
+    fn upsert_lock_for(&self, point_id: &str) -> &tokio::sync::Mutex<()> {
+        let shard = (string_to_point_id(point_id) as usize) % self.upsert_locks.len();
+        &self.upsert_locks[shard]
+    }
+
     pub async fn ensure_collection_exists(&self) -> Result<(), AppError> {
         self.ensure_collection_exists_for(&self.collection_name).await?;
         self.ensure_collection_exists_for_documents(&self.documents_collection_name).await?;
@@ -63,16 +338,24 @@ impl QdrantService {
         if !exists {
             info!("Creating documents collection '{}'", collection_name);
 
+            let mut sparse_vectors_config = HashMap::new();
+            sparse_vectors_config.insert(
+                SPARSE_VECTOR_NAME.to_string(),
+                SparseVectorParamsBuilder::default().build(),
+            );
+
             self.client
                 .create_collection(
-                    CreateCollectionBuilder::new(collection_name).vectors_config(
-                        VectorParamsBuilder::new(self.vector_dimension, Distance::Cosine)
-                            .hnsw_config(HnswConfigDiff {
-                                m: Some(16),
-                                ef_construct: Some(100),
-                                ..Default::default()
-                            }),
-                    ),
+                    CreateCollectionBuilder::new(collection_name)
+                        .vectors_config(
+                            VectorParamsBuilder::new(self.vector_dimension, Distance::Cosine)
+                                .hnsw_config(HnswConfigDiff {
+                                    m: Some(16),
+                                    ef_construct: Some(100),
+                                    ..Default::default()
+                                }),
+                        )
+                        .sparse_vectors_config(sparse_vectors_config),
                 )
                 .await?;
 
@@ -80,6 +363,20 @@ impl QdrantService {
             self.client.create_field_index(collection_name, "user_id", FieldType::Integer, None, None).await?;
             self.client.create_field_index(collection_name, "file_id", FieldType::Integer, None, None).await?;
             self.client.create_field_index(collection_name, "group_key", FieldType::Keyword, None, None).await?;
+            // Backs the lexical leg of `search_documents_semantic_lexical`:
+            // full-text payload matches require an explicit text index. When
+            // encryption is enabled `text` is ciphertext by the time it's
+            // upserted, so a full-text index over it can never match a
+            // plaintext query term - skip it rather than build a useless
+            // index over encrypted data.
+            if self.cipher.is_some() {
+                warn!(
+                    "Skipping full-text index on '{}'.text: memory_encryption_enabled makes this field ciphertext, so lexical/hybrid search will not find matches in it",
+                    collection_name
+                );
+            } else {
+                self.client.create_field_index(collection_name, "text", FieldType::Text, None, None).await?;
+            }
 
             info!("Documents collection '{}' created successfully", collection_name);
         } else {
@@ -100,29 +397,415 @@ impl QdrantService {
         if !exists {
             info!("Creating collection '{}'", collection_name);
 
+            let mut sparse_vectors_config = HashMap::new();
+            sparse_vectors_config.insert(
+                SPARSE_VECTOR_NAME.to_string(),
+                SparseVectorParamsBuilder::default().build(),
+            );
+
             self.client
                 .create_collection(
-                    CreateCollectionBuilder::new(collection_name).vectors_config(
-                        VectorParamsBuilder::new(self.vector_dimension, Distance::Cosine),
-                    ),
+                    CreateCollectionBuilder::new(collection_name)
+                        .vectors_config(VectorParamsBuilder::new(
+                            self.vector_dimension,
+                            Distance::Cosine,
+                        ))
+                        .sparse_vectors_config(sparse_vectors_config),
                 )
                 .await?;
 
+            // Backs the lexical leg of `search_memories_semantic_lexical`:
+            // full-text payload matches require an explicit text index. When
+            // encryption is enabled `value` is ciphertext by the time it's
+            // upserted, so a full-text index over it can never match a
+            // plaintext query term - skip it rather than build a useless
+            // index over encrypted data.
+            if self.cipher.is_some() {
+                warn!(
+                    "Skipping full-text index on '{}'.value: memory_encryption_enabled makes this field ciphertext, so lexical/hybrid search will not find matches in it",
+                    collection_name
+                );
+            } else {
+                self.client
+                    .create_field_index(collection_name, "value", FieldType::Text, None, None)
+                    .await?;
+            }
+
+            self.write_schema_marker(collection_name, self.vector_dimension)
+                .await?;
+
             info!("Collection '{}' created successfully", collection_name);
         } else {
             debug!("Collection '{}' already exists", collection_name);
+
+            if let Some(schema) = self.read_schema_marker(collection_name).await? {
+                if schema.vector_dimension != self.vector_dimension {
+                    return Err(AppError::SchemaMismatch {
+                        collection: collection_name.to_string(),
+                        expected_dimension: self.vector_dimension,
+                        found_dimension: schema.vector_dimension,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes (or overwrites) the `_schema` marker point recording this
+    /// collection's vector dimension, so a later config change that shrinks
+    /// or grows it can be detected instead of silently producing garbage
+    /// search results.
+    async fn write_schema_marker(
+        &self,
+        collection_name: &str,
+        vector_dimension: u64,
+    ) -> Result<(), AppError> {
+        let schema = CollectionSchema {
+            version: SCHEMA_VERSION,
+            vector_dimension,
+            distance: "Cosine".to_string(),
+        };
+        let payload_value = serde_json::to_value(&schema)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize schema marker: {}", e)))?;
+        let payload = Payload::try_from(payload_value)
+            .map_err(|e| AppError::Internal(format!("Failed to convert schema marker: {}", e)))?;
+
+        let pid = PointId {
+            point_id_options: Some(PointIdOptions::Num(string_to_point_id(SCHEMA_MARKER_ID))),
+        };
+        let point = PointStruct::new(pid, vec![0.0f32; vector_dimension as usize], payload);
+
+        self.client
+            .upsert_points(UpsertPointsBuilder::new(collection_name, vec![point]))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Reads back the `_schema` marker written by [`Self::write_schema_marker`],
+    /// if the collection has one (collections created before this subsystem
+    /// existed won't, and are treated as compatible rather than erroring).
+    async fn read_schema_marker(
+        &self,
+        collection_name: &str,
+    ) -> Result<Option<CollectionSchema>, AppError> {
+        let pid = PointId {
+            point_id_options: Some(PointIdOptions::Num(string_to_point_id(SCHEMA_MARKER_ID))),
+        };
+
+        let response = self
+            .client
+            .get_points(GetPointsBuilder::new(collection_name, vec![pid]).with_payload(true))
+            .await?;
+
+        match response.result.first() {
+            Some(point) => {
+                let payload_json = serde_json::to_value(&point.payload).map_err(|e| {
+                    AppError::Internal(format!("Failed to serialize schema marker: {}", e))
+                })?;
+                let schema: CollectionSchema =
+                    serde_json::from_value(payload_json).map_err(|e| {
+                        AppError::Internal(format!("Failed to deserialize schema marker: {}", e))
+                    })?;
+                Ok(Some(schema))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Validates a dimension inferred via `Embedder::probe_dimension` against
+    /// both this service's configured `vector_dimension` and, if
+    /// `collection_name` already has one, its recorded `_schema` marker.
+    /// Meant to run at startup so a model/config mismatch fails fast instead
+    /// of surfacing later as Qdrant insert errors.
+    pub async fn verify_embedding_dimension(
+        &self,
+        collection_name: &str,
+        inferred_dimension: usize,
+    ) -> Result<(), AppError> {
+        let inferred_dimension = inferred_dimension as u64;
+
+        if inferred_dimension != self.vector_dimension {
+            return Err(AppError::SchemaMismatch {
+                collection: collection_name.to_string(),
+                expected_dimension: self.vector_dimension,
+                found_dimension: inferred_dimension,
+            });
+        }
+
+        if let Some(schema) = self.read_schema_marker(collection_name).await? {
+            if schema.vector_dimension != inferred_dimension {
+                return Err(AppError::SchemaMismatch {
+                    collection: collection_name.to_string(),
+                    expected_dimension: inferred_dimension,
+                    found_dimension: schema.vector_dimension,
+                });
+            }
         }
 
         Ok(())
     }
 
+    /// Scrolls every point out of `source_collection_name`, re-embeds its
+    /// stored payload via `reembed_fn`, and upserts the result into
+    /// `staging_collection_name`. Upserts are keyed by point ID, so calling
+    /// this more than once over the same source/staging pair is idempotent
+    /// and safe - [`Self::migrate_collection`] relies on that to re-copy a
+    /// collection until it stops growing before cutting over. Returns the
+    /// number of points processed in this pass.
+    async fn copy_scroll_pass<F, Fut>(
+        &self,
+        source_collection_name: &str,
+        staging_collection_name: &str,
+        new_dimension: u64,
+        reembed_fn: &F,
+    ) -> Result<u64, AppError>
+    where
+        F: Fn(MemoryPayload) -> Fut,
+        Fut: std::future::Future<Output = Result<Vec<f32>, AppError>>,
+    {
+        let mut migrated = 0u64;
+        let mut offset = None;
+
+        loop {
+            let mut builder = ScrollPointsBuilder::new(source_collection_name)
+                .limit(256)
+                .with_payload(true)
+                .with_vectors(false);
+            if let Some(o) = offset.take() {
+                builder = builder.offset(o);
+            }
+
+            let scroll_result = self.client.scroll(builder).await?;
+
+            for point in &scroll_result.result {
+                let payload = point.payload.clone();
+                let Some(point_id) = payload
+                    .get("_point_id")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+                else {
+                    // Skips the `_schema` marker itself, which carries no `_point_id`.
+                    continue;
+                };
+
+                let payload_json = serde_json::to_value(&payload).map_err(|e| {
+                    AppError::Internal(format!("Failed to serialize payload: {}", e))
+                })?;
+                let memory_payload: MemoryPayload =
+                    serde_json::from_value(payload_json).map_err(|e| {
+                        AppError::Internal(format!("Failed to deserialize payload: {}", e))
+                    })?;
+                // `reembed_fn` needs the plaintext to re-tokenize, and the
+                // staging collection should be encrypted exactly like the
+                // original so migration doesn't change the encryption
+                // posture of a point.
+                let memory_payload = self.decrypt_memory_payload(memory_payload)?;
+
+                let new_vector = reembed_fn(memory_payload.clone()).await?;
+                if new_vector.len() != new_dimension as usize {
+                    return Err(AppError::InvalidRequest(format!(
+                        "Re-embedding returned dimension {}, expected {}",
+                        new_vector.len(),
+                        new_dimension
+                    )));
+                }
+
+                let memory_payload = self.encrypt_memory_payload(memory_payload)?;
+                let payload_map_value = serde_json::to_value(&memory_payload).map_err(|e| {
+                    AppError::Internal(format!("Failed to serialize payload: {}", e))
+                })?;
+                let mut payload_map = payload_map_value.as_object().unwrap().clone();
+                payload_map.insert(
+                    "_point_id".to_string(),
+                    serde_json::Value::String(point_id.clone()),
+                );
+                let payload_qdrant = Payload::try_from(serde_json::Value::Object(payload_map))
+                    .map_err(|e| AppError::Internal(format!("Failed to convert payload: {}", e)))?;
+
+                let new_pid = PointId {
+                    point_id_options: Some(PointIdOptions::Num(string_to_point_id(&point_id))),
+                };
+                let new_point = PointStruct::new(new_pid, new_vector, payload_qdrant);
+
+                self.client
+                    .upsert_points(UpsertPointsBuilder::new(
+                        staging_collection_name,
+                        vec![new_point],
+                    ))
+                    .await?;
+
+                migrated += 1;
+            }
+
+            offset = scroll_result.next_page_offset;
+            if offset.is_none() {
+                break;
+            }
+        }
+
+        Ok(migrated)
+    }
+
+    /// Migrates a namespace's memory collection to a new vector dimension
+    /// online: scrolls every point out of the old collection, re-embeds its
+    /// stored payload via the caller-supplied `reembed_fn` (so this module
+    /// doesn't need to know about any particular embedding backend), upserts
+    /// the result into a freshly created collection at `new_dimension`, then
+    /// swaps it in under the old collection's logical name via a Qdrant
+    /// alias. Existing code that looks up the collection by its logical name
+    /// (via [`Self::get_collection_name`]) keeps working unchanged once the
+    /// swap completes. Returns the number of points migrated.
+    pub async fn migrate_collection<F, Fut>(
+        &self,
+        namespace: Option<&str>,
+        new_dimension: u64,
+        reembed_fn: F,
+    ) -> Result<u64, AppError>
+    where
+        F: Fn(MemoryPayload) -> Fut,
+        Fut: std::future::Future<Output = Result<Vec<f32>, AppError>>,
+    {
+        let old_collection_name = self.get_collection_name(namespace);
+        let staging_collection_name =
+            format!("{}__migrating_v{}", old_collection_name, SCHEMA_VERSION);
+
+        let mut sparse_vectors_config = HashMap::new();
+        sparse_vectors_config.insert(
+            SPARSE_VECTOR_NAME.to_string(),
+            SparseVectorParamsBuilder::default().build(),
+        );
+        self.client
+            .create_collection(
+                CreateCollectionBuilder::new(&staging_collection_name)
+                    .vectors_config(VectorParamsBuilder::new(new_dimension, Distance::Cosine))
+                    .sparse_vectors_config(sparse_vectors_config),
+            )
+            .await?;
+
+        let mut migrated = self
+            .copy_scroll_pass(&old_collection_name, &staging_collection_name, new_dimension, &reembed_fn)
+            .await?;
+
+        // The pass above can take a while on a large collection, during
+        // which writes keep landing on `old_collection_name`. Re-scroll and
+        // re-copy until a pass sees the same point count as the one before
+        // it, so points written mid-migration aren't silently dropped when
+        // `old_collection_name` is deleted below. Each pass re-copies the
+        // whole collection rather than just the delta, but that's safe and
+        // idempotent since upserts are keyed by point ID. Bounded so a
+        // collection under sustained write load can't keep this from ever
+        // converging; if it doesn't, we proceed anyway and log so an
+        // operator can check for points written in that last window.
+        const MAX_DELTA_PASSES: u32 = 5;
+        for pass in 1..=MAX_DELTA_PASSES {
+            let recopied = self
+                .copy_scroll_pass(&old_collection_name, &staging_collection_name, new_dimension, &reembed_fn)
+                .await?;
+            let converged = recopied == migrated;
+            migrated = recopied;
+            if converged {
+                break;
+            }
+            if pass == MAX_DELTA_PASSES {
+                warn!(
+                    "Migration of '{}' did not converge after {} delta passes; swapping anyway, points written in the last pass's window may be lost",
+                    old_collection_name, MAX_DELTA_PASSES
+                );
+            }
+        }
+
+        self.write_schema_marker(&staging_collection_name, new_dimension)
+            .await?;
+
+        // Swap: drop the old physical collection, then alias the logical
+        // name to the staging collection so callers using `get_collection_name`
+        // keep resolving to a working collection. There's a brief window
+        // between these two calls where the logical name resolves to
+        // nothing; Qdrant's gRPC API doesn't offer a single atomic rename,
+        // and a plain collection (as opposed to an alias) can't be
+        // repointed without deleting it first. The calls are kept
+        // back-to-back with nothing else in between to keep that window as
+        // short as possible.
+        self.client.delete_collection(&old_collection_name).await?;
+        self.client
+            .create_alias(&old_collection_name, &staging_collection_name)
+            .await?;
+
+        info!(
+            "Migrated {} points from '{}' to dimension {}",
+            migrated, old_collection_name, new_dimension
+        );
+
+        Ok(migrated)
+    }
+
+    /// Encrypts `key`/`value` in place when encryption is enabled; a no-op
+    /// otherwise, so callers can apply it unconditionally.
+    fn encrypt_memory_payload(&self, mut payload: MemoryPayload) -> Result<MemoryPayload, AppError> {
+        let Some(cipher) = &self.cipher else {
+            return Ok(payload);
+        };
+        payload.key = cipher.encrypt_field(payload.user_id, &payload.key)?;
+        payload.value = cipher.encrypt_field(payload.user_id, &payload.value)?;
+        Ok(payload)
+    }
+
+    /// Decrypts `key`/`value` in place when encryption is enabled. Plaintext
+    /// fields (written before encryption was enabled) pass through
+    /// unchanged, so this is also safe to apply unconditionally.
+    fn decrypt_memory_payload(&self, mut payload: MemoryPayload) -> Result<MemoryPayload, AppError> {
+        let Some(cipher) = &self.cipher else {
+            return Ok(payload);
+        };
+        payload.key = cipher.decrypt_field(payload.user_id, &payload.key)?;
+        payload.value = cipher.decrypt_field(payload.user_id, &payload.value)?;
+        Ok(payload)
+    }
+
+    /// Encrypts `text` in place when encryption is enabled; a no-op
+    /// otherwise.
+    fn encrypt_document_payload(&self, mut payload: DocumentPayload) -> Result<DocumentPayload, AppError> {
+        let Some(cipher) = &self.cipher else {
+            return Ok(payload);
+        };
+        payload.text = cipher.encrypt_field(payload.user_id, &payload.text)?;
+        Ok(payload)
+    }
+
+    /// Decrypts `text` in place when encryption is enabled; passes plaintext
+    /// through unchanged otherwise.
+    fn decrypt_document_payload(&self, mut payload: DocumentPayload) -> Result<DocumentPayload, AppError> {
+        let Some(cipher) = &self.cipher else {
+            return Ok(payload);
+        };
+        payload.text = cipher.decrypt_field(payload.user_id, &payload.text)?;
+        Ok(payload)
+    }
+
     pub async fn upsert_memory(
         &self,
         point_id: String,
         vector: Vec<f32>,
         payload: MemoryPayload,
         namespace: Option<&str>,
-    ) -> Result<(), AppError> {
+        expected_version: Option<i64>,
+    ) -> Result<i64, AppError> {
+        self.upsert_memory_with_sparse(point_id, vector, None, payload, namespace, expected_version)
+            .await
+    }
+
+    /// Validates the vector dimension, encrypts the payload, and builds the
+    /// `PointStruct` a memory upsert sends to Qdrant. Shared by the
+    /// single-point and batch upsert paths so both stay in sync.
+    fn build_memory_point(
+        &self,
+        point_id: &str,
+        vector: Vec<f32>,
+        sparse_vector: Option<SparseVector>,
+        payload: MemoryPayload,
+    ) -> Result<PointStruct, AppError> {
         if vector.len() != self.vector_dimension as usize {
             return Err(AppError::InvalidRequest(format!(
                 "Vector dimension mismatch: expected {}, got {}",
@@ -131,22 +814,77 @@ impl QdrantService {
             )));
         }
 
+        let payload = self.encrypt_memory_payload(payload)?;
+
         let payload_map = serde_json::to_value(&payload)
             .map_err(|e| AppError::Internal(format!("Failed to serialize payload: {}", e)))?;
 
         // Add the original point_id to the payload for later retrieval
         let mut payload_map = payload_map.as_object().unwrap().clone();
-        payload_map.insert("_point_id".to_string(), serde_json::Value::String(point_id.clone()));
+        payload_map.insert("_point_id".to_string(), serde_json::Value::String(point_id.to_string()));
 
         let payload_qdrant = Payload::try_from(serde_json::Value::Object(payload_map))
             .map_err(|e| AppError::Internal(format!("Failed to convert payload: {}", e)))?;
 
-        let numeric_id = string_to_point_id(&point_id);
         let pid = PointId {
-            point_id_options: Some(PointIdOptions::Num(numeric_id)),
+            point_id_options: Some(PointIdOptions::Num(string_to_point_id(point_id))),
         };
 
-        let point = PointStruct::new(pid, vector, payload_qdrant);
+        Ok(match sparse_vector {
+            Some(sparse) => {
+                let vectors = NamedVectors::default()
+                    .add_vector("", Vector::new_dense(vector))
+                    .add_vector(
+                        SPARSE_VECTOR_NAME,
+                        Vector::new_sparse(sparse.indices, sparse.values),
+                    );
+                PointStruct::new(pid, vectors, payload_qdrant)
+            }
+            None => PointStruct::new(pid, vector, payload_qdrant),
+        })
+    }
+
+    /// Upsert a memory with an optional sparse (lexical) vector alongside the
+    /// dense one, so it can later be found via `search_memories_hybrid`.
+    ///
+    /// Every upsert bumps the point's `version` (0 for a point that doesn't
+    /// exist yet). If `expected_version` is supplied, the write is rejected
+    /// with `AppError::Conflict` unless it matches the point's current
+    /// version, giving callers optimistic-concurrency read-modify-write
+    /// without a distributed lock. The read-then-write is itself serialized
+    /// per point ID via [`Self::upsert_lock_for`], so two concurrent
+    /// requests racing on the same `expected_version` can't both pass the
+    /// check and clobber each other. Returns the new version on success.
+    pub async fn upsert_memory_with_sparse(
+        &self,
+        point_id: String,
+        vector: Vec<f32>,
+        sparse_vector: Option<SparseVector>,
+        mut payload: MemoryPayload,
+        namespace: Option<&str>,
+        expected_version: Option<i64>,
+    ) -> Result<i64, AppError> {
+        let _upsert_guard = self.upsert_lock_for(&point_id).lock().await;
+
+        let current_version = self
+            .get_memory(&point_id, namespace)
+            .await?
+            .map(|existing| existing.version)
+            .unwrap_or(0);
+
+        if let Some(expected) = expected_version {
+            if expected != current_version {
+                return Err(AppError::Conflict(format!(
+                    "Memory '{}' expected version {} but found {}",
+                    point_id, expected, current_version
+                )));
+            }
+        }
+
+        let new_version = current_version + 1;
+        payload.version = new_version;
+
+        let point = self.build_memory_point(&point_id, vector, sparse_vector, payload)?;
 
         let collection_name = self.get_collection_name(namespace);
         self.ensure_collection_exists_for(&collection_name).await?;
@@ -158,9 +896,67 @@ impl QdrantService {
             )
             .await?;
 
-        debug!("Memory upserted: {} (numeric: {})", point_id, numeric_id);
+        debug!("Memory upserted: {} (version {})", point_id, new_version);
 
-        Ok(())
+        Ok(new_version)
+    }
+
+    /// Upsert many memories in as few round trips as possible. Dimension
+    /// mismatches are reported per point without touching Qdrant; valid
+    /// points are grouped by namespace (namespace selects the physical
+    /// collection) and sent in chunks of [`BATCH_CHUNK_SIZE`] via a single
+    /// `UpsertPointsBuilder` call each, instead of one round trip per point.
+    pub async fn upsert_memories_batch(
+        &self,
+        items: Vec<MemoryUpsertItem>,
+    ) -> Vec<(String, Result<(), AppError>)> {
+        let mut results = Vec::with_capacity(items.len());
+        let mut by_namespace: HashMap<Option<String>, Vec<(String, PointStruct)>> = HashMap::new();
+
+        for item in items {
+            match self.build_memory_point(&item.point_id, item.vector, item.sparse_vector, item.payload) {
+                Ok(point) => by_namespace
+                    .entry(item.namespace)
+                    .or_default()
+                    .push((item.point_id, point)),
+                Err(e) => results.push((item.point_id, Err(e))),
+            }
+        }
+
+        for (namespace, points) in by_namespace {
+            let collection_name = self.get_collection_name(namespace.as_deref());
+            if let Err(e) = self.ensure_collection_exists_for(&collection_name).await {
+                let message = e.to_string();
+                results.extend(points.into_iter().map(|(id, _)| (id, Err(AppError::Internal(message.clone())))));
+                continue;
+            }
+
+            let mut points = points.into_iter().peekable();
+            while points.peek().is_some() {
+                let (chunk_ids, chunk_points): (Vec<String>, Vec<PointStruct>) =
+                    points.by_ref().take(BATCH_CHUNK_SIZE).unzip();
+
+                match self
+                    .client
+                    .upsert_points(UpsertPointsBuilder::new(&collection_name, chunk_points))
+                    .await
+                {
+                    Ok(_) => results.extend(chunk_ids.into_iter().map(|id| (id, Ok(())))),
+                    Err(e) => {
+                        let message = AppError::from(e).to_string();
+                        results.extend(
+                            chunk_ids
+                                .into_iter()
+                                .map(|id| (id, Err(AppError::Internal(message.clone())))),
+                        );
+                    }
+                }
+            }
+        }
+
+        debug!("Batch upserted {} memories", results.len());
+
+        results
     }
 
     pub async fn get_memory(&self, point_id: &str, namespace: Option<&str>) -> Result<Option<MemoryPayload>, AppError> {
@@ -190,12 +986,68 @@ impl QdrantService {
                     AppError::Internal(format!("Failed to deserialize payload: {}", e))
                 })?;
 
-            return Ok(Some(memory_payload));
+            return Ok(Some(self.decrypt_memory_payload(memory_payload)?));
         }
 
         Ok(None)
     }
 
+    /// Fetch many memories by ID in a single Qdrant `retrieve` call instead
+    /// of one `get_memory` round trip per ID. Returns the points found
+    /// alongside which of the requested IDs had no matching point.
+    pub async fn get_memories(
+        &self,
+        point_ids: &[String],
+        namespace: Option<&str>,
+    ) -> Result<(Vec<(String, MemoryPayload)>, Vec<String>), AppError> {
+        let collection_name = self.get_collection_name(namespace);
+
+        let pids: Vec<PointId> = point_ids
+            .iter()
+            .map(|id| PointId {
+                point_id_options: Some(PointIdOptions::Num(string_to_point_id(id))),
+            })
+            .collect();
+
+        let response = self
+            .client
+            .get_points(GetPointsBuilder::new(&collection_name, pids).with_payload(true))
+            .await?;
+
+        let mut found = Vec::with_capacity(response.result.len());
+        let mut found_ids = std::collections::HashSet::with_capacity(response.result.len());
+
+        for point in response.result {
+            let payload_json = serde_json::to_value(&point.payload).map_err(|e| {
+                AppError::Internal(format!("Failed to serialize payload: {}", e))
+            })?;
+            let memory_payload: MemoryPayload = serde_json::from_value(payload_json)
+                .map_err(|e| {
+                    AppError::Internal(format!("Failed to deserialize payload: {}", e))
+                })?;
+            let memory_payload = self.decrypt_memory_payload(memory_payload)?;
+
+            let Some(point_id) = point
+                .payload
+                .get("_point_id")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+            else {
+                continue;
+            };
+            found_ids.insert(point_id.clone());
+            found.push((point_id, memory_payload));
+        }
+
+        let missing = point_ids
+            .iter()
+            .filter(|id| !found_ids.contains(*id))
+            .cloned()
+            .collect();
+
+        Ok((found, missing))
+    }
+
     pub async fn search_memories(
         &self,
         query_vector: Vec<f32>,
@@ -204,6 +1056,7 @@ impl QdrantService {
         limit: u64,
         min_score: f32,
         namespace: Option<&str>,
+        filter_expr: Option<&str>,
     ) -> Result<Vec<(String, f32, MemoryPayload)>, AppError> {
         if query_vector.len() != self.vector_dimension as usize {
             return Err(AppError::InvalidRequest(format!(
@@ -222,6 +1075,7 @@ impl QdrantService {
         if let Some(cat) = category {
             must_conditions.push(Condition::matches("category", cat));
         }
+        push_filter_expr(&mut must_conditions, filter_expr)?;
 
         let filter = Filter {
             must: must_conditions,
@@ -268,6 +1122,7 @@ impl QdrantService {
                 .map_err(|e| {
                     AppError::Internal(format!("Failed to deserialize payload: {}", e))
                 })?;
+            let memory_payload = self.decrypt_memory_payload(memory_payload)?;
 
             results.push((point_id, scored_point.score, memory_payload));
         }
@@ -277,16 +1132,283 @@ impl QdrantService {
         Ok(results)
     }
 
-    pub async fn delete_memory(&self, point_id: &str, namespace: Option<&str>) -> Result<(), AppError> {
-        let numeric_id = string_to_point_id(point_id);
-        let pid = PointId {
-            point_id_options: Some(PointIdOptions::Num(numeric_id)),
+    /// Hybrid dense + sparse search over memories, fused with Reciprocal Rank
+    /// Fusion. Issues a dense query against the default vector and a sparse
+    /// query against the named `"sparse"` vector, then combines the two
+    /// ranked lists so a point that ranks well in either shows up near the
+    /// top, and one that ranks well in both ranks higher still.
+    pub async fn search_memories_hybrid(
+        &self,
+        query_vector: Vec<f32>,
+        query_sparse_vector: SparseVector,
+        user_id: i64,
+        category: Option<String>,
+        limit: u64,
+        min_score: f32,
+        namespace: Option<&str>,
+        filter_expr: Option<&str>,
+    ) -> Result<Vec<(String, f32, MemoryPayload, String)>, AppError> {
+        if query_vector.len() != self.vector_dimension as usize {
+            return Err(AppError::InvalidRequest(format!(
+                "Query vector dimension mismatch: expected {}, got {}",
+                self.vector_dimension,
+                query_vector.len()
+            )));
+        }
+
+        let mut must_conditions = vec![Condition::matches("user_id", user_id)];
+        must_conditions.push(Condition::matches("active", true));
+        if let Some(cat) = category {
+            must_conditions.push(Condition::matches("category", cat));
+        }
+        push_filter_expr(&mut must_conditions, filter_expr)?;
+        let filter = Filter {
+            must: must_conditions,
+            ..Default::default()
         };
 
+        // Over-fetch on each leg so fusion has enough candidates to rank
+        // before truncating to `limit`.
+        let fetch_limit = (limit * 4).max(limit);
         let collection_name = self.get_collection_name(namespace);
-        // Use DeletePointsBuilder
-        self.client
-            .delete_points(
+
+        // `min_score` is a similarity threshold on each underlying leg, not on
+        // the fused RRF score (which lives on a different, unrelated scale).
+        let dense_result = self
+            .client
+            .search_points(
+                SearchPointsBuilder::new(&collection_name, query_vector, fetch_limit)
+                    .filter(filter.clone())
+                    .score_threshold(min_score)
+                    .with_payload(true),
+            )
+            .await?;
+
+        let sparse_result = self
+            .client
+            .search_points(
+                SearchPointsBuilder::new(&collection_name, query_sparse_vector.values, fetch_limit)
+                    .sparse_indices(query_sparse_vector.indices)
+                    .vector_name(SPARSE_VECTOR_NAME)
+                    .filter(filter)
+                    .score_threshold(min_score)
+                    .with_payload(true),
+            )
+            .await?;
+
+        let dense_ranked = dense_result
+            .result
+            .into_iter()
+            .map(scored_point_to_memory)
+            .collect::<Result<Vec<_>, _>>()?;
+        let sparse_ranked = sparse_result
+            .result
+            .into_iter()
+            .map(scored_point_to_memory)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut fused: HashMap<String, (f64, MemoryPayload, bool, bool)> = HashMap::new();
+
+        for (rank, (point_id, _score, payload)) in dense_ranked.into_iter().enumerate() {
+            let entry = fused
+                .entry(point_id)
+                .or_insert((0.0, payload, false, false));
+            entry.0 += rrf_score(rank + 1, 1.0);
+            entry.2 = true;
+        }
+
+        for (rank, (point_id, _score, payload)) in sparse_ranked.into_iter().enumerate() {
+            let entry = fused
+                .entry(point_id)
+                .or_insert((0.0, payload, false, false));
+            entry.0 += rrf_score(rank + 1, 1.0);
+            entry.3 = true;
+        }
+
+        let mut results: Vec<(String, f32, MemoryPayload, String)> = fused
+            .into_iter()
+            .map(|(point_id, (score, payload, in_dense, in_sparse))| {
+                let modality = match (in_dense, in_sparse) {
+                    (true, true) => "hybrid",
+                    (true, false) => "dense",
+                    (false, true) => "sparse",
+                    (false, false) => unreachable!("fused entry always comes from one of the two lists"),
+                };
+                let payload = self.decrypt_memory_payload(payload)?;
+                Ok((point_id, score as f32, payload, modality.to_string()))
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        results.truncate(limit as usize);
+
+        debug!(
+            "Hybrid search found {} memories for user {}",
+            results.len(),
+            user_id
+        );
+
+        Ok(results)
+    }
+
+    /// Hybrid dense + lexical search over memories, fused with weighted
+    /// Reciprocal Rank Fusion. Unlike [`Self::search_memories_hybrid`]'s
+    /// sparse leg (a client-supplied sparse vector with its own similarity
+    /// score), the lexical leg here matches `query_text` against Qdrant's
+    /// full-text index on the `value` field via [`Self::ensure_collection_exists_for`]'s
+    /// text index — a full-text filter only reports match/no-match, so its
+    /// "rank" is approximated by Qdrant's own scroll order rather than a true
+    /// relevance ranking. `semantic_ratio` (0.0-1.0) weights the dense leg;
+    /// the lexical leg gets the remainder. The fused score is normalized into
+    /// 0.0-1.0 (the max a point can reach is ranking first in both legs).
+    ///
+    /// When `MEMORY_ENCRYPTION_ENABLED` is set, [`Self::ensure_collection_exists_for`]
+    /// never builds the `value` text index (it would index ciphertext), and
+    /// Qdrant errors on a text-match filter against an unindexed field. So
+    /// with a cipher configured, this skips the lexical leg entirely and
+    /// falls back to dense-only results instead of letting that filter hit
+    /// Qdrant.
+    pub async fn search_memories_semantic_lexical(
+        &self,
+        query_vector: Vec<f32>,
+        query_text: &str,
+        semantic_ratio: f32,
+        user_id: i64,
+        category: Option<String>,
+        limit: u64,
+        min_score: f32,
+        namespace: Option<&str>,
+        filter_expr: Option<&str>,
+    ) -> Result<Vec<(String, f32, MemoryPayload, String)>, AppError> {
+        if query_vector.len() != self.vector_dimension as usize {
+            return Err(AppError::InvalidRequest(format!(
+                "Query vector dimension mismatch: expected {}, got {}",
+                self.vector_dimension,
+                query_vector.len()
+            )));
+        }
+
+        let lexical_available = self.cipher.is_none();
+        let semantic_weight = if lexical_available { semantic_ratio.clamp(0.0, 1.0) as f64 } else { 1.0 };
+        let lexical_weight = 1.0 - semantic_weight;
+
+        let mut must_conditions = vec![Condition::matches("user_id", user_id)];
+        must_conditions.push(Condition::matches("active", true));
+        if let Some(cat) = category {
+            must_conditions.push(Condition::matches("category", cat));
+        }
+        push_filter_expr(&mut must_conditions, filter_expr)?;
+        let filter = Filter {
+            must: must_conditions.clone(),
+            ..Default::default()
+        };
+
+        let fetch_limit = (limit * 4).max(limit);
+        let collection_name = self.get_collection_name(namespace);
+
+        let dense_result = self
+            .client
+            .search_points(
+                SearchPointsBuilder::new(&collection_name, query_vector, fetch_limit)
+                    .filter(filter)
+                    .score_threshold(min_score)
+                    .with_payload(true),
+            )
+            .await?;
+
+        let lexical_result = if lexical_available {
+            let mut lexical_conditions = must_conditions;
+            lexical_conditions.push(Condition::matches_text("value", query_text.to_string()));
+            let lexical_filter = Filter {
+                must: lexical_conditions,
+                ..Default::default()
+            };
+            self.client
+                .scroll(
+                    ScrollPointsBuilder::new(&collection_name)
+                        .filter(lexical_filter)
+                        .limit(fetch_limit as u32)
+                        .with_payload(true)
+                        .with_vectors(false),
+                )
+                .await?
+                .result
+        } else {
+            warn!(
+                "Skipping lexical leg of hybrid search on '{}': memory_encryption_enabled has no text index to match against",
+                collection_name
+            );
+            Vec::new()
+        };
+
+        let dense_ranked = dense_result
+            .result
+            .into_iter()
+            .map(scored_point_to_memory)
+            .collect::<Result<Vec<_>, _>>()?;
+        let lexical_ranked = lexical_result
+            .into_iter()
+            .map(retrieved_point_to_memory)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut fused: HashMap<String, (f64, MemoryPayload, bool, bool)> = HashMap::new();
+
+        for (rank, (point_id, _score, payload)) in dense_ranked.into_iter().enumerate() {
+            let entry = fused
+                .entry(point_id)
+                .or_insert((0.0, payload, false, false));
+            entry.0 += rrf_score(rank + 1, semantic_weight);
+            entry.2 = true;
+        }
+
+        for (rank, (point_id, payload)) in lexical_ranked.into_iter().enumerate() {
+            let entry = fused
+                .entry(point_id)
+                .or_insert((0.0, payload, false, false));
+            entry.0 += rrf_score(rank + 1, lexical_weight);
+            entry.3 = true;
+        }
+
+        // Weights always sum to 1.0, so this is the constant max attainable
+        // score (ranking first in both legs), used to normalize into 0.0-1.0.
+        let max_possible = rrf_score(1, semantic_weight) + rrf_score(1, lexical_weight);
+
+        let mut results: Vec<(String, f32, MemoryPayload, String)> = fused
+            .into_iter()
+            .map(|(point_id, (score, payload, in_dense, in_lexical))| {
+                let modality = match (in_dense, in_lexical) {
+                    (true, true) => "hybrid",
+                    (true, false) => "semantic",
+                    (false, true) => "lexical",
+                    (false, false) => unreachable!("fused entry always comes from one of the two lists"),
+                };
+                let payload = self.decrypt_memory_payload(payload)?;
+                Ok((point_id, (score / max_possible) as f32, payload, modality.to_string()))
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        results.truncate(limit as usize);
+
+        debug!(
+            "Semantic+lexical search found {} memories for user {}",
+            results.len(),
+            user_id
+        );
+
+        Ok(results)
+    }
+
+    pub async fn delete_memory(&self, point_id: &str, namespace: Option<&str>) -> Result<(), AppError> {
+        let numeric_id = string_to_point_id(point_id);
+        let pid = PointId {
+            point_id_options: Some(PointIdOptions::Num(numeric_id)),
+        };
+
+        let collection_name = self.get_collection_name(namespace);
+        // Use DeletePointsBuilder
+        self.client
+            .delete_points(
                 DeletePointsBuilder::new(&collection_name)
                     .points(vec![pid])
             )
@@ -297,6 +1419,111 @@ impl QdrantService {
         Ok(())
     }
 
+    /// Deletes every point matching `filter` from `collection`, scrolling
+    /// and deleting in chunks of [`BATCH_CHUNK_SIZE`] rather than issuing a
+    /// single filter-based delete. This way a failure partway through
+    /// reports exactly how many points were confirmed deleted instead of
+    /// either silently claiming full success or losing the count entirely,
+    /// per Quickwit's "do not ignore storage errors silently when deleting"
+    /// fix.
+    async fn delete_points_matching(&self, collection: &str, filter: Filter) -> Result<u64, AppError> {
+        let mut deleted: u64 = 0;
+        let mut offset = None;
+
+        loop {
+            let scrolled = self
+                .client
+                .scroll(
+                    ScrollPointsBuilder::new(collection)
+                        .filter(filter.clone())
+                        .limit(BATCH_CHUNK_SIZE as u32)
+                        .offset(offset)
+                        .with_payload(false)
+                        .with_vectors(false),
+                )
+                .await
+                .map_err(|e| {
+                    AppError::Internal(format!(
+                        "{} point(s) confirmed deleted before scroll failed: {}",
+                        deleted,
+                        AppError::from(e)
+                    ))
+                })?;
+
+            if scrolled.result.is_empty() {
+                break;
+            }
+
+            let ids: Vec<PointId> = scrolled.result.into_iter().filter_map(|p| p.id).collect();
+            let batch_len = ids.len() as u64;
+
+            self.client
+                .delete_points(DeletePointsBuilder::new(collection).points(ids))
+                .await
+                .map_err(|e| {
+                    AppError::Internal(format!(
+                        "{} point(s) confirmed deleted before a delete batch failed: {}",
+                        deleted,
+                        AppError::from(e)
+                    ))
+                })?;
+
+            deleted += batch_len;
+            offset = scrolled.next_page_offset;
+            if offset.is_none() {
+                break;
+            }
+        }
+
+        Ok(deleted)
+    }
+
+    /// Delete every memory matching `user_id` (and `category`, if given),
+    /// returning the number of points deleted.
+    pub async fn delete_memories_by_category(
+        &self,
+        user_id: i64,
+        category: Option<String>,
+        namespace: Option<&str>,
+    ) -> Result<u64, AppError> {
+        let mut must_conditions = vec![Condition::matches("user_id", user_id)];
+        if let Some(cat) = category {
+            must_conditions.push(Condition::matches("category", cat));
+        }
+        let filter = Filter::must(must_conditions);
+
+        let collection_name = self.get_collection_name(namespace);
+        self.delete_points_matching(&collection_name, filter).await
+    }
+
+    /// Delete every memory belonging to `user_id`, returning the number of
+    /// points deleted. Used to purge a user's memories on account deletion.
+    pub async fn delete_all_memories_for_user(
+        &self,
+        user_id: i64,
+        namespace: Option<&str>,
+    ) -> Result<u64, AppError> {
+        let filter = Filter::must(vec![Condition::matches("user_id", user_id)]);
+        let collection_name = self.get_collection_name(namespace);
+        self.delete_points_matching(&collection_name, filter).await
+    }
+
+    /// Delete every memory matching `user_id` ANDed with a [`crate::filter`]
+    /// DSL expression, returning the number of points deleted.
+    pub async fn delete_memories_by_filter(
+        &self,
+        user_id: i64,
+        filter_expr: &str,
+        namespace: Option<&str>,
+    ) -> Result<u64, AppError> {
+        let mut must_conditions = vec![Condition::matches("user_id", user_id)];
+        push_filter_expr(&mut must_conditions, Some(filter_expr))?;
+        let filter = Filter::must(must_conditions);
+
+        let collection_name = self.get_collection_name(namespace);
+        self.delete_points_matching(&collection_name, filter).await
+    }
+
     pub async fn health_check(&self) -> Result<bool, AppError> {
         match self.client.health_check().await {
             Ok(_) => Ok(true),
@@ -318,24 +1545,161 @@ impl QdrantService {
 
         let status = format!("{:?}", info.status);
         let points_count = info.points_count.unwrap_or(0);
-        
-        // vectors_count and indexed_vectors_count don't exist in CollectionInfo
-        // Use points_count as approximation
+
+        // `vectors_count` was deprecated by Qdrant without a direct
+        // replacement, so it still approximates via points_count; but
+        // `indexed_vectors_count` is reported by the server and reflects how
+        // many vectors have actually finished HNSW indexing, which can lag
+        // `points_count` right after a burst of writes.
         let vectors_count = points_count;
-        let indexed_vectors_count = points_count;
+        let indexed_vectors_count = info.indexed_vectors_count.unwrap_or(points_count);
 
         Ok((status, points_count, vectors_count, indexed_vectors_count))
     }
 
-    /// Scroll (list) all memories for a user without vector search
-    /// Uses Qdrant scroll API to retrieve points with filtering
+    /// Exact count of memories matching the same `user_id`/`active`/category
+    /// filter used by [`Self::search_memories`] and [`Self::scroll_memories`],
+    /// via Qdrant's Count API rather than an approximation.
+    pub async fn count_memories(
+        &self,
+        user_id: i64,
+        category: Option<String>,
+        namespace: Option<&str>,
+    ) -> Result<u64, AppError> {
+        let mut must_conditions = vec![Condition::matches("user_id", user_id)];
+        must_conditions.push(Condition::matches("active", true));
+        if let Some(cat) = category {
+            must_conditions.push(Condition::matches("category", cat));
+        }
+
+        let filter = Filter {
+            must: must_conditions,
+            ..Default::default()
+        };
+
+        let collection_name = self.get_collection_name(namespace);
+        let response = self
+            .client
+            .count(
+                CountPointsBuilder::new(&collection_name)
+                    .filter(filter)
+                    .exact(true),
+            )
+            .await?;
+
+        let result = response
+            .result
+            .ok_or_else(|| AppError::Internal("Count result is empty".to_string()))?;
+
+        Ok(result.count)
+    }
+
+    /// Exact count of documents matching the same `user_id`/`group_key`
+    /// filter used by [`Self::search_documents`], via Qdrant's Count API.
+    pub async fn count_documents(
+        &self,
+        user_id: i64,
+        group_key: Option<&str>,
+    ) -> Result<u64, AppError> {
+        let mut conditions = vec![Condition::matches("user_id", user_id)];
+        if let Some(gk) = group_key {
+            conditions.push(Condition::matches("group_key", gk.to_string()));
+        }
+
+        let filter = Filter::must(conditions);
+
+        let response = self
+            .client
+            .count(
+                CountPointsBuilder::new(&self.documents_collection_name)
+                    .filter(filter)
+                    .exact(true),
+            )
+            .await?;
+
+        let result = response
+            .result
+            .ok_or_else(|| AppError::Internal("Count result is empty".to_string()))?;
+
+        Ok(result.count)
+    }
+
+    /// Triggers a snapshot of a namespace's memory collection, for operators
+    /// to back up before a risky migration or as part of a restore plan.
+    pub async fn create_snapshot(&self, namespace: Option<&str>) -> Result<SnapshotInfo, AppError> {
+        let collection_name = self.get_collection_name(namespace);
+        let response = self.client.create_snapshot(&collection_name).await?;
+        let description = response
+            .snapshot_description
+            .ok_or_else(|| AppError::Internal("Snapshot description is empty".to_string()))?;
+
+        Ok(SnapshotInfo {
+            name: description.name,
+            creation_time: description.creation_time.map(|t| t.to_string()),
+            size: description.size as u64,
+        })
+    }
+
+    /// Lists the snapshots currently stored for a namespace's collection.
+    pub async fn list_snapshots(&self, namespace: Option<&str>) -> Result<Vec<SnapshotInfo>, AppError> {
+        let collection_name = self.get_collection_name(namespace);
+        let response = self.client.list_snapshots(&collection_name).await?;
+
+        Ok(response
+            .snapshot_descriptions
+            .into_iter()
+            .map(|description| SnapshotInfo {
+                name: description.name,
+                creation_time: description.creation_time.map(|t| t.to_string()),
+                size: description.size as u64,
+            })
+            .collect())
+    }
+
+    /// Triggers a full-storage snapshot covering every collection (memories
+    /// for every namespace plus the documents collection), for whole-instance
+    /// backups rather than a single namespace.
+    pub async fn create_full_snapshot(&self) -> Result<SnapshotInfo, AppError> {
+        let response = self.client.create_full_snapshot().await?;
+        let description = response
+            .snapshot_description
+            .ok_or_else(|| AppError::Internal("Snapshot description is empty".to_string()))?;
+
+        Ok(SnapshotInfo {
+            name: description.name,
+            creation_time: description.creation_time.map(|t| t.to_string()),
+            size: description.size as u64,
+        })
+    }
+
+    /// Restores a namespace's memory collection from a previously created
+    /// snapshot by name, e.g. after corruption or as the final step of a
+    /// migration. The collection is unavailable for writes while recovering.
+    pub async fn restore_from_snapshot(
+        &self,
+        namespace: Option<&str>,
+        snapshot_name: &str,
+    ) -> Result<(), AppError> {
+        let collection_name = self.get_collection_name(namespace);
+        self.client
+            .recover_snapshot(&collection_name, snapshot_name)
+            .await?;
+        Ok(())
+    }
+
+    /// Paginated scroll (list) of memories for a user without vector search.
+    /// Uses Qdrant's scroll API, threading its opaque point-id offset token
+    /// through `offset`/the returned cursor so callers can page through
+    /// large result sets instead of pulling everything in one shot.
     pub async fn scroll_memories(
         &self,
         user_id: i64,
         category: Option<String>,
         limit: u64,
         namespace: Option<&str>,
-    ) -> Result<Vec<(String, MemoryPayload)>, AppError> {
+        offset: Option<String>,
+        filter_expr: Option<&str>,
+    ) -> Result<(Vec<(String, MemoryPayload)>, Option<String>), AppError> {
         // Build filter for user_id and optional category
         let mut must_conditions = vec![Condition::matches("user_id", user_id)];
 
@@ -345,24 +1709,26 @@ impl QdrantService {
         if let Some(cat) = category {
             must_conditions.push(Condition::matches("category", cat));
         }
+        push_filter_expr(&mut must_conditions, filter_expr)?;
 
         let filter = Filter {
             must: must_conditions,
             ..Default::default()
         };
 
-        // Use scroll API to retrieve all matching points
+        // Use scroll API to retrieve one page of matching points
         let collection_name = self.get_collection_name(namespace);
-        let scroll_result = self
-            .client
-            .scroll(
-                qdrant_client::qdrant::ScrollPointsBuilder::new(&collection_name)
-                    .filter(filter)
-                    .limit(limit as u32)
-                    .with_payload(true)
-                    .with_vectors(false), // We don't need vectors for listing
-            )
-            .await?;
+        let mut builder = qdrant_client::qdrant::ScrollPointsBuilder::new(&collection_name)
+            .filter(filter)
+            .limit(limit as u32)
+            .with_payload(true)
+            .with_vectors(false); // We don't need vectors for listing
+        if let Some(cursor) = offset {
+            builder = builder.offset(cursor_to_point_id(&cursor));
+        }
+
+        let scroll_result = self.client.scroll(builder).await?;
+        let next_offset = scroll_result.next_page_offset.map(point_id_to_cursor);
 
         let mut results = Vec::new();
 
@@ -396,17 +1762,19 @@ impl QdrantService {
                 .map_err(|e| {
                     AppError::Internal(format!("Failed to deserialize payload: {}", e))
                 })?;
+            let memory_payload = self.decrypt_memory_payload(memory_payload)?;
 
             results.push((point_id, memory_payload));
         }
 
         debug!(
-            "Scroll found {} memories for user {}",
+            "Scroll found {} memories for user {} (more pages: {})",
             results.len(),
-            user_id
+            user_id,
+            next_offset.is_some()
         );
 
-        Ok(results)
+        Ok((results, next_offset))
     }
 
     fn get_collection_name(&self, namespace: Option<&str>) -> String {
@@ -436,46 +1804,299 @@ impl QdrantService {
             }
         }
 
-        output.trim_matches('_').to_string()
+        output.trim_matches('_').to_string()
+    }
+
+    /// Upsert a single document
+    pub async fn upsert_document(
+        &self,
+        point_id: &str,
+        vector: &[f32],
+        payload: &DocumentPayload,
+    ) -> Result<(), AppError> {
+        self.upsert_document_with_sparse(point_id, vector, None, payload).await
+    }
+
+    /// Encrypts the payload and builds the `PointStruct` a document upsert
+    /// sends to Qdrant. Shared by the single-point and batch upsert paths so
+    /// both stay in sync.
+    fn build_document_point(
+        &self,
+        point_id: &str,
+        vector: Vec<f32>,
+        sparse_vector: Option<SparseVector>,
+        payload: DocumentPayload,
+    ) -> Result<PointStruct, AppError> {
+        let payload = self.encrypt_document_payload(payload)?;
+
+        let mut payload_map = serde_json::to_value(&payload)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize payload: {}", e)))?
+            .as_object()
+            .cloned()
+            .unwrap_or_default();
+
+        // Store original string ID for retrieval
+        payload_map.insert("_point_id".to_string(), json!(point_id));
+
+        let payload_qdrant = Payload::try_from(serde_json::Value::Object(payload_map))
+            .map_err(|e| AppError::Internal(format!("Failed to convert payload: {}", e)))?;
+
+        let pid = PointId {
+            point_id_options: Some(PointIdOptions::Num(string_to_point_id(point_id))),
+        };
+
+        Ok(match sparse_vector {
+            Some(sparse) => {
+                let vectors = NamedVectors::default()
+                    .add_vector("", Vector::new_dense(vector))
+                    .add_vector(
+                        SPARSE_VECTOR_NAME,
+                        Vector::new_sparse(sparse.indices, sparse.values),
+                    );
+                PointStruct::new(pid, vectors, payload_qdrant)
+            }
+            None => PointStruct::new(pid, vector, payload_qdrant),
+        })
+    }
+
+    /// Upsert a document with an optional sparse (lexical) vector alongside
+    /// the dense one, so it can later be found via hybrid search.
+    pub async fn upsert_document_with_sparse(
+        &self,
+        point_id: &str,
+        vector: &[f32],
+        sparse_vector: Option<SparseVector>,
+        payload: &DocumentPayload,
+    ) -> Result<(), AppError> {
+        let collection = &self.documents_collection_name;
+        let point = self.build_document_point(point_id, vector.to_vec(), sparse_vector, payload.clone())?;
+
+        self.client
+            .upsert_points(
+                UpsertPointsBuilder::new(collection, vec![point])
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Upsert a document chunk, deduplicated on content. The point ID is
+    /// derived from a SHA-256 hash of `(payload.user_id, payload.text)`
+    /// rather than supplied by the caller, so identical chunk content
+    /// appearing in different files *for the same user* always maps to the
+    /// same point. `user_id` is part of the key - not just a field checked
+    /// after the fact - so two different users uploading identical plaintext
+    /// never share a point or see each other's `file_id` in `ref_files`,
+    /// matching every other document path's per-user scoping. If a point for
+    /// that content already exists, `payload.file_id` is appended to its
+    /// `ref_files` list instead of inserting a duplicate; otherwise a new
+    /// point is created with `ref_files: vec![payload.file_id]`. Returns the
+    /// derived point ID.
+    pub async fn upsert_document_deduped(
+        &self,
+        vector: &[f32],
+        sparse_vector: Option<SparseVector>,
+        payload: DocumentPayload,
+    ) -> Result<String, AppError> {
+        let mut hasher = Sha256::new();
+        hasher.update(payload.user_id.to_le_bytes());
+        hasher.update(b"\0");
+        hasher.update(payload.text.as_bytes());
+        let point_id = format!("chunk_{:x}", hasher.finalize());
+        let numeric_id = string_to_point_id(&point_id);
+        let pid = PointId {
+            point_id_options: Some(PointIdOptions::Num(numeric_id)),
+        };
+
+        let collection = &self.documents_collection_name;
+        let existing = self
+            .client
+            .get_points(GetPointsBuilder::new(collection, vec![pid.clone()]).with_payload(true))
+            .await?
+            .result
+            .into_iter()
+            .next();
+
+        let merged_payload = match existing {
+            Some(point) => {
+                let payload_json = serde_json::to_value(&point.payload).map_err(|e| {
+                    AppError::Internal(format!("Failed to serialize payload: {}", e))
+                })?;
+                let existing_payload: DocumentPayload = serde_json::from_value(payload_json)
+                    .map_err(|e| {
+                        AppError::Internal(format!("Failed to deserialize payload: {}", e))
+                    })?;
+                let mut existing_payload = self.decrypt_document_payload(existing_payload)?;
+                if !existing_payload.ref_files.contains(&payload.file_id) {
+                    existing_payload.ref_files.push(payload.file_id);
+                }
+                existing_payload
+            }
+            None => DocumentPayload {
+                ref_files: vec![payload.file_id],
+                ..payload
+            },
+        };
+
+        let point = self.build_document_point(&point_id, vector.to_vec(), sparse_vector, merged_payload)?;
+        self.client
+            .upsert_points(UpsertPointsBuilder::new(collection, vec![point]))
+            .await?;
+
+        Ok(point_id)
     }
 
-    /// Upsert a single document
-    pub async fn upsert_document(
-        &self,
-        point_id: &str,
-        vector: &[f32],
-        payload: &DocumentPayload,
-    ) -> Result<(), AppError> {
-        let collection = &self.documents_collection_name;
+    /// Removes `file_id`'s reference to a deduplicated chunk, deleting the
+    /// point only once no file references it any more. No-op (returns
+    /// `Ok(())`) if the point doesn't exist.
+    pub async fn remove_document_ref(&self, point_id: &str, file_id: i64) -> Result<(), AppError> {
         let numeric_id = string_to_point_id(point_id);
+        let pid = PointId {
+            point_id_options: Some(PointIdOptions::Num(numeric_id)),
+        };
+
+        let collection = &self.documents_collection_name;
+        let Some(point) = self
+            .client
+            .get_points(GetPointsBuilder::new(collection, vec![pid.clone()]).with_payload(true))
+            .await?
+            .result
+            .into_iter()
+            .next()
+        else {
+            return Ok(());
+        };
+
+        let payload_json = serde_json::to_value(&point.payload)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize payload: {}", e)))?;
+        let payload: DocumentPayload = serde_json::from_value(payload_json)
+            .map_err(|e| AppError::Internal(format!("Failed to deserialize payload: {}", e)))?;
+        let mut payload = self.decrypt_document_payload(payload)?;
+        payload.ref_files.retain(|&id| id != file_id);
+
+        if payload.ref_files.is_empty() {
+            self.client
+                .delete_points(DeletePointsBuilder::new(collection).points(vec![pid]))
+                .await?;
+            return Ok(());
+        }
 
-        let mut payload_map = serde_json::to_value(payload)
+        let payload = self.encrypt_document_payload(payload)?;
+        let payload_map = serde_json::to_value(&payload)
             .map_err(|e| AppError::Internal(format!("Failed to serialize payload: {}", e)))?
             .as_object()
             .cloned()
             .unwrap_or_default();
-        
-        // Store original string ID for retrieval
-        payload_map.insert("_point_id".to_string(), json!(point_id));
-
-        let point = PointStruct::new(
-            PointId {
-                point_id_options: Some(PointIdOptions::Num(numeric_id)),
-            },
-            vector.to_vec(),
-            Payload::try_from(serde_json::Value::Object(payload_map))
-                .map_err(|e| AppError::Internal(format!("Failed to convert payload: {}", e)))?,
-        );
+        let qdrant_payload = Payload::try_from(serde_json::Value::Object(payload_map))
+            .map_err(|e| AppError::Internal(format!("Failed to convert payload: {}", e)))?;
 
+        let filter = Filter::must(vec![Condition::matches("_point_id", point_id.to_string())]);
         self.client
-            .upsert_points(
-                UpsertPointsBuilder::new(collection, vec![point])
-            )
+            .set_payload(collection, &filter.into(), qdrant_payload, None)
             .await?;
 
         Ok(())
     }
 
+    /// Removes `file_id`'s reference from every deduplicated chunk it
+    /// touches, scrolling points whose `ref_files` contains `file_id` and
+    /// calling [`Self::remove_document_ref`] on each. Returns the number of
+    /// chunks `file_id` referenced (whether a chunk was deleted outright or
+    /// just had the reference dropped), not the number of points deleted.
+    pub async fn remove_document_refs_for_file(&self, user_id: i64, file_id: i64) -> Result<u64, AppError> {
+        let collection = &self.documents_collection_name;
+        let filter = Filter::must(vec![
+            Condition::matches("user_id", user_id),
+            Condition::matches("ref_files", file_id),
+        ]);
+
+        let mut removed: u64 = 0;
+        let mut offset = None;
+
+        loop {
+            let scrolled = self
+                .client
+                .scroll(
+                    ScrollPointsBuilder::new(collection)
+                        .filter(filter.clone())
+                        .limit(BATCH_CHUNK_SIZE as u32)
+                        .offset(offset)
+                        .with_payload(true)
+                        .with_vectors(false),
+                )
+                .await?;
+
+            if scrolled.result.is_empty() {
+                break;
+            }
+
+            for point in &scrolled.result {
+                let Some(point_id) = point
+                    .payload
+                    .get("_point_id")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+                else {
+                    continue;
+                };
+                self.remove_document_ref(&point_id, file_id).await?;
+                removed += 1;
+            }
+
+            offset = scrolled.next_page_offset;
+            if offset.is_none() {
+                break;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Upsert many document chunks in as few round trips as possible.
+    /// Points are sent in chunks of [`BATCH_CHUNK_SIZE`] via a single
+    /// `UpsertPointsBuilder` call each, instead of one round trip per point.
+    pub async fn upsert_documents_batch(
+        &self,
+        items: Vec<DocumentUpsertItem>,
+    ) -> Vec<(String, Result<(), AppError>)> {
+        let mut results = Vec::with_capacity(items.len());
+        let mut points = Vec::with_capacity(items.len());
+
+        for item in items {
+            match self.build_document_point(&item.point_id, item.vector, item.sparse_vector, item.payload) {
+                Ok(point) => points.push((item.point_id, point)),
+                Err(e) => results.push((item.point_id, Err(e))),
+            }
+        }
+
+        let collection = &self.documents_collection_name;
+        let mut points = points.into_iter().peekable();
+        while points.peek().is_some() {
+            let (chunk_ids, chunk_points): (Vec<String>, Vec<PointStruct>) =
+                points.by_ref().take(BATCH_CHUNK_SIZE).unzip();
+
+            match self
+                .client
+                .upsert_points(UpsertPointsBuilder::new(collection, chunk_points))
+                .await
+            {
+                Ok(_) => results.extend(chunk_ids.into_iter().map(|id| (id, Ok(())))),
+                Err(e) => {
+                    let message = AppError::from(e).to_string();
+                    results.extend(
+                        chunk_ids
+                            .into_iter()
+                            .map(|id| (id, Err(AppError::Internal(message.clone())))),
+                    );
+                }
+            }
+        }
+
+        debug!("Batch upserted {} documents", results.len());
+
+        results
+    }
+
     /// Search documents with user isolation
     pub async fn search_documents(
         &self,
@@ -484,6 +2105,7 @@ impl QdrantService {
         group_key: Option<&str>,
         limit: u64,
         min_score: f32,
+        filter_expr: Option<&str>,
     ) -> Result<Vec<DocumentSearchResult>, AppError> {
         let collection = &self.documents_collection_name;
 
@@ -495,6 +2117,7 @@ impl QdrantService {
         if let Some(gk) = group_key {
             conditions.push(Condition::matches("group_key", gk.to_string()));
         }
+        push_filter_expr(&mut conditions, filter_expr)?;
 
         let filter = Filter::must(conditions);
 
@@ -518,6 +2141,7 @@ impl QdrantService {
                 .map_err(|e| {
                     AppError::Internal(format!("Failed to deserialize payload: {}", e))
                 })?;
+            let payload = self.decrypt_document_payload(payload)?;
 
             let id = p.payload.get("_point_id")
                 .and_then(|v| v.as_str())
@@ -529,12 +2153,209 @@ impl QdrantService {
                 score: p.score,
                 payload,
                 vector: None,
+                modality: "dense".to_string(),
             });
         }
 
         Ok(doc_results)
     }
 
+    /// Hybrid dense + sparse search over documents, fused with Reciprocal
+    /// Rank Fusion. See [`Self::search_memories_hybrid`] for the fusion
+    /// details.
+    pub async fn search_documents_hybrid(
+        &self,
+        query_vector: &[f32],
+        query_sparse_vector: SparseVector,
+        user_id: i64,
+        group_key: Option<&str>,
+        limit: u64,
+        min_score: f32,
+        filter_expr: Option<&str>,
+    ) -> Result<Vec<DocumentSearchResult>, AppError> {
+        let collection = &self.documents_collection_name;
+
+        let mut conditions = vec![Condition::matches("user_id", user_id)];
+        if let Some(gk) = group_key {
+            conditions.push(Condition::matches("group_key", gk.to_string()));
+        }
+        push_filter_expr(&mut conditions, filter_expr)?;
+        let filter = Filter::must(conditions);
+
+        let fetch_limit = (limit * 4).max(limit);
+
+        let dense_result = self
+            .client
+            .search_points(
+                SearchPointsBuilder::new(collection, query_vector.to_vec(), fetch_limit)
+                    .filter(filter.clone())
+                    .score_threshold(min_score)
+                    .with_payload(true),
+            )
+            .await?;
+
+        let sparse_result = self
+            .client
+            .search_points(
+                SearchPointsBuilder::new(collection, query_sparse_vector.values, fetch_limit)
+                    .sparse_indices(query_sparse_vector.indices)
+                    .vector_name(SPARSE_VECTOR_NAME)
+                    .filter(filter)
+                    .score_threshold(min_score)
+                    .with_payload(true),
+            )
+            .await?;
+
+        let mut fused: HashMap<String, (f64, DocumentPayload, bool, bool)> = HashMap::new();
+
+        for (rank, p) in dense_result.result.into_iter().enumerate() {
+            let (id, payload) = document_scored_point(p)?;
+            let entry = fused.entry(id).or_insert((0.0, payload, false, false));
+            entry.0 += rrf_score(rank + 1, 1.0);
+            entry.2 = true;
+        }
+
+        for (rank, p) in sparse_result.result.into_iter().enumerate() {
+            let (id, payload) = document_scored_point(p)?;
+            let entry = fused.entry(id).or_insert((0.0, payload, false, false));
+            entry.0 += rrf_score(rank + 1, 1.0);
+            entry.3 = true;
+        }
+
+        let mut doc_results: Vec<DocumentSearchResult> = fused
+            .into_iter()
+            .map(|(id, (score, payload, in_dense, in_sparse))| {
+                let modality = match (in_dense, in_sparse) {
+                    (true, true) => "hybrid",
+                    (true, false) => "dense",
+                    (false, true) => "sparse",
+                    (false, false) => unreachable!("fused entry always comes from one of the two lists"),
+                };
+                let payload = self.decrypt_document_payload(payload)?;
+                Ok(DocumentSearchResult {
+                    id,
+                    score: score as f32,
+                    payload,
+                    vector: None,
+                    modality: modality.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        doc_results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        doc_results.truncate(limit as usize);
+
+        Ok(doc_results)
+    }
+
+    /// Document-flavored counterpart to [`Self::search_memories_semantic_lexical`]:
+    /// hybrid dense + lexical search fused with weighted Reciprocal Rank
+    /// Fusion, matching `query_text` against the full-text index on the
+    /// `text` field. See that method's doc comment for the ranking
+    /// approximation and normalization details, including the dense-only
+    /// fallback when `MEMORY_ENCRYPTION_ENABLED` leaves that field unindexed.
+    pub async fn search_documents_semantic_lexical(
+        &self,
+        query_vector: &[f32],
+        query_text: &str,
+        semantic_ratio: f32,
+        user_id: i64,
+        group_key: Option<&str>,
+        limit: u64,
+        min_score: f32,
+        filter_expr: Option<&str>,
+    ) -> Result<Vec<DocumentSearchResult>, AppError> {
+        let collection = &self.documents_collection_name;
+
+        let lexical_available = self.cipher.is_none();
+        let semantic_weight = if lexical_available { semantic_ratio.clamp(0.0, 1.0) as f64 } else { 1.0 };
+        let lexical_weight = 1.0 - semantic_weight;
+
+        let mut conditions = vec![Condition::matches("user_id", user_id)];
+        if let Some(gk) = group_key {
+            conditions.push(Condition::matches("group_key", gk.to_string()));
+        }
+        push_filter_expr(&mut conditions, filter_expr)?;
+        let filter = Filter::must(conditions.clone());
+
+        let fetch_limit = (limit * 4).max(limit);
+
+        let dense_result = self
+            .client
+            .search_points(
+                SearchPointsBuilder::new(collection, query_vector.to_vec(), fetch_limit)
+                    .filter(filter)
+                    .score_threshold(min_score)
+                    .with_payload(true),
+            )
+            .await?;
+
+        let lexical_result = if lexical_available {
+            let mut lexical_conditions = conditions;
+            lexical_conditions.push(Condition::matches_text("text", query_text.to_string()));
+            let lexical_filter = Filter::must(lexical_conditions);
+            self.client
+                .scroll(
+                    ScrollPointsBuilder::new(collection)
+                        .filter(lexical_filter)
+                        .limit(fetch_limit as u32)
+                        .with_payload(true)
+                        .with_vectors(false),
+                )
+                .await?
+                .result
+        } else {
+            warn!(
+                "Skipping lexical leg of hybrid document search on '{}': memory_encryption_enabled has no text index to match against",
+                collection
+            );
+            Vec::new()
+        };
+
+        let mut fused: HashMap<String, (f64, DocumentPayload, bool, bool)> = HashMap::new();
+
+        for (rank, p) in dense_result.result.into_iter().enumerate() {
+            let (id, payload) = document_scored_point(p)?;
+            let entry = fused.entry(id).or_insert((0.0, payload, false, false));
+            entry.0 += rrf_score(rank + 1, semantic_weight);
+            entry.2 = true;
+        }
+
+        for (rank, p) in lexical_result.into_iter().enumerate() {
+            let (id, payload) = retrieved_point_to_document(p)?;
+            let entry = fused.entry(id).or_insert((0.0, payload, false, false));
+            entry.0 += rrf_score(rank + 1, lexical_weight);
+            entry.3 = true;
+        }
+
+        let max_possible = rrf_score(1, semantic_weight) + rrf_score(1, lexical_weight);
+
+        let mut doc_results: Vec<DocumentSearchResult> = fused
+            .into_iter()
+            .map(|(id, (score, payload, in_dense, in_lexical))| {
+                let modality = match (in_dense, in_lexical) {
+                    (true, true) => "hybrid",
+                    (true, false) => "semantic",
+                    (false, true) => "lexical",
+                    (false, false) => unreachable!("fused entry always comes from one of the two lists"),
+                };
+                let payload = self.decrypt_document_payload(payload)?;
+                Ok(DocumentSearchResult {
+                    id,
+                    score: (score / max_possible) as f32,
+                    payload,
+                    vector: None,
+                    modality: modality.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        doc_results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        doc_results.truncate(limit as usize);
+
+        Ok(doc_results)
+    }
+
     /// Get document by ID
     pub async fn get_document(&self, point_id: &str) -> Result<Option<DocumentSearchResult>, AppError> {
         let numeric_id = string_to_point_id(point_id);
@@ -561,6 +2382,7 @@ impl QdrantService {
                 .map_err(|e| {
                     AppError::Internal(format!("Failed to deserialize payload: {}", e))
                 })?;
+            let payload = self.decrypt_document_payload(payload)?;
 
             // Extract vector
             let vector = point.vectors.and_then(|v| {
@@ -575,6 +2397,7 @@ impl QdrantService {
                 score: 1.0, // Exact match
                 payload,
                 vector,
+                modality: "dense".to_string(),
             }));
         }
 
@@ -708,7 +2531,12 @@ impl QdrantService {
         let mut total_chunks = 0u64;
         let mut file_ids = std::collections::HashSet::new();
         let mut chunks_by_group: std::collections::HashMap<String, u64> = std::collections::HashMap::new();
-        
+        // Grouped by content hash, mirroring how backup tools report
+        // deduplication savings: one group per distinct chunk content,
+        // regardless of how many points currently store that content.
+        let mut chunks_by_content_hash: std::collections::HashMap<String, (u64, u64)> =
+            std::collections::HashMap::new();
+
         let mut offset = None;
         loop {
             let results = self.client
@@ -724,14 +2552,25 @@ impl QdrantService {
 
             for point in &results.result {
                 total_chunks += 1;
-                
+
                 if let Some(file_id) = point.payload.get("file_id").and_then(|v| v.as_i64()) {
                     file_ids.insert(file_id);
                 }
-                
+
                 if let Some(group_key) = point.payload.get("group_key").and_then(|v| v.as_str()) {
                     *chunks_by_group.entry(group_key.to_string()).or_insert(0) += 1;
                 }
+
+                if let Some(text) = point.payload.get("text").and_then(|v| v.as_str()) {
+                    // Hash plaintext, not ciphertext: encrypt_field uses a fresh
+                    // nonce per call, so identical plaintexts never match as stored.
+                    let text = decrypt_text_field(self.cipher.as_ref(), user_id, text)?;
+                    let mut hasher = Sha256::new();
+                    hasher.update(text.as_bytes());
+                    let content_hash = format!("{:x}", hasher.finalize());
+                    let entry = chunks_by_content_hash.entry(content_hash).or_insert((0, text.len() as u64));
+                    entry.0 += 1;
+                }
             }
 
             offset = results.next_page_offset;
@@ -740,14 +2579,194 @@ impl QdrantService {
             }
         }
 
+        let unique_chunks = chunks_by_content_hash.len() as u64;
+        let bytes_saved = chunks_by_content_hash
+            .values()
+            .map(|(count, byte_len)| count.saturating_sub(1) * byte_len)
+            .sum();
+
         Ok(DocumentStatsResponse {
             total_chunks,
             total_files: file_ids.len() as u64,
             total_groups: chunks_by_group.len() as u64,
             chunks_by_group,
+            unique_chunks,
+            bytes_saved,
         })
     }
 
+    /// Same result as [`Self::get_document_stats`], computed by partitioning
+    /// the `file_id` keyspace into `shards` disjoint ranges and scrolling
+    /// each range concurrently, the way a multi-threaded bucket index fans
+    /// work across drives and combines per-bucket results. Worth it on
+    /// large collections where a single sequential `limit(1000)` scroll is
+    /// the bottleneck; `shards` of `1` behaves like the sequential version.
+    pub async fn get_document_stats_parallel(
+        &self,
+        user_id: i64,
+        shards: usize,
+    ) -> Result<DocumentStatsResponse, AppError> {
+        let shards = shards.max(1);
+        let collection = self.documents_collection_name.clone();
+
+        // The keyspace partitioning only needs to roughly balance shards;
+        // correctness doesn't depend on this bound being tight, since the
+        // first and last shard's open-ended range still covers every file_id
+        // outside it.
+        const ASSUMED_MAX_FILE_ID: f64 = 1_000_000_000.0;
+        let bucket_width = ASSUMED_MAX_FILE_ID / shards as f64;
+
+        let mut handles = Vec::with_capacity(shards);
+        for shard in 0..shards {
+            let client = self.client.clone();
+            let collection = collection.clone();
+            let cipher = self.cipher.clone();
+            let gte = (shard > 0).then(|| bucket_width * shard as f64);
+            let lt = (shard + 1 < shards).then(|| bucket_width * (shard + 1) as f64);
+
+            handles.push(tokio::spawn(async move {
+                Self::scan_document_stats_shard(client, collection, cipher, user_id, gte, lt).await
+            }));
+        }
+
+        let mut merged = StatsPartial::default();
+        for handle in handles {
+            let partial = handle
+                .await
+                .map_err(|e| AppError::Internal(format!("Stats shard task panicked: {}", e)))??;
+            merged = merged.merge(partial);
+        }
+
+        let unique_chunks = merged.chunks_by_content_hash.len() as u64;
+        let bytes_saved = merged
+            .chunks_by_content_hash
+            .values()
+            .map(|(count, byte_len)| count.saturating_sub(1) * byte_len)
+            .sum();
+
+        Ok(DocumentStatsResponse {
+            total_chunks: merged.total_chunks,
+            total_files: merged.file_ids.len() as u64,
+            total_groups: merged.chunks_by_group.len() as u64,
+            chunks_by_group: merged.chunks_by_group,
+            unique_chunks,
+            bytes_saved,
+        })
+    }
+
+    /// Scrolls one `file_id` range (`[gte, lt)`, either bound optionally
+    /// open) to completion, accumulating the same counts
+    /// [`Self::get_document_stats`] does for the whole collection.
+    async fn scan_document_stats_shard(
+        client: Qdrant,
+        collection: String,
+        cipher: Option<crate::crypto::PayloadCipher>,
+        user_id: i64,
+        gte: Option<f64>,
+        lt: Option<f64>,
+    ) -> Result<StatsPartial, AppError> {
+        let filter = Filter::must(vec![
+            Condition::matches("user_id", user_id),
+            Condition::range(
+                "file_id",
+                Range {
+                    gte,
+                    lt,
+                    gt: None,
+                    lte: None,
+                },
+            ),
+        ]);
+
+        let mut partial = StatsPartial::default();
+        let mut offset = None;
+        loop {
+            let results = client
+                .scroll(
+                    ScrollPointsBuilder::new(&collection)
+                        .filter(filter.clone())
+                        .limit(1000)
+                        .offset(offset)
+                        .with_payload(true)
+                        .with_vectors(false),
+                )
+                .await?;
+
+            for point in &results.result {
+                partial.total_chunks += 1;
+
+                if let Some(file_id) = point.payload.get("file_id").and_then(|v| v.as_i64()) {
+                    partial.file_ids.insert(file_id);
+                }
+
+                if let Some(group_key) = point.payload.get("group_key").and_then(|v| v.as_str()) {
+                    *partial.chunks_by_group.entry(group_key.to_string()).or_insert(0) += 1;
+                }
+
+                if let Some(text) = point.payload.get("text").and_then(|v| v.as_str()) {
+                    let text = decrypt_text_field(cipher.as_ref(), user_id, text)?;
+                    let mut hasher = Sha256::new();
+                    hasher.update(text.as_bytes());
+                    let content_hash = format!("{:x}", hasher.finalize());
+                    let entry = partial
+                        .chunks_by_content_hash
+                        .entry(content_hash)
+                        .or_insert((0, text.len() as u64));
+                    entry.0 += 1;
+                }
+            }
+
+            offset = results.next_page_offset;
+            if offset.is_none() || results.result.is_empty() {
+                break;
+            }
+        }
+
+        Ok(partial)
+    }
+
+    /// Runs a declarative [`crate::aggregation::AggregationRequest`] over a
+    /// user's document chunks in a single scroll pass. Unlike
+    /// [`Self::get_document_stats`]'s fixed set of counts, callers compose
+    /// whatever nested buckets and metrics they need (see
+    /// [`crate::aggregation`] for the available aggregation types).
+    pub async fn aggregate(
+        &self,
+        user_id: i64,
+        request: &crate::aggregation::AggregationRequest,
+    ) -> Result<HashMap<String, crate::aggregation::AggregationResult>, AppError> {
+        let collection = &self.documents_collection_name;
+        let filter = Filter::must(vec![Condition::matches("user_id", user_id)]);
+
+        let mut payloads = Vec::new();
+        let mut offset = None;
+        loop {
+            let results = self.client
+                .scroll(
+                    ScrollPointsBuilder::new(collection)
+                        .filter(filter.clone())
+                        .limit(1000)
+                        .offset(offset)
+                        .with_payload(true)
+                        .with_vectors(false),
+                )
+                .await?;
+
+            for point in &results.result {
+                let value = serde_json::to_value(&point.payload)
+                    .map_err(|e| AppError::Internal(format!("Failed to serialize payload: {}", e)))?;
+                payloads.push(value);
+            }
+
+            offset = results.next_page_offset;
+            if offset.is_none() || results.result.is_empty() {
+                break;
+            }
+        }
+
+        Ok(crate::aggregation::run_aggregation(request, payloads.into_iter()))
+    }
+
     /// Get distinct group keys
     pub async fn get_document_group_keys(&self, user_id: i64) -> Result<Vec<String>, AppError> {
         let stats = self.get_document_stats(user_id).await?;
@@ -783,4 +2802,61 @@ mod tests {
         let expected_dim = 1024_u64;
         assert_ne!(wrong_vector.len(), expected_dim as usize);
     }
+
+    #[test]
+    fn test_rrf_score_decreases_with_rank() {
+        assert!(rrf_score(1, 1.0) > rrf_score(2, 1.0));
+        assert!(rrf_score(2, 1.0) > rrf_score(10, 1.0));
+    }
+
+    #[test]
+    fn test_rrf_score_scales_with_weight() {
+        assert_eq!(rrf_score(1, 0.5), rrf_score(1, 1.0) / 2.0);
+        assert_eq!(rrf_score(1, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_rrf_fuses_ranks_from_both_lists() {
+        // A point ranked 1st in both lists should score higher than one
+        // ranked 1st in only one of them.
+        let both = rrf_score(1, 1.0) + rrf_score(1, 1.0);
+        let one_only = rrf_score(1, 1.0);
+        assert!(both > one_only);
+    }
+
+    #[test]
+    fn test_cursor_round_trips_through_numeric_point_id() {
+        let original = PointId {
+            point_id_options: Some(PointIdOptions::Num(42)),
+        };
+        let cursor = point_id_to_cursor(original);
+        assert_eq!(cursor, "42");
+        let restored = cursor_to_point_id(&cursor);
+        assert_eq!(restored.point_id_options, Some(PointIdOptions::Num(42)));
+    }
+
+    #[test]
+    fn test_cursor_round_trips_through_uuid_point_id() {
+        let uuid = "4b9f5b3e-7b9a-4b9a-9b9a-7b9a4b9a9b9a".to_string();
+        let original = PointId {
+            point_id_options: Some(PointIdOptions::Uuid(uuid.clone())),
+        };
+        let cursor = point_id_to_cursor(original);
+        assert_eq!(cursor, uuid);
+        let restored = cursor_to_point_id(&cursor);
+        assert_eq!(restored.point_id_options, Some(PointIdOptions::Uuid(uuid)));
+    }
+
+    #[test]
+    fn test_collection_schema_round_trips_through_json() {
+        let schema = CollectionSchema {
+            version: SCHEMA_VERSION,
+            vector_dimension: 1024,
+            distance: "Cosine".to_string(),
+        };
+        let value = serde_json::to_value(&schema).unwrap();
+        let parsed: CollectionSchema = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed.vector_dimension, 1024);
+        assert_eq!(parsed.version, SCHEMA_VERSION);
+    }
 }
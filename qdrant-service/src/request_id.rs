@@ -7,7 +7,7 @@
 //!
 //! **Usage:**
 //! - Client can send `X-Request-ID` (we'll use it)
-//! - If not present, we generate a new UUIDv4
+//! - If not present, we generate one via the configured [`MakeRequestId`]
 //! - All logs include the request ID
 //! - Response includes `X-Request-ID` header
 //!
@@ -23,49 +23,480 @@
 //! Response includes:
 //!   X-Request-ID: abc-123
 //! ```
+//!
+//! **Outbound propagation:** [`current()`] exposes the in-flight request's id
+//! from a task-local, and [`propagate_header`] stamps it onto outbound
+//! `reqwest` calls (e.g. the Ollama embedder). The Qdrant gRPC client doesn't
+//! expose a per-call metadata hook through the high-level builders used in
+//! `qdrant.rs`, so its calls aren't tagged yet — the trace currently ends at
+//! the last outbound HTTP hop, not at Qdrant itself.
+//!
+//! **W3C Trace Context:** alongside `X-Request-ID`, the middleware reads and
+//! emits a standard `traceparent` header so this service is interoperable
+//! with any OpenTelemetry-compatible backend. An inherited trace-id is kept
+//! across hops; a fresh span-id is minted per hop and becomes the parent-id
+//! handed to whatever we call downstream via [`propagate_header`]. Both ids
+//! are recorded on the current span as `trace_id`/`span_id`, so logs carry
+//! the human-friendly `request_id` and the OTel-compatible `trace_id` side
+//! by side.
+
+use std::convert::Infallible;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 
 use axum::{
-    extract::Request,
-    http::{header::HeaderName, HeaderValue},
+    extract::{FromRequestParts, Request, State},
+    http::{header::HeaderName, request::Parts, HeaderMap, HeaderValue},
     middleware::Next,
     response::Response,
 };
+use opentelemetry::propagation::Extractor;
+use tokio::task_local;
 use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 use uuid::Uuid;
 
 /// Header name for request ID
 static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
 
+/// W3C Trace Context header carrying `version-trace_id-parent_id-flags`.
+static TRACEPARENT_HEADER: HeaderName = HeaderName::from_static("traceparent");
+
+/// Only version `00` of the W3C spec is defined; we only ever emit that one.
+const TRACEPARENT_VERSION: &str = "00";
+/// "Sampled" flag bit set, since we always record and export spans.
+const TRACEPARENT_FLAGS: &str = "01";
+
+/// This hop's view of the W3C trace: the (possibly inherited) trace-id and a
+/// span-id minted fresh for this hop, reused as the parent-id we hand to
+/// whatever we call downstream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TraceContext {
+    trace_id: String,
+    span_id: String,
+}
+
+impl TraceContext {
+    fn traceparent_header(&self) -> String {
+        format!(
+            "{}-{}-{}-{}",
+            TRACEPARENT_VERSION, self.trace_id, self.span_id, TRACEPARENT_FLAGS
+        )
+    }
+}
+
+/// Extracts the 128-bit trace-id from an incoming `traceparent` header
+/// (`version-trace_id-parent_id-flags`). The parent-id dies at this hop since
+/// we mint our own span-id; version and flags aren't acted on yet.
+fn parse_traceparent_trace_id(value: &str) -> Option<String> {
+    let mut parts = value.split('-');
+    let _version = parts.next()?;
+    let trace_id = parts.next()?;
+    let _parent_id = parts.next()?;
+    let _flags = parts.next()?;
+    if trace_id.len() == 32 && trace_id.bytes().all(|b| b.is_ascii_hexdigit()) {
+        Some(trace_id.to_ascii_lowercase())
+    } else {
+        None
+    }
+}
+
+/// Synthesizes a fresh 128-bit trace-id for a request with no `traceparent`.
+fn generate_trace_id() -> String {
+    format!("{:032x}", Uuid::new_v4().as_u128())
+}
+
+/// Mints a 64-bit span-id for this hop.
+fn generate_span_id() -> String {
+    let (_, low) = Uuid::new_v4().as_u64_pair();
+    format!("{:016x}", low)
+}
+
+/// Typed wrapper around the per-request ID, stored in request extensions by
+/// [`request_id_middleware`]. A newtype (rather than a bare `String`) avoids
+/// colliding with any other `String` extension and lets handlers pull it out
+/// with a plain `RequestId` argument instead of reaching into extensions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(String);
+
+impl RequestId {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for RequestId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<RequestId> for String {
+    fn from(id: RequestId) -> Self {
+        id.0
+    }
+}
+
+/// Extracts the [`RequestId`] stashed in request extensions by
+/// `request_id_middleware`. If the middleware isn't installed (or ran before
+/// the ID existed), lazily generates a fresh UUIDv4 so the extractor always
+/// succeeds, matching the ergonomics of the actix/gotham request-id crates.
+impl<S> FromRequestParts<S> for RequestId
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(id) = parts.extensions.get::<RequestId>() {
+            return Ok(id.clone());
+        }
+        let id = RequestId::new(Uuid::new_v4().to_string());
+        parts.extensions.insert(id.clone());
+        Ok(id)
+    }
+}
+
+task_local! {
+    /// The [`RequestId`] of the request currently being handled on this task,
+    /// set by [`request_id_middleware`] for the lifetime of `next.run(..)`.
+    /// Lets outbound calls (see [`propagate_header`]) forward the same id
+    /// without every function on the call path taking it as a parameter.
+    static CURRENT_REQUEST_ID: RequestId;
+
+    /// This hop's W3C trace context, set by [`request_id_middleware`]
+    /// alongside [`CURRENT_REQUEST_ID`] for the lifetime of `next.run(..)`.
+    static CURRENT_TRACE_CONTEXT: TraceContext;
+}
+
+/// Returns the request ID of the request currently being handled, if any.
+///
+/// Only set while inside [`request_id_middleware`]'s call to the next layer
+/// (i.e. for the duration of a single request). Returns `None` outside of
+/// request handling, e.g. in background jobs or at startup.
+pub fn current() -> Option<RequestId> {
+    CURRENT_REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Returns the W3C trace-id of the request currently being handled, if any.
+pub fn current_trace_id() -> Option<String> {
+    CURRENT_TRACE_CONTEXT.try_with(|tc| tc.trace_id.clone()).ok()
+}
+
+/// Injects the current request's `X-Request-ID` and `traceparent` (if any)
+/// onto an outbound `reqwest` call, so both the human-friendly correlation
+/// id and the OpenTelemetry-compatible trace linkage carry across to
+/// downstream services as advertised by this module's
+/// "Backend → Microservice → Qdrant" tracing promise.
+pub fn propagate_header(builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    let builder = match current() {
+        Some(id) => builder.header(REQUEST_ID_HEADER.as_str(), id.as_ref()),
+        None => builder,
+    };
+    match CURRENT_TRACE_CONTEXT.try_with(|tc| tc.traceparent_header()).ok() {
+        Some(traceparent) => builder.header(TRACEPARENT_HEADER.as_str(), traceparent),
+        None => builder,
+    }
+}
+
+/// Generates a request ID for a request that didn't arrive with one.
+///
+/// Implementations are expected to be cheap and callable from inside the
+/// request-handling hot path; any shared mutable state (a counter, an RNG)
+/// should be held behind an `Arc` internally so cloning the generator is
+/// free and its instances can be shared across a `Mutex` in [`RequestIdMaker`].
+pub trait MakeRequestId: Send + Sync {
+    fn make_request_id(&mut self, req: &Request) -> String;
+}
+
+/// Default generator, matching the crate's original behavior: a random UUIDv4.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MakeRequestUuid;
+
+impl MakeRequestId for MakeRequestUuid {
+    fn make_request_id(&mut self, _req: &Request) -> String {
+        Uuid::new_v4().to_string()
+    }
+}
+
+/// Monotonically increasing counter, formatted as a plain decimal string.
+/// Cheap and strictly ordered, which makes it easy to eyeball request order
+/// in logs on a single node; not meaningful across a multi-node deployment.
+#[derive(Debug, Clone, Default)]
+pub struct MakeRequestCounter {
+    next: Arc<AtomicU64>,
+}
+
+impl MakeRequestId for MakeRequestCounter {
+    fn make_request_id(&mut self, _req: &Request) -> String {
+        self.next.fetch_add(1, Ordering::Relaxed).to_string()
+    }
+}
+
+/// Short, URL-safe base62 ID (no dashes), friendlier than a UUID in log
+/// greps and Qdrant correlation IDs while still being effectively unique.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MakeRequestNanoid;
+
+const BASE62_ALPHABET: &[u8; 62] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+/// 12 base62 characters give ~71 bits of entropy, comparable to a UUIDv4.
+const NANOID_LENGTH: usize = 12;
+
+impl MakeRequestId for MakeRequestNanoid {
+    fn make_request_id(&mut self, _req: &Request) -> String {
+        let random_bytes = Uuid::new_v4();
+        random_bytes
+            .as_bytes()
+            .iter()
+            .cycle()
+            .take(NANOID_LENGTH)
+            .map(|b| BASE62_ALPHABET[*b as usize % BASE62_ALPHABET.len()] as char)
+            .collect()
+    }
+}
+
+/// Router state wrapping the configured [`MakeRequestId`] so a custom
+/// generator can be plugged in from the binary (`main.rs`) without the
+/// middleware itself needing to be generic.
+#[derive(Clone)]
+pub struct RequestIdMaker(Arc<Mutex<Box<dyn MakeRequestId>>>);
+
+impl RequestIdMaker {
+    pub fn new(maker: impl MakeRequestId + 'static) -> Self {
+        Self(Arc::new(Mutex::new(Box::new(maker))))
+    }
+
+    /// Builds the maker named by `config.request_id_strategy` ("uuid",
+    /// "counter", or "nanoid"), falling back to the UUID generator for an
+    /// unrecognized value.
+    pub fn from_strategy(strategy: &str) -> Self {
+        match strategy {
+            "counter" => Self::new(MakeRequestCounter::default()),
+            "nanoid" => Self::new(MakeRequestNanoid),
+            _ => Self::new(MakeRequestUuid),
+        }
+    }
+
+    fn make(&self, req: &Request) -> String {
+        self.0.lock().unwrap().make_request_id(req)
+    }
+}
+
+impl Default for RequestIdMaker {
+    fn default() -> Self {
+        Self::new(MakeRequestUuid)
+    }
+}
+
+/// Whether an incoming `X-Request-ID` header is trusted and reused, or
+/// ignored in favor of one from the configured [`MakeRequestId`].
+///
+/// Blindly trusting a client-supplied ID lets it inject newlines/control
+/// characters into our logs (`[INFO] [<id>] ...`) or pass a megabyte-long
+/// string to waste memory, so reuse is validated (see [`sanitize_incoming_id`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdReuse {
+    UseIncoming,
+    IgnoreIncoming,
+}
+
+impl IdReuse {
+    pub fn from_strategy(value: &str) -> Self {
+        match value {
+            "ignore_incoming" => Self::IgnoreIncoming,
+            _ => Self::UseIncoming,
+        }
+    }
+}
+
+/// Policy controlling how the middleware is configured: whether it reuses a
+/// client-supplied `X-Request-ID`, and whether an invalid one is rejected
+/// with `400` or silently replaced with a freshly generated ID.
+#[derive(Debug, Clone, Copy)]
+pub struct RequestIdPolicy {
+    pub id_reuse: IdReuse,
+    pub reject_invalid: bool,
+}
+
+impl Default for RequestIdPolicy {
+    fn default() -> Self {
+        Self {
+            id_reuse: IdReuse::UseIncoming,
+            reject_invalid: false,
+        }
+    }
+}
+
+/// Maximum accepted length, in bytes, of an incoming `X-Request-ID` value.
+const MAX_INCOMING_ID_LEN: usize = 128;
+
+/// Validates a client-supplied request ID: must be non-empty, at most
+/// [`MAX_INCOMING_ID_LEN`] bytes, and composed only of `[A-Za-z0-9_-]` (which
+/// also rules out newlines/control characters and guarantees a valid
+/// `HeaderValue`).
+fn sanitize_incoming_id(value: &str) -> Option<&str> {
+    if value.is_empty() || value.len() > MAX_INCOMING_ID_LEN {
+        return None;
+    }
+    if !value.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-') {
+        return None;
+    }
+    Some(value)
+}
+
+/// Router state for [`request_id_middleware`]: the ID generator plus the
+/// trust policy for a client-supplied `X-Request-ID`.
+#[derive(Clone, Default)]
+pub struct RequestIdState {
+    pub maker: RequestIdMaker,
+    pub policy: RequestIdPolicy,
+}
+
+impl RequestIdState {
+    pub fn new(maker: RequestIdMaker, policy: RequestIdPolicy) -> Self {
+        Self { maker, policy }
+    }
+}
+
+/// Adapts an axum `HeaderMap` to the `opentelemetry` `Extractor` trait so an
+/// incoming W3C `traceparent` header can be turned into a parent span context.
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl<'a> Extractor for HeaderExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|k| k.as_str()).collect()
+    }
+}
+
+/// [`tower_http::trace::TraceLayer::make_span_with`] implementation for the
+/// span [`request_id_middleware`] annotates.
+///
+/// `tracing::Span::record` can only set a field that was already declared at
+/// span-creation time, so the plain `DefaultMakeSpan` this replaces made
+/// every `span.record("request_id", ...)` (and `trace_id`/`span_id`/
+/// `client_cert_cn`) below a silent no-op. Declaring them here as
+/// [`tracing::field::Empty`] is what makes those `record` calls actually
+/// attach to the span that ends up in logs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestSpan;
+
+impl<B> tower_http::trace::MakeSpan<B> for RequestSpan {
+    fn make_span(&mut self, request: &Request<B>) -> Span {
+        tracing::debug_span!(
+            "request",
+            method = %request.method(),
+            uri = %request.uri(),
+            version = ?request.version(),
+            request_id = tracing::field::Empty,
+            trace_id = tracing::field::Empty,
+            span_id = tracing::field::Empty,
+            client_cert_cn = tracing::field::Empty,
+        )
+    }
+}
+
 /// Request ID middleware
 ///
 /// Extracts or generates a request ID and adds it to:
 /// 1. Response headers
 /// 2. Tracing span (for logs)
-pub async fn request_id_middleware(mut request: Request, next: Next) -> Response {
-    // Extract existing request ID or generate new one
-    let request_id = request
+///
+/// Also joins an existing distributed trace if the caller sent a W3C
+/// `traceparent` header, rather than always starting a fresh root span.
+///
+/// An incoming `X-Request-ID` is only reused when `policy.id_reuse` is
+/// [`IdReuse::UseIncoming`] *and* the value passes [`sanitize_incoming_id`];
+/// an invalid value is either rejected with `400` or silently replaced,
+/// depending on `policy.reject_invalid`.
+pub async fn request_id_middleware(
+    State(state): State<RequestIdState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, axum::http::StatusCode> {
+    let incoming = request
         .headers()
         .get(&REQUEST_ID_HEADER)
-        .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string())
-        .unwrap_or_else(|| Uuid::new_v4().to_string());
+        .and_then(|v| v.to_str().ok());
+
+    let request_id = match (state.policy.id_reuse, incoming) {
+        (IdReuse::UseIncoming, Some(raw)) => match sanitize_incoming_id(raw) {
+            Some(valid) => valid.to_string(),
+            None if state.policy.reject_invalid => {
+                return Err(axum::http::StatusCode::BAD_REQUEST);
+            }
+            None => state.maker.make(&request),
+        },
+        _ => state.maker.make(&request),
+    };
 
     // Add request ID to tracing span
     let span = Span::current();
     span.record("request_id", &request_id.as_str());
 
+    // When mutual TLS is in use, log the verified client cert's CN alongside
+    // the request ID (axum inserts `ConnectInfo<ClientCertInfo>` into the
+    // request extensions per-connection when mTLS is configured).
+    #[cfg(feature = "tls")]
+    if let Some(axum::extract::ConnectInfo(cert_info)) = request
+        .extensions()
+        .get::<axum::extract::ConnectInfo<crate::ClientCertInfo>>()
+        .cloned()
+    {
+        if let Some(cn) = cert_info.common_name.as_deref() {
+            span.record("client_cert_cn", cn);
+        }
+    }
+
+    // Join an existing trace if a W3C traceparent header is present.
+    let parent_context = opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(request.headers()))
+    });
+    span.set_parent(parent_context);
+
+    // Reuse an inherited trace-id if present, otherwise start a new trace;
+    // either way this hop gets its own fresh span-id. `request_id` is our
+    // human-friendly correlation key, `trace_id` is the OTel-compatible one.
+    let trace_id = request
+        .headers()
+        .get(&TRACEPARENT_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_traceparent_trace_id)
+        .unwrap_or_else(generate_trace_id);
+    let span_id = generate_span_id();
+    span.record("trace_id", &trace_id.as_str());
+    span.record("span_id", &span_id.as_str());
+    let trace_context = TraceContext { trace_id, span_id };
+
     // Store request ID in extensions for potential use in handlers
-    request.extensions_mut().insert(request_id.clone());
+    let id = RequestId::new(request_id.clone());
+    request.extensions_mut().insert(id.clone());
 
-    // Call next middleware/handler
-    let mut response = next.run(request).await;
+    // Run the rest of the stack inside the task-local scopes so any outbound
+    // call made while handling this request (see `propagate_header`) can
+    // pick up the same id and trace context without threading them through
+    // every function signature.
+    let mut response = CURRENT_REQUEST_ID
+        .scope(id, CURRENT_TRACE_CONTEXT.scope(trace_context, next.run(request)))
+        .await;
 
     // Add request ID to response headers
     if let Ok(header_value) = HeaderValue::from_str(&request_id) {
         response.headers_mut().insert(REQUEST_ID_HEADER.clone(), header_value);
     }
 
-    response
+    Ok(response)
 }
 
 #[cfg(test)]
@@ -79,11 +510,37 @@ mod tests {
     };
     use tower::ServiceExt;
 
+    fn app_with_maker(maker: RequestIdMaker) -> Router {
+        app_with_state(RequestIdState::new(maker, RequestIdPolicy::default()))
+    }
+
+    fn app_with_state(state: RequestIdState) -> Router {
+        Router::new()
+            .route("/test", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn_with_state(
+                state,
+                request_id_middleware,
+            ))
+    }
+
+    #[test]
+    fn test_request_span_predeclares_record_fields() {
+        // `Span::record` silently no-ops on a field that wasn't declared at
+        // span-creation time, so this locks in the fields
+        // `request_id_middleware` later calls `span.record(...)` with.
+        let mut make_span = RequestSpan;
+        let request = Request::builder().uri("/test").body(Body::empty()).unwrap();
+        let span = tower_http::trace::MakeSpan::make_span(&mut make_span, &request);
+
+        let fields = span.metadata().unwrap().fields();
+        for name in ["request_id", "trace_id", "span_id", "client_cert_cn"] {
+            assert!(fields.field(name).is_some(), "span should declare `{}`", name);
+        }
+    }
+
     #[tokio::test]
     async fn test_request_id_generated_if_missing() {
-        let app = Router::new()
-            .route("/test", get(|| async { "ok" }))
-            .layer(axum::middleware::from_fn(request_id_middleware));
+        let app = app_with_maker(RequestIdMaker::default());
 
         let response = app
             .oneshot(Request::builder().uri("/test").body(Body::empty()).unwrap())
@@ -101,9 +558,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_request_id_propagated_from_client() {
-        let app = Router::new()
-            .route("/test", get(|| async { "ok" }))
-            .layer(axum::middleware::from_fn(request_id_middleware));
+        let app = app_with_maker(RequestIdMaker::default());
 
         let client_id = "client-request-123";
         let response = app
@@ -122,5 +577,290 @@ mod tests {
         let request_id = response.headers().get("x-request-id").unwrap();
         assert_eq!(request_id.to_str().unwrap(), client_id);
     }
+
+    #[tokio::test]
+    async fn test_request_id_counter_is_monotonic() {
+        let app = app_with_maker(RequestIdMaker::new(MakeRequestCounter::default()));
+
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            let response = app
+                .clone()
+                .oneshot(Request::builder().uri("/test").body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let id: u64 = response
+                .headers()
+                .get("x-request-id")
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .parse()
+                .unwrap();
+            ids.push(id);
+        }
+
+        assert!(ids.windows(2).all(|w| w[1] > w[0]), "ids should increase: {ids:?}");
+    }
+
+    #[tokio::test]
+    async fn test_request_id_nanoid_is_short_and_url_safe() {
+        let app = app_with_maker(RequestIdMaker::new(MakeRequestNanoid));
+
+        let response = app
+            .oneshot(Request::builder().uri("/test").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let id_str = response
+            .headers()
+            .get("x-request-id")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        assert_eq!(id_str.len(), NANOID_LENGTH);
+        assert!(id_str.chars().all(|c| c.is_ascii_alphanumeric()));
+    }
+
+    #[test]
+    fn test_sanitize_incoming_id_accepts_valid() {
+        assert_eq!(sanitize_incoming_id("client-request-123"), Some("client-request-123"));
+    }
+
+    #[test]
+    fn test_sanitize_incoming_id_rejects_control_chars() {
+        assert_eq!(sanitize_incoming_id("abc\ndef"), None);
+    }
+
+    #[test]
+    fn test_sanitize_incoming_id_rejects_too_long() {
+        let long = "a".repeat(MAX_INCOMING_ID_LEN + 1);
+        assert_eq!(sanitize_incoming_id(&long), None);
+    }
+
+    #[tokio::test]
+    async fn test_ignore_incoming_always_generates_fresh_id() {
+        let state = RequestIdState::new(
+            RequestIdMaker::default(),
+            RequestIdPolicy {
+                id_reuse: IdReuse::IgnoreIncoming,
+                reject_invalid: false,
+            },
+        );
+        let app = app_with_state(state);
+
+        let client_id = "client-request-123";
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/test")
+                    .header("x-request-id", client_id)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let request_id = response.headers().get("x-request-id").unwrap().to_str().unwrap();
+        assert_ne!(request_id, client_id);
+        assert!(Uuid::parse_str(request_id).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_incoming_id_falls_back_when_not_rejecting() {
+        let state = RequestIdState::new(
+            RequestIdMaker::default(),
+            RequestIdPolicy {
+                id_reuse: IdReuse::UseIncoming,
+                reject_invalid: false,
+            },
+        );
+        let app = app_with_state(state);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/test")
+                    .header("x-request-id", "a".repeat(MAX_INCOMING_ID_LEN + 1))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let request_id = response.headers().get("x-request-id").unwrap().to_str().unwrap();
+        assert!(Uuid::parse_str(request_id).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_incoming_id_rejected_with_400_when_configured() {
+        let state = RequestIdState::new(
+            RequestIdMaker::default(),
+            RequestIdPolicy {
+                id_reuse: IdReuse::UseIncoming,
+                reject_invalid: true,
+            },
+        );
+        let app = app_with_state(state);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/test")
+                    .header("x-request-id", "bad id with spaces")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_request_id_extractor_reads_value_set_by_middleware() {
+        async fn handler(id: RequestId) -> String {
+            id.to_string()
+        }
+        let app = Router::new()
+            .route("/test", get(handler))
+            .layer(axum::middleware::from_fn_with_state(
+                RequestIdState::default(),
+                request_id_middleware,
+            ));
+
+        let client_id = "client-request-123";
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/test")
+                    .header("x-request-id", client_id)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], client_id.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_request_id_extractor_generates_fallback_without_middleware() {
+        async fn handler(id: RequestId) -> String {
+            id.to_string()
+        }
+        let app = Router::new().route("/test", get(handler));
+
+        let response = app
+            .oneshot(Request::builder().uri("/test").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let id_str = std::str::from_utf8(&body).unwrap();
+        assert!(Uuid::parse_str(id_str).is_ok());
+    }
+
+    #[test]
+    fn test_current_is_none_outside_a_request() {
+        assert!(current().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_current_reflects_the_scoped_request_id() {
+        let id = RequestId::new("scoped-id".to_string());
+        let observed = CURRENT_REQUEST_ID
+            .scope(id.clone(), async { current() })
+            .await;
+        assert_eq!(observed, Some(id));
+    }
+
+    #[tokio::test]
+    async fn test_propagate_header_injects_current_request_id() {
+        let id = RequestId::new("downstream-id".to_string());
+        let request = CURRENT_REQUEST_ID
+            .scope(id, async {
+                propagate_header(reqwest::Client::new().get("http://example.invalid"))
+                    .build()
+                    .unwrap()
+            })
+            .await;
+
+        assert_eq!(
+            request
+                .headers()
+                .get("x-request-id")
+                .and_then(|v| v.to_str().ok()),
+            Some("downstream-id")
+        );
+    }
+
+    #[test]
+    fn test_propagate_header_is_noop_without_current_id() {
+        let request = propagate_header(reqwest::Client::new().get("http://example.invalid"))
+            .build()
+            .unwrap();
+        assert!(request.headers().get("x-request-id").is_none());
+    }
+
+    #[test]
+    fn test_parse_traceparent_trace_id_extracts_valid_id() {
+        let header = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+        assert_eq!(
+            parse_traceparent_trace_id(header),
+            Some("0af7651916cd43dd8448eb211c80319c".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_traceparent_trace_id_rejects_malformed_header() {
+        assert_eq!(parse_traceparent_trace_id("not-a-traceparent"), None);
+        assert_eq!(parse_traceparent_trace_id("00-tooshort-b7ad6b7169203331-01"), None);
+    }
+
+    #[test]
+    fn test_generate_trace_id_is_32_hex_chars() {
+        let id = generate_trace_id();
+        assert_eq!(id.len(), 32);
+        assert!(id.bytes().all(|b| b.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn test_generate_span_id_is_16_hex_chars() {
+        let id = generate_span_id();
+        assert_eq!(id.len(), 16);
+        assert!(id.bytes().all(|b| b.is_ascii_hexdigit()));
+    }
+
+    #[tokio::test]
+    async fn test_propagate_header_injects_traceparent_for_current_trace() {
+        let trace_context = TraceContext {
+            trace_id: "0af7651916cd43dd8448eb211c80319c".to_string(),
+            span_id: "b7ad6b7169203331".to_string(),
+        };
+        let request = CURRENT_TRACE_CONTEXT
+            .scope(trace_context, async {
+                propagate_header(reqwest::Client::new().get("http://example.invalid"))
+                    .build()
+                    .unwrap()
+            })
+            .await;
+
+        assert_eq!(
+            request
+                .headers()
+                .get("traceparent")
+                .and_then(|v| v.to_str().ok()),
+            Some("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01")
+        );
+    }
 }
 
@@ -17,6 +17,14 @@ pub struct StatsTracker {
     pub upserts: Arc<AtomicU64>,
     pub searches: Arc<AtomicU64>,
     pub deletes: Arc<AtomicU64>,
+    /// Never-reset counterparts of `upserts`/`searches`/`deletes`. The
+    /// report-facing fields above are zeroed by `reset()` after every daily
+    /// Discord report, but Prometheus scrapers expect `_total` counters to
+    /// be monotonic for the life of the process, so `render_prometheus`
+    /// reads from these instead.
+    upserts_total: Arc<AtomicU64>,
+    searches_total: Arc<AtomicU64>,
+    deletes_total: Arc<AtomicU64>,
     pub start_time: Instant,
 }
 
@@ -26,6 +34,9 @@ impl StatsTracker {
             upserts: Arc::new(AtomicU64::new(0)),
             searches: Arc::new(AtomicU64::new(0)),
             deletes: Arc::new(AtomicU64::new(0)),
+            upserts_total: Arc::new(AtomicU64::new(0)),
+            searches_total: Arc::new(AtomicU64::new(0)),
+            deletes_total: Arc::new(AtomicU64::new(0)),
             start_time: Instant::now(),
         }
     }
@@ -33,16 +44,19 @@ impl StatsTracker {
     #[inline]
     pub fn increment_upserts(&self, count: u64) {
         self.upserts.fetch_add(count, Ordering::Relaxed);
+        self.upserts_total.fetch_add(count, Ordering::Relaxed);
     }
 
     #[inline]
     pub fn increment_searches(&self) {
         self.searches.fetch_add(1, Ordering::Relaxed);
+        self.searches_total.fetch_add(1, Ordering::Relaxed);
     }
 
     #[inline]
     pub fn increment_deletes(&self) {
         self.deletes.fetch_add(1, Ordering::Relaxed);
+        self.deletes_total.fetch_add(1, Ordering::Relaxed);
     }
 
     pub fn get_snapshot(&self) -> StatsSnapshot {
@@ -59,6 +73,49 @@ impl StatsTracker {
         self.searches.store(0, Ordering::Relaxed);
         self.deletes.store(0, Ordering::Relaxed);
     }
+
+    /// Renders the never-reset counters (plus uptime) in Prometheus text
+    /// exposition format, the way Garage's `admin/metrics.rs` hand-formats
+    /// its counters/gauges for scraping. `collection`, if given, is
+    /// attached as a `collection="..."` label on every series so operators
+    /// running multiple collections can tell them apart.
+    pub fn render_prometheus(&self, collection: Option<&str>) -> String {
+        let label = collection
+            .map(|c| format!("{{collection=\"{c}\"}}"))
+            .unwrap_or_default();
+
+        let mut out = String::new();
+        for (name, help, value) in [
+            (
+                "synaplan_upserts_total",
+                "Total number of memory/document upserts",
+                self.upserts_total.load(Ordering::Relaxed),
+            ),
+            (
+                "synaplan_searches_total",
+                "Total number of search requests",
+                self.searches_total.load(Ordering::Relaxed),
+            ),
+            (
+                "synaplan_deletes_total",
+                "Total number of delete requests",
+                self.deletes_total.load(Ordering::Relaxed),
+            ),
+        ] {
+            out.push_str(&format!("# HELP {name} {help}\n"));
+            out.push_str(&format!("# TYPE {name} counter\n"));
+            out.push_str(&format!("{name}{label} {value}\n"));
+        }
+
+        out.push_str("# HELP synaplan_uptime_seconds Service uptime in seconds\n");
+        out.push_str("# TYPE synaplan_uptime_seconds gauge\n");
+        out.push_str(&format!(
+            "synaplan_uptime_seconds{label} {}\n",
+            self.start_time.elapsed().as_secs()
+        ));
+
+        out
+    }
 }
 
 impl Default for StatsTracker {
@@ -116,6 +173,33 @@ mod tests {
         assert_eq!(snapshot.deletes, 0);
     }
 
+    #[test]
+    fn test_prometheus_counters_survive_reset() {
+        let stats = StatsTracker::new();
+
+        stats.increment_upserts(5);
+        stats.increment_searches();
+        stats.reset();
+        stats.increment_deletes();
+
+        let rendered = stats.render_prometheus(None);
+        assert!(rendered.contains("synaplan_upserts_total 5"));
+        assert!(rendered.contains("synaplan_searches_total 1"));
+        assert!(rendered.contains("synaplan_deletes_total 1"));
+    }
+
+    #[test]
+    fn test_prometheus_rendering_has_help_and_type_and_label() {
+        let stats = StatsTracker::new();
+        stats.increment_upserts(1);
+
+        let rendered = stats.render_prometheus(Some("user_memories"));
+        assert!(rendered.contains("# HELP synaplan_upserts_total"));
+        assert!(rendered.contains("# TYPE synaplan_upserts_total counter"));
+        assert!(rendered.contains("synaplan_upserts_total{collection=\"user_memories\"} 1"));
+        assert!(rendered.contains("# TYPE synaplan_uptime_seconds gauge"));
+    }
+
     #[test]
     fn test_uptime_formatting() {
         let snapshot = StatsSnapshot {
@@ -0,0 +1,184 @@
+//! Binary wire format support for high-volume endpoints.
+//!
+//! JSON's float encoding roughly triples the size of a batch of 1024-dim
+//! vectors and costs real parse time on the hot path. [`Wire<T>`] lets those
+//! handlers additionally accept a compact `postcard`-encoded body (chosen via
+//! `Content-Type: application/x-postcard` or `application/octet-stream`,
+//! falling back to `serde_json` otherwise so existing JSON clients are
+//! unaffected), and [`Wired<T>`] mirrors the same negotiation for the
+//! response, chosen via the request's `Accept` header and carried alongside
+//! the request through the [`Accepts`] extractor.
+//!
+//! **Known limitation:** postcard isn't self-describing, so
+//! `#[serde(skip_serializing_if = "...")]` breaks the fixed-arity layout its
+//! derived `Deserialize` expects (the field is silently dropped instead of
+//! encoded as absent, desyncing every field after it). Response types
+//! reached via [`Wired`] must not use it. `MemoryPayload::message_id` and
+//! `DocumentPayload::ref_files` still do for the JSON API's sake, so
+//! `search_memories`/`search_documents` stay JSON-only on the response side
+//! for now; only the batch upsert responses (which don't touch those fields)
+//! negotiate via `Wired`.
+
+use axum::body::Bytes;
+use axum::extract::{FromRequest, FromRequestParts, Request};
+use axum::http::{header, request::Parts, HeaderMap};
+use axum::response::{IntoResponse, Response};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::error::AppError;
+
+/// MIME type negotiated for postcard-encoded bodies. `application/octet-stream`
+/// is treated the same way since it's the generic "opaque bytes" type many
+/// HTTP clients default to for binary payloads.
+const POSTCARD_CONTENT_TYPE: &str = "application/x-postcard";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireFormat {
+    Json,
+    Postcard,
+}
+
+impl WireFormat {
+    fn from_header_value(value: Option<&str>) -> Self {
+        match value {
+            Some(v) if v.starts_with(POSTCARD_CONTENT_TYPE) || v.starts_with("application/octet-stream") => {
+                WireFormat::Postcard
+            }
+            _ => WireFormat::Json,
+        }
+    }
+
+    fn from_content_type(headers: &HeaderMap) -> Self {
+        Self::from_header_value(headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()))
+    }
+
+    fn from_accept(headers: &HeaderMap) -> Self {
+        Self::from_header_value(headers.get(header::ACCEPT).and_then(|v| v.to_str().ok()))
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            WireFormat::Json => "application/json",
+            WireFormat::Postcard => POSTCARD_CONTENT_TYPE,
+        }
+    }
+}
+
+/// Extracts the negotiated response format from the request's `Accept`
+/// header, for handlers that return a [`Wired`] response.
+pub struct Accepts(pub WireFormat);
+
+impl<S> FromRequestParts<S> for Accepts
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Accepts(WireFormat::from_accept(&parts.headers)))
+    }
+}
+
+/// Request body extractor that deserializes from `serde_json` or `postcard`
+/// depending on the request's `Content-Type`. See the module doc comment.
+pub struct Wire<T>(pub T);
+
+impl<S, T> FromRequest<S> for Wire<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let format = WireFormat::from_content_type(req.headers());
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(|e| AppError::InvalidRequest(format!("Failed to read request body: {}", e)))?;
+
+        let value = match format {
+            WireFormat::Postcard => postcard::from_bytes(&bytes)
+                .map_err(|e| AppError::InvalidRequest(format!("Invalid postcard body: {}", e)))?,
+            WireFormat::Json => serde_json::from_slice(&bytes)
+                .map_err(|e| AppError::InvalidRequest(format!("Invalid JSON body: {}", e)))?,
+        };
+
+        Ok(Wire(value))
+    }
+}
+
+/// Response wrapper that serializes to `serde_json` or `postcard` depending
+/// on the [`WireFormat`] negotiated by [`Accepts`]. See the module doc
+/// comment for which response types are safe to wrap in this.
+pub struct Wired<T> {
+    pub format: WireFormat,
+    pub body: T,
+}
+
+impl<T> Wired<T> {
+    pub fn new(format: WireFormat, body: T) -> Self {
+        Self { format, body }
+    }
+}
+
+impl<T: Serialize> IntoResponse for Wired<T> {
+    fn into_response(self) -> Response {
+        match self.format {
+            WireFormat::Json => axum::Json(self.body).into_response(),
+            WireFormat::Postcard => match postcard::to_allocvec(&self.body) {
+                Ok(bytes) => {
+                    ([(header::CONTENT_TYPE, self.format.content_type())], bytes).into_response()
+                }
+                Err(e) => AppError::Internal(format!("Failed to encode postcard response: {}", e))
+                    .into_response(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Sample {
+        a: i32,
+        b: String,
+    }
+
+    #[test]
+    fn test_from_content_type_defaults_to_json() {
+        let headers = HeaderMap::new();
+        assert_eq!(WireFormat::from_content_type(&headers), WireFormat::Json);
+    }
+
+    #[test]
+    fn test_from_content_type_detects_postcard() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, "application/x-postcard".parse().unwrap());
+        assert_eq!(WireFormat::from_content_type(&headers), WireFormat::Postcard);
+    }
+
+    #[test]
+    fn test_from_content_type_detects_octet_stream() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, "application/octet-stream".parse().unwrap());
+        assert_eq!(WireFormat::from_content_type(&headers), WireFormat::Postcard);
+    }
+
+    #[test]
+    fn test_from_accept_detects_postcard() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, "application/x-postcard".parse().unwrap());
+        assert_eq!(WireFormat::from_accept(&headers), WireFormat::Postcard);
+    }
+
+    #[test]
+    fn test_postcard_roundtrip() {
+        let sample = Sample { a: 7, b: "hi".to_string() };
+        let bytes = postcard::to_allocvec(&sample).unwrap();
+        let decoded: Sample = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(sample, decoded);
+    }
+}